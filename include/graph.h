@@ -0,0 +1,32 @@
+/* C API of the graph crate, built with `cargo build --release --features ffi` */
+#ifndef GRAPH_H
+#define GRAPH_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct FfiGraph FfiGraph;
+
+FfiGraph *graph_new(void);
+void graph_free(FfiGraph *graph);
+
+/* 0 for a new vertex/edge, 1 when an existing label was replaced, -1 on error */
+int graph_insert_node(FfiGraph *graph, uint32_t vertex, const char *label);
+int graph_insert_edge(FfiGraph *graph, uint32_t from, uint32_t to, const char *label);
+
+/* fills up to capacity ids in BFS order, returns the number of vertices */
+size_t graph_bfs(const FfiGraph *graph, uint32_t *out, size_t capacity);
+
+/* NULL on error, release with graph_string_free */
+char *graph_serialize_tgf(const FfiGraph *graph);
+void graph_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
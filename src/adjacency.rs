@@ -0,0 +1,249 @@
+use crate::hash::HashMap;
+use crate::VertexId;
+use smallvec::SmallVec;
+use std::mem::size_of;
+
+// neighbors stored without a heap allocation, enough for the degree below 8 that most
+// vertices have
+pub(crate) const INLINE_CAPACITY: usize = 8;
+// above this many neighbors a linear scan loses to hashing
+pub(crate) const MAP_THRESHOLD: usize = 16;
+
+// outgoing edges of a single vertex: a small unordered list for the common low-degree
+// case that upgrades itself to a hash map once it outgrows MAP_THRESHOLD
+#[derive(Debug, Clone)]
+pub(crate) enum Adjacency<E> {
+    List(SmallVec<[(VertexId, E); INLINE_CAPACITY]>),
+    Map(HashMap<VertexId, E>),
+}
+
+impl<E> Default for Adjacency<E> {
+    fn default() -> Self {
+        Adjacency::List(SmallVec::new())
+    }
+}
+
+impl<E> Adjacency<E> {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Adjacency::List(list) => list.len(),
+            Adjacency::Map(map) => map.len(),
+        }
+    }
+
+    pub(crate) fn get(&self, vertex: &VertexId) -> Option<&E> {
+        match self {
+            Adjacency::List(list) => list.iter().find(|(v, _)| v == vertex).map(|(_, e)| e),
+            Adjacency::Map(map) => map.get(vertex),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, vertex: &VertexId) -> Option<&mut E> {
+        match self {
+            Adjacency::List(list) => list.iter_mut().find(|(v, _)| v == vertex).map(|(_, e)| e),
+            Adjacency::Map(map) => map.get_mut(vertex),
+        }
+    }
+
+    pub(crate) fn contains_key(&self, vertex: &VertexId) -> bool {
+        self.get(vertex).is_some()
+    }
+
+    pub(crate) fn insert(&mut self, vertex: VertexId, value: E) -> Option<E> {
+        match self {
+            Adjacency::List(list) => {
+                if let Some((_, e)) = list.iter_mut().find(|(v, _)| *v == vertex) {
+                    return Some(std::mem::replace(e, value));
+                }
+                if list.len() < MAP_THRESHOLD {
+                    list.push((vertex, value));
+                } else {
+                    let mut map: HashMap<VertexId, E> = list.drain(..).collect();
+                    map.insert(vertex, value);
+                    *self = Adjacency::Map(map);
+                }
+                None
+            }
+            Adjacency::Map(map) => map.insert(vertex, value),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, vertex: &VertexId) -> Option<E> {
+        match self {
+            Adjacency::List(list) => {
+                let pos = list.iter().position(|(v, _)| v == vertex)?;
+                Some(list.swap_remove(pos).1)
+            }
+            Adjacency::Map(map) => map.remove(vertex),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, E> {
+        match self {
+            Adjacency::List(list) => Iter::List(list.iter()),
+            Adjacency::Map(map) => Iter::Map(map.iter()),
+        }
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &VertexId> + '_ {
+        self.iter().map(|(v, _)| v)
+    }
+
+    // heap bytes of the entries and the part of it taken by the edge payloads
+    pub(crate) fn heap_bytes(&self) -> (usize, usize) {
+        match self {
+            Adjacency::List(list) if list.spilled() => (
+                list.capacity() * size_of::<(VertexId, E)>(),
+                list.capacity() * size_of::<E>(),
+            ),
+            Adjacency::List(_) => (0, 0),
+            Adjacency::Map(map) => {
+                let buckets = crate::memory::buckets(map.capacity());
+                (
+                    crate::memory::table_bytes(map.capacity(), size_of::<(VertexId, E)>()),
+                    buckets * size_of::<E>(),
+                )
+            }
+        }
+    }
+
+    // drops spare capacity and moves a map that got small again back to the list form
+    pub(crate) fn shrink_to_fit(&mut self) {
+        match self {
+            Adjacency::List(list) => list.shrink_to_fit(),
+            Adjacency::Map(map) if map.len() <= MAP_THRESHOLD => {
+                let mut list: SmallVec<_> = map.drain().collect();
+                list.shrink_to_fit();
+                *self = Adjacency::List(list);
+            }
+            Adjacency::Map(map) => map.shrink_to_fit(),
+        }
+    }
+}
+
+pub(crate) enum Iter<'a, E> {
+    List(std::slice::Iter<'a, (VertexId, E)>),
+    Map(std::collections::hash_map::Iter<'a, VertexId, E>),
+}
+
+impl<'a, E> Iterator for Iter<'a, E> {
+    type Item = (&'a VertexId, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::List(iter) => iter.next().map(|(v, e)| (v, e)),
+            Iter::Map(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::List(iter) => iter.size_hint(),
+            Iter::Map(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, E> IntoIterator for &'a Adjacency<E> {
+    type Item = (&'a VertexId, &'a E);
+    type IntoIter = Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub(crate) enum IntoIter<E> {
+    List(smallvec::IntoIter<[(VertexId, E); INLINE_CAPACITY]>),
+    Map(std::collections::hash_map::IntoIter<VertexId, E>),
+}
+
+impl<E> Iterator for IntoIter<E> {
+    type Item = (VertexId, E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::List(iter) => iter.next(),
+            IntoIter::Map(iter) => iter.next(),
+        }
+    }
+}
+
+impl<E> IntoIterator for Adjacency<E> {
+    type Item = (VertexId, E);
+    type IntoIter = IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Adjacency::List(list) => IntoIter::List(list.into_iter()),
+            Adjacency::Map(map) => IntoIter::Map(map.into_iter()),
+        }
+    }
+}
+
+impl<E> std::ops::Index<&VertexId> for Adjacency<E> {
+    type Output = E;
+
+    fn index(&self, vertex: &VertexId) -> &E {
+        self.get(vertex).expect("no edge to this vertex")
+    }
+}
+
+// equality ignores both the representation and the order of the neighbors
+impl<E: PartialEq> PartialEq for Adjacency<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(v, e)| other.get(v) == Some(e))
+    }
+}
+
+impl<E: Eq> Eq for Adjacency<E> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::adjacency::{Adjacency, INLINE_CAPACITY, MAP_THRESHOLD};
+
+    #[test]
+    fn low_degree_stays_inline() {
+        let mut adjacency = Adjacency::default();
+        for v in 0..INLINE_CAPACITY as u32 {
+            adjacency.insert(v, ());
+            assert_eq!(adjacency.heap_bytes(), (0, 0));
+        }
+        adjacency.insert(INLINE_CAPACITY as u32, ());
+        assert_ne!(adjacency.heap_bytes(), (0, 0));
+    }
+
+    #[test]
+    fn upgrade_and_shrink() {
+        let mut adjacency = Adjacency::default();
+        for v in 0..MAP_THRESHOLD as u32 {
+            assert_eq!(adjacency.insert(v, v * 10), None);
+        }
+        assert!(matches!(adjacency, Adjacency::List(_)));
+        assert_eq!(adjacency.insert(3, 33), Some(30));
+        assert_eq!(adjacency.insert(100, 1000), None);
+        assert!(matches!(adjacency, Adjacency::Map(_)));
+        assert_eq!(adjacency.len(), MAP_THRESHOLD + 1);
+        assert_eq!(adjacency[&3], 33);
+
+        assert_eq!(adjacency.remove(&100), Some(1000));
+        adjacency.shrink_to_fit();
+        assert!(matches!(adjacency, Adjacency::List(_)));
+        let mut keys: Vec<_> = adjacency.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, (0..MAP_THRESHOLD as u32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn equality_ignores_order() {
+        let mut a = Adjacency::default();
+        let mut b = Adjacency::default();
+        for v in 0..5 {
+            a.insert(v, v);
+            b.insert(4 - v, 4 - v);
+        }
+        assert_eq!(a, b);
+        b.insert(0, 7);
+        assert_ne!(a, b);
+    }
+}
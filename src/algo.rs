@@ -0,0 +1,228 @@
+use crate::hash::{HashMap, HashSet};
+use crate::traits::{Neighbors, NodeIndexable};
+use crate::{Graph, VertexId};
+use std::collections::VecDeque;
+
+// Kahn's algorithm, None when the graph has a cycle; ties are broken by index order
+pub fn topological_sort<G: Neighbors + NodeIndexable>(graph: &G) -> Option<Vec<VertexId>> {
+    let n = graph.node_bound();
+    let mut in_degree = vec![0usize; n];
+    for v in graph.vertex_ids() {
+        for to in graph.neighbors(v) {
+            in_degree[graph.index_of(to)?] += 1;
+        }
+    }
+    let mut queue: VecDeque<usize> = (0..n).filter(|i| in_degree[*i] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(index) = queue.pop_front() {
+        let vertex = graph.vertex_at(index);
+        order.push(vertex);
+        for to in graph.neighbors(vertex) {
+            let to = graph.index_of(to)?;
+            in_degree[to] -= 1;
+            if in_degree[to] == 0 {
+                queue.push_back(to);
+            }
+        }
+    }
+    (order.len() == n).then_some(order)
+}
+
+// structural checks, each stopping at the first vertex or edge that rules the structure
+// out; the empty graph is a DAG and a forest but neither connected nor a tree
+impl<V, E> Graph<V, E> {
+    // depth-first, an edge back to a vertex still on the stack closes a cycle
+    pub fn is_dag(&self) -> bool {
+        // false while the vertex is on the stack, true once everything below it is done
+        let mut finished: HashMap<VertexId, bool> = HashMap::default();
+        for start in self.vertices.keys() {
+            if finished.contains_key(start) {
+                continue;
+            }
+            finished.insert(*start, false);
+            let mut stack = vec![(*start, self.adj_list[start].keys())];
+            while let Some((vertex, next)) = stack.last_mut() {
+                match next.next() {
+                    Some(to) => match finished.get(to) {
+                        Some(false) => return false,
+                        Some(true) => {}
+                        None => {
+                            finished.insert(*to, false);
+                            stack.push((*to, self.adj_list[to].keys()));
+                        }
+                    },
+                    None => {
+                        finished.insert(*vertex, true);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // every vertex has at most one parent and each can be reached from a root; a cycle
+    // would be left unreached, its vertices having their only parent on the cycle
+    pub fn is_forest(&self) -> bool {
+        if self.incoming.values().any(|parents| parents.len() > 1) {
+            return false;
+        }
+        let mut stack: Vec<VertexId> = self
+            .incoming
+            .iter()
+            .filter(|(_, parents)| parents.is_empty())
+            .map(|(v, _)| *v)
+            .collect();
+        let mut reached = 0;
+        while let Some(vertex) = stack.pop() {
+            reached += 1;
+            stack.extend(self.adj_list[&vertex].keys());
+        }
+        reached == self.vertices.len()
+    }
+
+    // a single rooted tree, the graph as_tree accepts for its root: a forest with one
+    // edge fewer than vertices
+    pub fn is_tree(&self) -> bool {
+        !self.vertices.is_empty() && self.edge_count + 1 == self.vertices.len() && self.is_forest()
+    }
+
+    // weakly connected; answered by the connectivity index while it is up to date
+    pub fn is_connected(&self) -> bool {
+        if let Some(components) = self.cached_component_count() {
+            return components == 1;
+        }
+        let Some(start) = self.vertices.keys().next() else {
+            return false;
+        };
+        if self.edge_count + 1 < self.vertices.len() {
+            return false;
+        }
+        let mut seen = HashSet::default();
+        seen.insert(*start);
+        let mut stack = vec![*start];
+        while let Some(vertex) = stack.pop() {
+            for next in self.adj_list[&vertex].keys().chain(&self.incoming[&vertex]) {
+                if seen.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+        seen.len() == self.vertices.len()
+    }
+
+    // everything is reachable from one vertex and reaches it back
+    pub fn is_strongly_connected(&self) -> bool {
+        let Some(start) = self.vertices.keys().next() else {
+            return false;
+        };
+        if self.vertices.len() > 1
+            && self
+                .vertices
+                .keys()
+                .any(|v| self.adj_list[v].len() == 0 || self.incoming[v].is_empty())
+        {
+            return false;
+        }
+        let reaches_all = |forward: bool| {
+            let mut seen = HashSet::default();
+            seen.insert(*start);
+            let mut stack = vec![*start];
+            while let Some(vertex) = stack.pop() {
+                let next: Box<dyn Iterator<Item = &VertexId>> = if forward {
+                    Box::new(self.adj_list[&vertex].keys())
+                } else {
+                    Box::new(self.incoming[&vertex].iter())
+                };
+                for next in next {
+                    if seen.insert(*next) {
+                        stack.push(*next);
+                    }
+                }
+            }
+            seen.len() == self.vertices.len()
+        };
+        reaches_all(true) && reaches_all(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algo::topological_sort;
+    use crate::*;
+
+    #[test]
+    fn topological() {
+        let mut g = Graph::<(), ()>::new();
+        for v in [5, 3, 9, 1] {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(9, 3), ());
+        g.insert_edge(OrientedEdge(3, 1), ());
+        g.insert_edge(OrientedEdge(5, 1), ());
+        assert_eq!(topological_sort(&g.freeze()), Some(vec![5, 9, 3, 1]));
+        assert_eq!(topological_sort(&g), Some(vec![5, 9, 3, 1]));
+        g.insert_edge(OrientedEdge(1, 9), ());
+        assert_eq!(topological_sort(&g.freeze()), None);
+        assert_eq!(topological_sort(&g), None);
+        // leaving 3 out breaks the cycle
+        let sub = Subgraph::induced(&g, [1, 5, 9]);
+        assert_eq!(topological_sort(&sub), Some(vec![5, 1, 9]));
+        g.remove_node(3);
+        g.insert_node(0, ());
+        assert_eq!(topological_sort(&g), Some(vec![0, 5, 1, 9]));
+    }
+
+    #[test]
+    fn predicates() {
+        let empty = Graph::<(), ()>::new();
+        assert!(empty.is_dag() && empty.is_forest());
+        assert!(!empty.is_tree() && !empty.is_connected() && !empty.is_strongly_connected());
+
+        let tree = generators::binary_tree::<(), ()>(3);
+        assert!(tree.is_dag() && tree.is_forest() && tree.is_tree() && tree.is_connected());
+        assert!(!tree.is_strongly_connected());
+
+        let mut forest = tree.clone();
+        forest.remove_edge(OrientedEdge(0, 1));
+        assert!(forest.is_forest() && !forest.is_tree() && !forest.is_connected());
+
+        // one parent each, but 1 -> 3 -> 1 hangs off nothing
+        let mut g = Graph::<(), ()>::new();
+        for v in 0..4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(0, 2), ());
+        g.insert_edge(OrientedEdge(1, 3), ());
+        g.insert_edge(OrientedEdge(3, 1), ());
+        assert!(!g.is_forest() && !g.is_tree() && !g.is_dag());
+
+        // two parents for one vertex is still a DAG
+        let mut diamond = Graph::<(), ()>::new();
+        for v in 0..4 {
+            diamond.insert_node(v, ());
+        }
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3)] {
+            diamond.insert_edge(OrientedEdge(from, to), ());
+        }
+        assert!(diamond.is_dag() && !diamond.is_forest() && diamond.is_connected());
+        diamond.insert_edge(OrientedEdge(3, 0), ());
+        assert!(!diamond.is_dag() && diamond.is_strongly_connected());
+
+        let cycle = generators::cycle::<(), ()>(5);
+        assert!(cycle.is_strongly_connected() && !cycle.is_dag());
+        let mut looped = generators::path::<(), ()>(1);
+        assert!(looped.is_strongly_connected() && looped.is_dag());
+        looped.insert_edge(OrientedEdge(0, 0), ());
+        assert!(!looped.is_dag() && !looped.is_forest());
+
+        // through the connectivity index
+        let mut two = generators::path::<(), ()>(3);
+        assert_eq!(two.component_count(), 1);
+        assert!(two.is_connected());
+        two.insert_node(3, ());
+        assert!(!two.is_connected());
+        two.insert_edge(OrientedEdge(3, 0), ());
+        assert!(two.is_connected());
+    }
+}
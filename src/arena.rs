@@ -0,0 +1,504 @@
+use crate::hash::HashMap;
+use crate::{Graph, OrientedEdge, VertexId};
+use std::collections::VecDeque;
+
+const NONE: u32 = u32::MAX;
+
+// handles stay valid until their element is removed, a slot reused afterwards gets a new
+// generation so stale handles are rejected instead of pointing at someone else
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VertexHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+struct VertexSlot<V> {
+    generation: u32,
+    id: VertexId,
+    value: Option<V>,
+    first_out: u32,
+    first_in: u32,
+}
+
+#[derive(Debug, Clone)]
+struct EdgeSlot<E> {
+    generation: u32,
+    value: Option<E>,
+    from: u32,
+    to: u32,
+    next_out: u32,
+    next_in: u32,
+}
+
+// arena-backed alternative to Graph: vertices and edges live in contiguous vectors linked
+// into per-vertex outgoing/incoming lists, VertexId lookups are only needed by the
+// compatibility methods mirroring the Graph API
+#[derive(Debug, Clone)]
+pub struct ArenaGraph<V, E> {
+    vertices: Vec<VertexSlot<V>>,
+    edges: Vec<EdgeSlot<E>>,
+    free_vertices: Vec<u32>,
+    free_edges: Vec<u32>,
+    handles: HashMap<VertexId, VertexHandle>,
+    // None once u32::MAX is in use
+    next_id: Option<VertexId>,
+    edge_count: usize,
+}
+
+impl<V, E> Default for ArenaGraph<V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, E> ArenaGraph<V, E> {
+    pub fn new() -> ArenaGraph<V, E> {
+        ArenaGraph {
+            vertices: Vec::new(),
+            edges: Vec::new(),
+            free_vertices: Vec::new(),
+            free_edges: Vec::new(),
+            handles: HashMap::default(),
+            next_id: Some(0),
+            edge_count: 0,
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn vertex_slot(&self, handle: VertexHandle) -> Option<&VertexSlot<V>> {
+        self.vertices
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.value.is_some())
+    }
+
+    fn edge_slot(&self, handle: EdgeHandle) -> Option<&EdgeSlot<E>> {
+        self.edges
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.value.is_some())
+    }
+
+    fn vertex_handle(&self, index: u32) -> VertexHandle {
+        VertexHandle {
+            index,
+            generation: self.vertices[index as usize].generation,
+        }
+    }
+
+    fn edge_handle(&self, index: u32) -> EdgeHandle {
+        EdgeHandle {
+            index,
+            generation: self.edges[index as usize].generation,
+        }
+    }
+
+    // the vertex gets the next id above every id used so far, None when that would be
+    // past u32::MAX
+    pub fn add_vertex(&mut self, value: V) -> Option<VertexHandle> {
+        Some(self.add_vertex_with_id(self.next_id?, value))
+    }
+
+    fn add_vertex_with_id(&mut self, id: VertexId, value: V) -> VertexHandle {
+        let slot = VertexSlot {
+            generation: 0,
+            id,
+            value: Some(value),
+            first_out: NONE,
+            first_in: NONE,
+        };
+        let index = match self.free_vertices.pop() {
+            Some(index) => {
+                let generation = self.vertices[index as usize].generation;
+                self.vertices[index as usize] = VertexSlot { generation, ..slot };
+                index
+            }
+            None => {
+                self.vertices.push(slot);
+                (self.vertices.len() - 1) as u32
+            }
+        };
+        let handle = self.vertex_handle(index);
+        self.handles.insert(id, handle);
+        if self.next_id.is_some_and(|next| next <= id) {
+            self.next_id = id.checked_add(1);
+        }
+        handle
+    }
+
+    pub fn remove_vertex(&mut self, vertex: VertexHandle) -> Option<V> {
+        self.vertex_slot(vertex)?;
+        let index = vertex.index as usize;
+        while self.vertices[index].first_out != NONE {
+            self.unlink_edge(self.vertices[index].first_out);
+        }
+        while self.vertices[index].first_in != NONE {
+            self.unlink_edge(self.vertices[index].first_in);
+        }
+        let slot = &mut self.vertices[index];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.handles.remove(&slot.id);
+        self.free_vertices.push(vertex.index);
+        slot.value.take()
+    }
+
+    pub fn vertex(&self, vertex: VertexHandle) -> Option<&V> {
+        self.vertex_slot(vertex)?.value.as_ref()
+    }
+
+    pub fn vertex_mut(&mut self, vertex: VertexHandle) -> Option<&mut V> {
+        self.vertex_slot(vertex)?;
+        self.vertices[vertex.index as usize].value.as_mut()
+    }
+
+    pub fn id_of(&self, vertex: VertexHandle) -> Option<VertexId> {
+        Some(self.vertex_slot(vertex)?.id)
+    }
+
+    pub fn handle_of(&self, vertex: VertexId) -> Option<VertexHandle> {
+        self.handles.get(&vertex).copied()
+    }
+
+    pub fn vertex_handles(&self) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.vertices
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.value.is_some())
+            .map(|(index, slot)| VertexHandle {
+                index: index as u32,
+                generation: slot.generation,
+            })
+    }
+
+    fn outgoing(&self, index: u32) -> impl Iterator<Item = u32> + '_ {
+        let mut edge = self.vertices[index as usize].first_out;
+        std::iter::from_fn(move || {
+            let current = edge;
+            if current == NONE {
+                return None;
+            }
+            edge = self.edges[current as usize].next_out;
+            Some(current)
+        })
+    }
+
+    pub fn neighbors(&self, vertex: VertexHandle) -> impl Iterator<Item = VertexHandle> + '_ {
+        let edges = self
+            .vertex_slot(vertex)
+            .map(|_| self.outgoing(vertex.index));
+        edges
+            .into_iter()
+            .flatten()
+            .map(|edge| self.vertex_handle(self.edges[edge as usize].to))
+    }
+
+    pub fn find_edge(&self, from: VertexHandle, to: VertexHandle) -> Option<EdgeHandle> {
+        self.vertex_slot(from)?;
+        self.vertex_slot(to)?;
+        self.outgoing(from.index)
+            .find(|edge| self.edges[*edge as usize].to == to.index)
+            .map(|edge| self.edge_handle(edge))
+    }
+
+    // there's at most one edge per ordered pair, an existing edge just gets the new value
+    pub fn add_edge(
+        &mut self,
+        from: VertexHandle,
+        to: VertexHandle,
+        value: E,
+    ) -> Option<EdgeHandle> {
+        Some(self.upsert_edge(from, to, value)?.0)
+    }
+
+    fn upsert_edge(
+        &mut self,
+        from: VertexHandle,
+        to: VertexHandle,
+        value: E,
+    ) -> Option<(EdgeHandle, Option<E>)> {
+        if let Some(edge) = self.find_edge(from, to) {
+            let previous = self.edges[edge.index as usize].value.replace(value);
+            return Some((edge, previous));
+        }
+        self.vertex_slot(from)?;
+        self.vertex_slot(to)?;
+        let slot = EdgeSlot {
+            generation: 0,
+            value: Some(value),
+            from: from.index,
+            to: to.index,
+            next_out: self.vertices[from.index as usize].first_out,
+            next_in: self.vertices[to.index as usize].first_in,
+        };
+        let index = match self.free_edges.pop() {
+            Some(index) => {
+                let generation = self.edges[index as usize].generation;
+                self.edges[index as usize] = EdgeSlot { generation, ..slot };
+                index
+            }
+            None => {
+                self.edges.push(slot);
+                (self.edges.len() - 1) as u32
+            }
+        };
+        self.vertices[from.index as usize].first_out = index;
+        self.vertices[to.index as usize].first_in = index;
+        self.edge_count += 1;
+        Some((self.edge_handle(index), None))
+    }
+
+    pub fn remove_edge_by_handle(&mut self, edge: EdgeHandle) -> Option<E> {
+        self.edge_slot(edge)?;
+        self.unlink_edge(edge.index)
+    }
+
+    fn unlink_edge(&mut self, index: u32) -> Option<E> {
+        let (from, to, next_out, next_in) = {
+            let slot = &self.edges[index as usize];
+            (slot.from, slot.to, slot.next_out, slot.next_in)
+        };
+        if self.vertices[from as usize].first_out == index {
+            self.vertices[from as usize].first_out = next_out;
+        } else {
+            let mut current = self.vertices[from as usize].first_out;
+            while self.edges[current as usize].next_out != index {
+                current = self.edges[current as usize].next_out;
+            }
+            self.edges[current as usize].next_out = next_out;
+        }
+        if self.vertices[to as usize].first_in == index {
+            self.vertices[to as usize].first_in = next_in;
+        } else {
+            let mut current = self.vertices[to as usize].first_in;
+            while self.edges[current as usize].next_in != index {
+                current = self.edges[current as usize].next_in;
+            }
+            self.edges[current as usize].next_in = next_in;
+        }
+        let slot = &mut self.edges[index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_edges.push(index);
+        self.edge_count -= 1;
+        slot.value.take()
+    }
+
+    pub fn edge(&self, edge: EdgeHandle) -> Option<&E> {
+        self.edge_slot(edge)?.value.as_ref()
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeHandle) -> Option<(VertexHandle, VertexHandle)> {
+        let slot = self.edge_slot(edge)?;
+        Some((self.vertex_handle(slot.from), self.vertex_handle(slot.to)))
+    }
+}
+
+// compatibility layer with the same signatures as Graph
+impl<V, E> ArenaGraph<V, E> {
+    pub fn insert_node(&mut self, vertex_id: VertexId, value: V) -> Option<V> {
+        match self.handle_of(vertex_id) {
+            Some(handle) => self.vertices[handle.index as usize].value.replace(value),
+            None => {
+                self.add_vertex_with_id(vertex_id, value);
+                None
+            }
+        }
+    }
+
+    pub fn remove_node(&mut self, vertex_id: VertexId) -> Option<V> {
+        self.remove_vertex(self.handle_of(vertex_id)?)
+    }
+
+    // an id without a handle was never added or has been removed, which drops the edge
+    pub fn insert_edge(&mut self, edge: OrientedEdge, value: E) -> Option<E> {
+        let (from, to) = (self.handle_of(edge.0)?, self.handle_of(edge.1)?);
+        self.upsert_edge(from, to, value)?.1
+    }
+
+    pub fn remove_edge(&mut self, edge: OrientedEdge) -> Option<E> {
+        let found = self.find_edge(self.handle_of(edge.0)?, self.handle_of(edge.1)?)?;
+        self.remove_edge_by_handle(found)
+    }
+
+    pub fn get_adjacents(&self, vertex: VertexId) -> Option<Vec<&VertexId>> {
+        let handle = self.handle_of(vertex)?;
+        Some(
+            self.outgoing(handle.index)
+                .map(|edge| &self.vertices[self.edges[edge as usize].to as usize].id)
+                .collect(),
+        )
+    }
+
+    pub fn get_vertex_value(&self, vertex: VertexId) -> Option<&V> {
+        self.vertex(self.handle_of(vertex)?)
+    }
+
+    pub fn traverse_bfs(&self) -> Vec<VertexId> {
+        let mut traverse = Vec::new();
+        let mut queue = VecDeque::new();
+        // slot indices are dense, so they're tracked instead of the ids
+        let mut used = vec![false; self.vertices.len()];
+
+        for start in self.vertex_handles() {
+            if !used[start.index as usize] {
+                used[start.index as usize] = true;
+                queue.push_back(start.index);
+                while let Some(current) = queue.pop_front() {
+                    traverse.push(self.vertices[current as usize].id);
+                    for edge in self.outgoing(current) {
+                        let adjacent = self.edges[edge as usize].to;
+                        if !used[adjacent as usize] {
+                            used[adjacent as usize] = true;
+                            queue.push_back(adjacent);
+                        }
+                    }
+                }
+            }
+        }
+        traverse
+    }
+}
+
+impl<V, E> From<Graph<V, E>> for ArenaGraph<V, E> {
+    fn from(graph: Graph<V, E>) -> Self {
+        let mut arena = ArenaGraph::new();
+        for (id, value) in graph.vertices {
+            arena.insert_node(id, value);
+        }
+        for (from, map) in graph.adj_list {
+            for (to, value) in map {
+                arena.insert_edge(OrientedEdge(from, to), value);
+            }
+        }
+        arena
+    }
+}
+
+impl<V, E> From<ArenaGraph<V, E>> for Graph<V, E> {
+    fn from(arena: ArenaGraph<V, E>) -> Self {
+        let mut graph = Graph::new();
+        let ids: Vec<VertexId> = arena.vertices.iter().map(|slot| slot.id).collect();
+        for slot in arena.vertices {
+            if let Some(value) = slot.value {
+                graph.insert_node(slot.id, value);
+            }
+        }
+        for slot in arena.edges {
+            if let Some(value) = slot.value {
+                graph.insert_edge(
+                    OrientedEdge(ids[slot.from as usize], ids[slot.to as usize]),
+                    value,
+                );
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arena::ArenaGraph;
+    use crate::*;
+
+    #[test]
+    fn handles() {
+        let mut g = ArenaGraph::<&str, u32>::new();
+        let a = g.add_vertex("a").unwrap();
+        let b = g.add_vertex("b").unwrap();
+        let c = g.add_vertex("c").unwrap();
+        let ab = g.add_edge(a, b, 1).unwrap();
+        let ac = g.add_edge(a, c, 2).unwrap();
+        g.add_edge(c, a, 3).unwrap();
+        assert_eq!(g.add_edge(a, b, 10), Some(ab));
+        assert_eq!(g.edge(ab), Some(&10));
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.edge_endpoints(ac), Some((a, c)));
+        let mut neighbors: Vec<_> = g.neighbors(a).collect();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![b, c]);
+
+        assert_eq!(g.remove_vertex(c), Some("c"));
+        assert_eq!(g.edge_count(), 1);
+        assert_eq!(g.vertex(c), None);
+        assert_eq!(g.edge(ac), None);
+        assert_eq!(g.add_edge(a, c, 5), None);
+
+        // the freed slot is reused, but the stale handle doesn't see the new vertex
+        let d = g.add_vertex("d").unwrap();
+        assert_eq!(g.vertex(c), None);
+        assert_eq!(g.vertex(d), Some(&"d"));
+        assert_eq!(g.id_of(d), Some(3));
+        assert_eq!(g.remove_edge_by_handle(ab), Some(10));
+        assert_eq!(g.remove_edge_by_handle(ab), None);
+        assert_eq!(g.neighbors(a).count(), 0);
+    }
+
+    #[test]
+    fn exhausted_ids() {
+        let mut g = ArenaGraph::<&str, u32>::new();
+        g.insert_node(u32::MAX - 1, "a");
+        let b = g.add_vertex("b").unwrap();
+        assert_eq!(g.id_of(b), Some(u32::MAX));
+        assert_eq!(g.add_vertex("c"), None);
+        assert_eq!(g.vertex_count(), 2);
+        assert_eq!(g.vertex(b), Some(&"b"));
+        // lower ids can still be taken explicitly
+        g.insert_node(7, "d");
+        assert_eq!(g.add_vertex("e"), None);
+        assert_eq!(g.vertex_count(), 3);
+    }
+
+    #[test]
+    fn compatibility_layer() {
+        let mut g = ArenaGraph::<u32, u32>::new();
+        g.insert_node(1, 1);
+        g.insert_node(2, 2);
+        g.insert_node(3, 3);
+        assert_eq!(g.insert_node(3, 30), Some(3));
+        assert_eq!(g.insert_edge(OrientedEdge(1, 3), 5), None);
+        assert_eq!(g.insert_edge(OrientedEdge(1, 2), 3), None);
+        assert_eq!(g.insert_edge(OrientedEdge(5, 3), 5), None);
+        assert_eq!(g.insert_edge(OrientedEdge(1, 3), 7), Some(5));
+        assert_eq!(g.insert_edge(OrientedEdge(3, 1), 3), None);
+        assert_eq!(g.insert_edge(OrientedEdge(3, 3), 3), None);
+        let mut adjacents = g.get_adjacents(1).unwrap();
+        adjacents.sort();
+        assert_eq!(adjacents, vec![&2, &3]);
+        assert_eq!(g.remove_edge(OrientedEdge(1, 3)), Some(7));
+
+        g.remove_node(3);
+        assert_eq!(g.get_adjacents(3), None);
+        assert_eq!(g.get_adjacents(1).unwrap(), vec![&2]);
+        assert_eq!(g.get_vertex_value(2), Some(&2));
+        let mut traverse = g.traverse_bfs();
+        traverse.sort();
+        assert_eq!(traverse, vec![1, 2]);
+    }
+
+    #[test]
+    fn convert() {
+        let mut g = Graph::<u32, u32>::new();
+        for v in 0..10 {
+            g.insert_node(v * 3, v);
+        }
+        for v in 0..10 {
+            g.insert_edge(OrientedEdge(v * 3, (v * 7 % 10) * 3), v);
+            g.insert_edge(OrientedEdge(v * 3, (v * 3 % 10) * 3), v);
+        }
+        let arena = ArenaGraph::from(g.clone());
+        assert_eq!(arena.vertex_count(), 10);
+        assert_eq!(arena.edge_count(), g.edge_count());
+        assert_eq!(Graph::from(arena), g);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::hash::HashMap;
+use crate::{Graph, GraphEvent, OrientedEdge, VertexId};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl AttrValue {
+    // the type name GraphML and GEXF declare attributes with
+    pub(crate) fn xml_type(&self) -> &'static str {
+        match self {
+            AttrValue::Bool(_) => "boolean",
+            AttrValue::Int(_) => "long",
+            AttrValue::Float(_) => "double",
+            AttrValue::Str(_) => "string",
+        }
+    }
+}
+
+impl Display for AttrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrValue::Bool(value) => write!(f, "{}", value),
+            AttrValue::Int(value) => write!(f, "{}", value),
+            AttrValue::Float(value) => write!(f, "{}", value),
+            AttrValue::Str(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(value: bool) -> Self {
+        AttrValue::Bool(value)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(value: i64) -> Self {
+        AttrValue::Int(value)
+    }
+}
+
+impl From<i32> for AttrValue {
+    fn from(value: i32) -> Self {
+        AttrValue::Int(value.into())
+    }
+}
+
+impl From<u32> for AttrValue {
+    fn from(value: u32) -> Self {
+        AttrValue::Int(value.into())
+    }
+}
+
+impl From<f64> for AttrValue {
+    fn from(value: f64) -> Self {
+        AttrValue::Float(value)
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(value: String) -> Self {
+        AttrValue::Str(value)
+    }
+}
+
+impl From<&str> for AttrValue {
+    fn from(value: &str) -> Self {
+        AttrValue::Str(value.to_string())
+    }
+}
+
+// sorted by key so that exports are deterministic
+pub type AttrMap = BTreeMap<String, AttrValue>;
+
+// only vertices and edges that have attributes get an entry, so the layer costs nothing
+// until it is used
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Attributes {
+    pub(crate) nodes: HashMap<VertexId, AttrMap>,
+    pub(crate) edges: HashMap<(VertexId, VertexId), AttrMap>,
+}
+
+// every key used in the maps with the type to declare it with; a key whose values have
+// different types is declared as a double when they are all numbers, a string otherwise
+pub(crate) fn declared_types<'a>(
+    maps: impl Iterator<Item = &'a AttrMap>,
+) -> BTreeMap<&'a str, &'static str> {
+    let mut types = BTreeMap::new();
+    for map in maps {
+        for (key, value) in map {
+            let declared = types.entry(key.as_str()).or_insert(value.xml_type());
+            if *declared != value.xml_type() {
+                let numbers = ["long", "double"];
+                *declared = if numbers.contains(declared) && numbers.contains(&value.xml_type()) {
+                    "double"
+                } else {
+                    "string"
+                };
+            }
+        }
+    }
+    types
+}
+
+fn remove_attr<K: Eq + std::hash::Hash>(
+    maps: &mut HashMap<K, AttrMap>,
+    owner: K,
+    key: &str,
+) -> Option<AttrValue> {
+    let map = maps.get_mut(&owner)?;
+    let removed = map.remove(key);
+    if map.is_empty() {
+        maps.remove(&owner);
+    }
+    removed
+}
+
+// attributes live next to the V and E payloads, they go away with their vertex or edge
+impl<V, E> Graph<V, E> {
+    // ignored unless the vertex exists
+    pub fn set_node_attr(
+        &mut self,
+        vertex: VertexId,
+        key: impl Into<String>,
+        value: impl Into<AttrValue>,
+    ) -> Option<AttrValue> {
+        if !self.vertices.contains_key(&vertex) {
+            return None;
+        }
+        let key = key.into();
+        let attrs = self.attributes.nodes.entry(vertex).or_default();
+        let previous = attrs.insert(key.clone(), value.into());
+        if !self.observers.is_empty() {
+            self.observers.notify(GraphEvent::NodeAttrChanged {
+                id: vertex,
+                key: &key,
+                value: attrs.get(&key),
+            });
+        }
+        previous
+    }
+
+    pub fn get_node_attr(&self, vertex: VertexId, key: &str) -> Option<&AttrValue> {
+        self.attributes.nodes.get(&vertex)?.get(key)
+    }
+
+    pub fn remove_node_attr(&mut self, vertex: VertexId, key: &str) -> Option<AttrValue> {
+        let removed = remove_attr(&mut self.attributes.nodes, vertex, key)?;
+        if !self.observers.is_empty() {
+            self.observers.notify(GraphEvent::NodeAttrChanged {
+                id: vertex,
+                key,
+                value: None,
+            });
+        }
+        Some(removed)
+    }
+
+    pub fn node_attrs(&self, vertex: VertexId) -> Option<&AttrMap> {
+        self.attributes.nodes.get(&vertex)
+    }
+
+    // ignored unless the edge exists
+    pub fn set_edge_attr(
+        &mut self,
+        edge: OrientedEdge,
+        key: impl Into<String>,
+        value: impl Into<AttrValue>,
+    ) -> Option<AttrValue> {
+        let OrientedEdge(from, to) = edge;
+        if !self.adj_list.get(&from)?.contains_key(&to) {
+            return None;
+        }
+        let key = key.into();
+        let attrs = self.attributes.edges.entry((from, to)).or_default();
+        let previous = attrs.insert(key.clone(), value.into());
+        if !self.observers.is_empty() {
+            self.observers.notify(GraphEvent::EdgeAttrChanged {
+                from,
+                to,
+                key: &key,
+                value: attrs.get(&key),
+            });
+        }
+        previous
+    }
+
+    pub fn get_edge_attr(&self, edge: OrientedEdge, key: &str) -> Option<&AttrValue> {
+        self.attributes.edges.get(&(edge.0, edge.1))?.get(key)
+    }
+
+    pub fn remove_edge_attr(&mut self, edge: OrientedEdge, key: &str) -> Option<AttrValue> {
+        let removed = remove_attr(&mut self.attributes.edges, (edge.0, edge.1), key)?;
+        if !self.observers.is_empty() {
+            self.observers.notify(GraphEvent::EdgeAttrChanged {
+                from: edge.0,
+                to: edge.1,
+                key,
+                value: None,
+            });
+        }
+        Some(removed)
+    }
+
+    pub fn edge_attrs(&self, edge: OrientedEdge) -> Option<&AttrMap> {
+        self.attributes.edges.get(&(edge.0, edge.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn attributes() {
+        let mut g = Graph::<(), ()>::new();
+        g.insert_node(1, ());
+        g.insert_node(2, ());
+        g.insert_edge(OrientedEdge(1, 2), ());
+        g.insert_edge(OrientedEdge(2, 1), ());
+
+        assert_eq!(g.set_node_attr(1, "name", "first"), None);
+        assert_eq!(
+            g.set_node_attr(1, "name", "one"),
+            Some(AttrValue::Str("first".to_string()))
+        );
+        g.set_node_attr(1, "weight", 2.5);
+        g.set_node_attr(2, "visited", true);
+        assert_eq!(g.set_node_attr(3, "name", "missing"), None);
+        assert_eq!(g.get_node_attr(3, "name"), None);
+        assert_eq!(g.get_node_attr(1, "weight"), Some(&AttrValue::Float(2.5)));
+        let keys: Vec<&String> = g.node_attrs(1).unwrap().keys().collect();
+        assert_eq!(keys, ["name", "weight"]);
+
+        assert_eq!(g.set_edge_attr(OrientedEdge(1, 2), "hops", 3), None);
+        assert_eq!(g.set_edge_attr(OrientedEdge(1, 1), "hops", 3), None);
+        g.set_edge_attr(OrientedEdge(2, 1), "hops", 4);
+        assert_eq!(
+            g.get_edge_attr(OrientedEdge(1, 2), "hops"),
+            Some(&AttrValue::Int(3))
+        );
+        assert_eq!(g.get_edge_attr(OrientedEdge(1, 1), "hops"), None);
+
+        assert_eq!(
+            g.remove_node_attr(2, "visited"),
+            Some(AttrValue::Bool(true))
+        );
+        assert_eq!(g.node_attrs(2), None);
+
+        // attributes don't survive their edge or vertex, even when it comes back
+        g.remove_edge(OrientedEdge(1, 2));
+        g.insert_edge(OrientedEdge(1, 2), ());
+        assert_eq!(g.edge_attrs(OrientedEdge(1, 2)), None);
+        g.remove_node(1);
+        g.insert_node(1, ());
+        g.insert_edge(OrientedEdge(2, 1), ());
+        assert_eq!(g.node_attrs(1), None);
+        assert_eq!(g.edge_attrs(OrientedEdge(2, 1)), None);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::hash::HashMap;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+
+pub enum GraphOp<V, E> {
+    InsertNode(VertexId, V),
+    RemoveNode(VertexId),
+    InsertEdge(OrientedEdge, E),
+    RemoveEdge(OrientedEdge),
+}
+
+impl<V, E> Graph<V, E> {
+    // every op has to refer to vertices that exist at its point of the batch, otherwise
+    // nothing is applied; the ops go through the regular mutators, so the reverse index,
+    // counters, change tracking and observers follow each op as usual, the batch only grows
+    // the maps once up front and leaves the connectivity index to be rebuilt by the next query
+    pub fn apply_batch(
+        &mut self,
+        ops: impl IntoIterator<Item = GraphOp<V, E>>,
+    ) -> Result<(), GenericError> {
+        let ops: Vec<GraphOp<V, E>> = ops.into_iter().collect();
+        self.validate_batch(&ops)?;
+
+        let new_vertices = ops
+            .iter()
+            .filter(|op| matches!(op, GraphOp::InsertNode(..)))
+            .count();
+        self.vertices.reserve(new_vertices);
+        self.adj_list.reserve(new_vertices);
+        self.incoming.reserve(new_vertices);
+
+        let mut connectivity = self.connectivity.take();
+        for op in ops {
+            match op {
+                GraphOp::InsertNode(vertex, value) => {
+                    self.insert_node(vertex, value);
+                }
+                GraphOp::RemoveNode(vertex) => {
+                    self.remove_node(vertex);
+                }
+                GraphOp::InsertEdge(edge, value) => {
+                    self.insert_edge(edge, value);
+                }
+                GraphOp::RemoveEdge(edge) => {
+                    self.remove_edge(edge);
+                }
+            }
+        }
+        if let Some(connectivity) = &mut connectivity {
+            connectivity.invalidate();
+        }
+        self.connectivity = connectivity;
+        Ok(())
+    }
+
+    fn validate_batch(&self, ops: &[GraphOp<V, E>]) -> Result<(), GenericError> {
+        // vertices inserted or removed by the batch so far
+        let mut alive: HashMap<VertexId, bool> = HashMap::default();
+        let exists = |alive: &HashMap<VertexId, bool>, v: &VertexId| {
+            alive
+                .get(v)
+                .copied()
+                .unwrap_or_else(|| self.vertices.contains_key(v))
+        };
+        for (i, op) in ops.iter().enumerate() {
+            let (from, to) = match op {
+                GraphOp::InsertNode(vertex, _) => {
+                    alive.insert(*vertex, true);
+                    continue;
+                }
+                GraphOp::RemoveNode(vertex) => (vertex, vertex),
+                GraphOp::InsertEdge(OrientedEdge(from, to), _)
+                | GraphOp::RemoveEdge(OrientedEdge(from, to)) => (from, to),
+            };
+            let missing = if !exists(&alive, from) {
+                Some(from)
+            } else if !exists(&alive, to) {
+                Some(to)
+            } else {
+                None
+            };
+            if let Some(missing) = missing {
+                return Err(format!("operation {}: vertex {} doesn't exist", i, missing).into());
+            }
+            if let GraphOp::RemoveNode(vertex) = op {
+                alive.insert(*vertex, false);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn apply_batch() {
+        let mut g = Graph::<u32, u32>::new();
+        g.insert_node(1, 1);
+        assert_eq!(g.component_count(), 1);
+        let ops = vec![
+            GraphOp::InsertNode(2, 2),
+            GraphOp::InsertNode(3, 3),
+            GraphOp::InsertEdge(OrientedEdge(1, 2), 12),
+            GraphOp::InsertEdge(OrientedEdge(2, 3), 23),
+            GraphOp::InsertEdge(OrientedEdge(3, 1), 31),
+            GraphOp::RemoveEdge(OrientedEdge(2, 3)),
+            GraphOp::RemoveNode(1),
+            GraphOp::InsertNode(4, 4),
+        ];
+        g.apply_batch(ops).unwrap();
+        assert_eq!(g.vertex_count(), 3);
+        assert_eq!(g.edge_count(), 0);
+        assert_eq!(g.component_count(), 3);
+
+        let error = g
+            .apply_batch(vec![
+                GraphOp::InsertEdge(OrientedEdge(2, 3), 23),
+                GraphOp::RemoveNode(3),
+                GraphOp::InsertEdge(OrientedEdge(2, 3), 23),
+            ])
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "operation 2: vertex 3 doesn't exist");
+        let error = g
+            .apply_batch(vec![GraphOp::RemoveEdge(OrientedEdge(7, 2))])
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "operation 0: vertex 7 doesn't exist");
+        assert_eq!(g.edge_count(), 0);
+        assert_eq!(g.vertex_count(), 3);
+    }
+}
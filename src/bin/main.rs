@@ -1,21 +1,672 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use graph::algo::topological_sort;
+use graph::format::stream::{convert_tgf, StreamFormat};
+use graph::format::Compression;
+use graph::{Degree, EdgeWeights, GenericError, Graph, GraphBase, OrientedEdge, Path, VertexId};
+use std::io::{self, BufRead, BufWriter, Write};
+
+// labels are kept as they are, whatever the format
+type TextGraph = Graph<String, String>;
+
+// stands for stdin or stdout in place of a path
+const STDIO: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FileFormat {
+    Tgf,
+    Csv,
+    Json,
+    Gml,
+    Mtx,
+    Dot,
+    Graphml,
+}
+
+impl FileFormat {
+    // the extension in front of a compression suffix counts, graph.tgf.gz is TGF
+    fn from_path(path: &str) -> Option<FileFormat> {
+        let path = path
+            .strip_suffix(".gz")
+            .or_else(|| path.strip_suffix(".zst"))
+            .unwrap_or(path);
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "tgf" => Some(FileFormat::Tgf),
+            "csv" => Some(FileFormat::Csv),
+            "json" | "cyjs" => Some(FileFormat::Json),
+            "gml" => Some(FileFormat::Gml),
+            "mtx" => Some(FileFormat::Mtx),
+            "dot" | "gv" => Some(FileFormat::Dot),
+            "graphml" => Some(FileFormat::Graphml),
+            _ => None,
+        }
+    }
+
+    // piped graphs are TGF unless told otherwise
+    fn resolve(flag: Option<FileFormat>, path: &str) -> Result<FileFormat, GenericError> {
+        let guess = match path {
+            STDIO => Some(FileFormat::Tgf),
+            _ => FileFormat::from_path(path),
+        };
+        match flag.or(guess) {
+            Some(format) => Ok(format),
+            None => Err(format!("can't tell the format of {}, pass it explicitly", path).into()),
+        }
+    }
+
+    // the targets a TGF file can be converted to without loading it
+    fn stream_format(self) -> Option<StreamFormat> {
+        match self {
+            FileFormat::Tgf => Some(StreamFormat::Tgf),
+            FileFormat::Csv => Some(StreamFormat::Csv),
+            FileFormat::Json => Some(StreamFormat::Json),
+            FileFormat::Dot => Some(StreamFormat::Dot),
+            FileFormat::Graphml => Some(StreamFormat::GraphMl),
+            FileFormat::Gml | FileFormat::Mtx => None,
+        }
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "graph", version, about = "Inspect and convert graph files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Input {
+    #[arg(help = "Input file or - for stdin, .gz and .zst files are decompressed")]
+    input: String,
+    #[arg(
+        long,
+        visible_alias = "from",
+        value_enum,
+        help = "Input format [default: from the extension]"
+    )]
+    format: Option<FileFormat>,
+}
+
+#[derive(Subcommand)]
+enum Query {
+    #[command(about = "Print the successors of a vertex")]
+    Neighbors {
+        #[command(flatten)]
+        input: Input,
+        id: VertexId,
+    },
+    #[command(about = "Print every vertex reachable from a vertex")]
+    Reachable {
+        #[command(flatten)]
+        input: Input,
+        id: VertexId,
+    },
+    #[command(about = "Print a path with the fewest edges between two vertices")]
+    Path {
+        #[command(flatten)]
+        input: Input,
+        from: VertexId,
+        to: VertexId,
+    },
+    #[command(about = "Print the in- and out-degree of a vertex")]
+    Degree {
+        #[command(flatten)]
+        input: Input,
+        id: VertexId,
+    },
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Print the number of vertices and edges")]
+    Info(Input),
+    #[command(about = "Print the vertices in breadth-first order")]
+    Bfs(Input),
+    #[command(about = "Print the weakly connected components, one per line")]
+    Components(Input),
+    #[command(about = "Print a shortest path between two vertices")]
+    ShortestPath {
+        #[command(flatten)]
+        input: Input,
+        from: VertexId,
+        to: VertexId,
+        #[arg(
+            long,
+            help = "Use the edge labels as weights instead of counting edges"
+        )]
+        weighted: bool,
+    },
+    #[command(about = "Write the graph in another format")]
+    Convert {
+        #[command(flatten)]
+        input: Input,
+        #[arg(
+            default_value = STDIO,
+            help = "Output file or - for stdout, compressed when it ends in .gz or .zst"
+        )]
+        output: String,
+        #[arg(long, value_enum, help = "Output format [default: from the extension]")]
+        to: Option<FileFormat>,
+    },
+    #[command(about = "Print a summary of the graph's structure")]
+    Stats {
+        #[command(flatten)]
+        input: Input,
+        #[arg(
+            long,
+            default_value_t = 5,
+            help = "How many of the highest-degree vertices to list"
+        )]
+        top: usize,
+    },
+    #[command(about = "Look up vertices, paths and degrees")]
+    Query {
+        #[command(subcommand)]
+        query: Query,
+        #[arg(long, global = true, help = "Print JSON instead of plain text")]
+        json: bool,
+    },
+}
+
+fn open(path: &str) -> Result<Box<dyn BufRead>, GenericError> {
+    match path {
+        STDIO => Ok(Box::new(io::stdin().lock())),
+        _ => Compression::from_extension(path).open(path),
+    }
+}
+
+// the closure gets a buffered stdout or the file's encoder, either is flushed or finished;
+// a file is written aside and only replaces the target once complete, so converting a file
+// onto itself reads the original to the end
+fn create<F>(path: &str, write: F) -> Result<(), GenericError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), GenericError>,
+{
+    match path {
+        STDIO => {
+            let mut stdout = BufWriter::new(io::stdout().lock());
+            write(&mut stdout)?;
+            stdout.flush()?;
+        }
+        _ => Compression::from_extension(path).write_file(path, |encoder| write(encoder))?,
+    }
+    Ok(())
+}
+
+fn load(input: &Input) -> Result<TextGraph, GenericError> {
+    let path = &input.input;
+    let format = FileFormat::resolve(input.format, path)?;
+    match format {
+        FileFormat::Dot => return Err("DOT files can be written but not read".into()),
+        FileFormat::Graphml => return Err("GraphML files can be written but not read".into()),
+        _ => {}
+    }
+    let reader = open(path)?;
+    match format {
+        FileFormat::Tgf => TextGraph::deserialize_from_reader(reader),
+        FileFormat::Csv => TextGraph::deserialize_csv_from_reader(reader),
+        FileFormat::Json => TextGraph::import_cytoscape_from_reader(reader),
+        FileFormat::Gml => TextGraph::deserialize_gml_from_reader(reader),
+        FileFormat::Mtx => TextGraph::deserialize_mtx_from_reader(reader),
+        FileFormat::Dot | FileFormat::Graphml => unreachable!(),
+    }
+}
+
+fn save(graph: &TextGraph, writer: &mut dyn Write, format: FileFormat) -> Result<(), GenericError> {
+    match format {
+        FileFormat::Tgf => graph.serialize_into(writer),
+        FileFormat::Csv => graph.serialize_csv_into(writer),
+        FileFormat::Json => graph.export_cytoscape_into(writer),
+        FileFormat::Gml => graph.serialize_gml_into(writer),
+        FileFormat::Mtx => graph.serialize_mtx_into(writer),
+        FileFormat::Dot => graph.serialize_dot_into(writer),
+        FileFormat::Graphml => graph.export_graphml_into(writer),
+    }
+}
+
+// TGF input is converted record by record when the target allows it, so files larger than
+// memory can be converted; everything else is loaded first
+fn convert(input: &Input, output: &str, to: Option<FileFormat>) -> Result<(), GenericError> {
+    let format = FileFormat::resolve(to, output)?;
+    let from = FileFormat::resolve(input.format, &input.input)?;
+    match format.stream_format() {
+        Some(target) if from == FileFormat::Tgf => {
+            let reader = open(&input.input)?;
+            create(output, |writer| convert_tgf(reader, writer, target))
+        }
+        _ => {
+            let graph = load(input)?;
+            create(output, |writer| save(&graph, writer, format))
+        }
+    }
+}
+
+fn ensure_vertex(graph: &TextGraph, vertex: VertexId) -> Result<(), GenericError> {
+    if !graph.contains_vertex(vertex) {
+        return Err(format!("vertex {} doesn't exist", vertex).into());
+    }
+    Ok(())
+}
+
+// edge directions are ignored, components and their members are sorted by id
+fn components(graph: &TextGraph) -> Vec<Vec<VertexId>> {
+    let mut ids: Vec<VertexId> = graph.vertex_ids().collect();
+    ids.sort_unstable();
+    let mut seen = std::collections::HashSet::new();
+    let mut components = Vec::new();
+    for start in ids {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            let adjacents = graph.get_adjacents(current).unwrap();
+            let predecessors = graph.get_predecessors(current).unwrap();
+            for next in adjacents.into_iter().chain(predecessors) {
+                if seen.insert(*next) {
+                    component.push(*next);
+                    stack.push(*next);
+                }
+            }
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+    components
+}
+
+// sorted, the start itself only when it lies on a cycle
+fn reachable(graph: &TextGraph, start: VertexId) -> Result<Vec<VertexId>, GenericError> {
+    ensure_vertex(graph, start)?;
+    let mut seen = std::collections::HashSet::new();
+    let mut stack = vec![start];
+    while let Some(current) = stack.pop() {
+        for next in graph.get_adjacents(current).unwrap() {
+            if seen.insert(*next) {
+                stack.push(*next);
+            }
+        }
+    }
+    let mut reachable: Vec<VertexId> = seen.into_iter().collect();
+    reachable.sort_unstable();
+    Ok(reachable)
+}
+
+// the answer as plain text lines and as a JSON value
+fn query(query: Query) -> Result<(Vec<String>, serde_json::Value), GenericError> {
+    use serde_json::json;
+    Ok(match query {
+        Query::Neighbors { input, id } => {
+            let g = load(&input)?;
+            ensure_vertex(&g, id)?;
+            let mut neighbors: Vec<VertexId> = g.edges(id).map(|(to, _)| to).collect();
+            neighbors.sort_unstable();
+            let lines = neighbors.iter().map(|v| v.to_string()).collect();
+            (lines, json!({ "id": id, "neighbors": neighbors }))
+        }
+        Query::Reachable { input, id } => {
+            let reachable = reachable(&load(&input)?, id)?;
+            let lines = reachable.iter().map(|v| v.to_string()).collect();
+            (lines, json!({ "id": id, "reachable": reachable }))
+        }
+        Query::Path { input, from, to } => match shortest_path(&load(&input)?, from, to, false)? {
+            Some(path) => (
+                vec![join(path.vertices(), " -> ")],
+                json!({ "from": from, "to": to, "length": path.cost(), "path": path.vertices() }),
+            ),
+            None => return Err(format!("no path from {} to {}", from, to).into()),
+        },
+        Query::Degree { input, id } => {
+            let g = load(&input)?;
+            ensure_vertex(&g, id)?;
+            let (incoming, outgoing) = (g.in_degree(id).unwrap(), g.out_degree(id).unwrap());
+            (
+                vec![format!("in: {}", incoming), format!("out: {}", outgoing)],
+                json!({ "id": id, "in": incoming, "out": outgoing }),
+            )
+        }
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct Stats {
+    vertices: usize,
+    edges: usize,
+    density: f64,
+    components: usize,
+    // over the total degree, incoming plus outgoing
+    min_degree: usize,
+    median_degree: f64,
+    max_degree: usize,
+    hubs: Vec<(VertexId, usize)>,
+    acyclic: bool,
+}
+
+fn stats(graph: &TextGraph, top: usize) -> Stats {
+    let n = graph.vertex_count();
+    let mut degrees: Vec<(VertexId, usize)> = graph
+        .vertex_ids()
+        .map(|v| {
+            (
+                v,
+                graph.in_degree(v).unwrap() + graph.out_degree(v).unwrap(),
+            )
+        })
+        .collect();
+    degrees.sort_unstable_by_key(|(v, degree)| (std::cmp::Reverse(*degree), *v));
+    let median_degree = match n {
+        0 => 0.0,
+        _ if n % 2 == 1 => degrees[n / 2].1 as f64,
+        _ => (degrees[n / 2 - 1].1 + degrees[n / 2].1) as f64 / 2.0,
+    };
+    let metrics = graph.metrics();
+    Stats {
+        vertices: n,
+        edges: metrics.edges,
+        density: metrics.density,
+        components: metrics.components,
+        min_degree: degrees.last().map_or(0, |(_, degree)| *degree),
+        median_degree,
+        max_degree: degrees.first().map_or(0, |(_, degree)| *degree),
+        hubs: graph.highest_degree(top, Degree::Total),
+        acyclic: topological_sort(graph).is_some(),
+    }
+}
+
+fn shortest_path(
+    graph: &TextGraph,
+    from: VertexId,
+    to: VertexId,
+    weighted: bool,
+) -> Result<Option<Path<u64>>, GenericError> {
+    ensure_vertex(graph, from)?;
+    ensure_vertex(graph, to)?;
+    let mut weights = Graph::<(), u64>::new();
+    for id in graph.vertex_ids() {
+        weights.insert_node(id, ());
+    }
+    for id in graph.vertex_ids() {
+        for (adjacent, label) in graph.edges(id) {
+            let weight = if weighted {
+                label.trim().parse::<u64>().map_err(|e| {
+                    format!(
+                        "edge {} -> {}: invalid weight `{}`: {}",
+                        id, adjacent, label, e
+                    )
+                })?
+            } else {
+                1
+            };
+            weights.insert_edge(OrientedEdge(id, adjacent), weight);
+        }
+    }
+    Ok(weights.freeze().shortest_path(from, to))
+}
+
+fn join(ids: &[VertexId], separator: &str) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn run(cli: Cli) -> Result<(), GenericError> {
+    let mut out = io::stdout().lock();
+    match cli.command {
+        Command::Info(input) => {
+            let g = load(&input)?;
+            writeln!(out, "vertices: {}", g.vertex_count())?;
+            writeln!(out, "edges: {}", g.edge_count())?;
+        }
+        Command::Bfs(input) => {
+            let g = load(&input)?;
+            for v in g.traverse_bfs() {
+                writeln!(out, "{}\t{}", v, g.get_vertex_value(v).unwrap())?;
+            }
+        }
+        Command::Components(input) => {
+            for component in components(&load(&input)?) {
+                writeln!(out, "{}", join(&component, " "))?;
+            }
+        }
+        Command::ShortestPath {
+            input,
+            from,
+            to,
+            weighted,
+        } => match shortest_path(&load(&input)?, from, to, weighted)? {
+            Some(path) => {
+                writeln!(out, "{}", join(path.vertices(), " -> "))?;
+                writeln!(out, "length: {}", path.cost())?;
+            }
+            None => return Err(format!("no path from {} to {}", from, to).into()),
+        },
+        Command::Convert { input, output, to } => convert(&input, &output, to)?,
+        Command::Stats { input, top } => {
+            let stats = stats(&load(&input)?, top);
+            writeln!(out, "vertices: {}", stats.vertices)?;
+            writeln!(out, "edges: {}", stats.edges)?;
+            writeln!(out, "density: {:.6}", stats.density)?;
+            writeln!(out, "components: {}", stats.components)?;
+            writeln!(
+                out,
+                "degree: min {}, median {}, max {}",
+                stats.min_degree, stats.median_degree, stats.max_degree
+            )?;
+            let hubs: Vec<String> = stats
+                .hubs
+                .iter()
+                .map(|(v, degree)| format!("{} ({})", v, degree))
+                .collect();
+            writeln!(out, "hubs: {}", hubs.join(", "))?;
+            writeln!(out, "dag: {}", if stats.acyclic { "yes" } else { "no" })?;
+        }
+        Command::Query { query: q, json } => {
+            let (lines, value) = query(q)?;
+            if json {
+                writeln!(out, "{}", value)?;
+            } else {
+                for line in lines {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn main() {
-    const FILENAME: &str = "sample.tgf";
+    if let Err(e) = run(Cli::parse()) {
+        // the reading end hung up, e.g. `graph bfs big.tgf | head`
+        if e.downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+        {
+            return;
+        }
+        eprintln!("graph: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
 
-    println!("Deserializing {}...", FILENAME);
-    let g =
-        graph::Graph::<String, String>::deserialize_from(FILENAME).expect("couldn't deserialize");
-    println!("Deserialization finished!\n");
+    fn sample() -> TextGraph {
+        let mut g = TextGraph::new();
+        for v in 1..=5 {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(OrientedEdge(1, 2), "5".to_string());
+        g.insert_edge(OrientedEdge(2, 3), "1".to_string());
+        g.insert_edge(OrientedEdge(1, 3), "9".to_string());
+        g.insert_edge(OrientedEdge(5, 4), "2".to_string());
+        g
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(FileFormat::from_path("a/b.tgf"), Some(FileFormat::Tgf));
+        assert_eq!(FileFormat::from_path("b.CSV.gz"), Some(FileFormat::Csv));
+        assert_eq!(FileFormat::from_path("b.gv.zst"), Some(FileFormat::Dot));
+        assert_eq!(
+            FileFormat::from_path("g.graphml"),
+            Some(FileFormat::Graphml)
+        );
+        assert_eq!(FileFormat::from_path("graph"), None);
+        assert_eq!(FileFormat::resolve(None, STDIO).unwrap(), FileFormat::Tgf);
+        let error = FileFormat::resolve(None, "graph.txt").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "can't tell the format of graph.txt, pass it explicitly"
+        );
+        assert_eq!(
+            FileFormat::resolve(Some(FileFormat::Gml), "graph.txt").unwrap(),
+            FileFormat::Gml
+        );
+    }
 
-    println!("Traversing all connectivity components with bfs...");
-    for v in g.traverse_bfs() {
-        println!(
-            "Vertex #{} ({}) is connected with",
-            v,
-            g.get_vertex_value(v).unwrap()
+    #[test]
+    fn commands() {
+        let mut g = sample();
+        assert_eq!(components(&g), vec![vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(
+            shortest_path(&g, 1, 3, false).unwrap(),
+            Path::from_parts(vec![1, 3], vec![1])
         );
-        for adj in g.get_adjacents(v).unwrap() {
-            println!("|- Vertex #{} ({})", adj, g.get_vertex_value(*adj).unwrap());
+        assert_eq!(
+            shortest_path(&g, 1, 3, true).unwrap(),
+            Path::from_parts(vec![1, 2, 3], vec![5, 1])
+        );
+        assert_eq!(shortest_path(&g, 3, 1, false).unwrap(), None);
+        assert_eq!(
+            shortest_path(&g, 1, 7, false).unwrap_err().to_string(),
+            "vertex 7 doesn't exist"
+        );
+        g.insert_edge(OrientedEdge(5, 4), "x".to_string());
+        assert_eq!(
+            shortest_path(&g, 1, 3, true).unwrap_err().to_string(),
+            "edge 5 -> 4: invalid weight `x`: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn statistics() {
+        let mut g = sample();
+        assert_eq!(
+            stats(&g, 2),
+            Stats {
+                vertices: 5,
+                edges: 4,
+                density: 0.2,
+                components: 2,
+                min_degree: 1,
+                median_degree: 2.0,
+                max_degree: 2,
+                hubs: vec![(1, 2), (2, 2)],
+                acyclic: true,
+            }
+        );
+        g.insert_edge(OrientedEdge(3, 1), "1".to_string());
+        g.insert_node(6, "v6".to_string());
+        let report = stats(&g, 1);
+        assert_eq!(report.median_degree, 1.5);
+        assert_eq!((report.min_degree, report.max_degree), (0, 3));
+        assert_eq!(report.hubs, vec![(1, 3)]);
+        assert!(!report.acyclic);
+        assert_eq!(report.components, 3);
+        assert_eq!(stats(&TextGraph::new(), 3).hubs, vec![]);
+    }
+
+    #[test]
+    fn streaming_convert() {
+        let dir = std::env::temp_dir();
+        let path = |name: &str| {
+            dir.join(format!("graph_cli_{}_{}", std::process::id(), name))
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        let (tgf, dot, gml) = (path("in.tgf"), path("out.dot"), path("out.gml"));
+        sample().serialize_to(&tgf).unwrap();
+        let input = Input {
+            input: tgf.clone(),
+            format: None,
+        };
+        convert(&input, &dot, None).unwrap();
+        let text = std::fs::read_to_string(&dot).unwrap();
+        assert!(text.starts_with("digraph {\n  1 [label=\"v1\"];\n"));
+        assert!(text.contains("  5 -> 4 [label=\"2\"];\n"));
+        convert(&input, &gml, None).unwrap();
+        let g = TextGraph::deserialize_gml_from(&gml).unwrap();
+        // the streamed input isn't truncated before it has been read
+        convert(&input, &tgf, None).unwrap();
+        assert_eq!(TextGraph::deserialize_from(&tgf).unwrap(), sample());
+        for file in [tgf, dot, gml] {
+            std::fs::remove_file(file).unwrap();
         }
+        assert_eq!(g.edge_count(), 4);
+    }
+
+    #[test]
+    fn queries() {
+        let path = std::env::temp_dir()
+            .join(format!("graph_cli_{}_query.tgf", std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        let mut g = sample();
+        g.insert_edge(OrientedEdge(3, 1), "1".to_string());
+        g.serialize_to(&path).unwrap();
+        let input = || Input {
+            input: path.clone(),
+            format: None,
+        };
+
+        let (lines, json) = query(Query::Neighbors {
+            input: input(),
+            id: 1,
+        })
+        .unwrap();
+        assert_eq!(lines, ["2", "3"]);
+        assert_eq!(json.to_string(), r#"{"id":1,"neighbors":[2,3]}"#);
+        let (lines, _) = query(Query::Reachable {
+            input: input(),
+            id: 2,
+        })
+        .unwrap();
+        assert_eq!(lines, ["1", "2", "3"]);
+        let (_, json) = query(Query::Reachable {
+            input: input(),
+            id: 4,
+        })
+        .unwrap();
+        assert_eq!(json.to_string(), r#"{"id":4,"reachable":[]}"#);
+        let (lines, json) = query(Query::Path {
+            input: input(),
+            from: 2,
+            to: 1,
+        })
+        .unwrap();
+        assert_eq!(lines, ["2 -> 3 -> 1"]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"from":2,"length":2,"path":[2,3,1],"to":1}"#
+        );
+        let (lines, json) = query(Query::Degree {
+            input: input(),
+            id: 3,
+        })
+        .unwrap();
+        assert_eq!(lines, ["in: 2", "out: 1"]);
+        assert_eq!(json.to_string(), r#"{"id":3,"in":2,"out":1}"#);
+        let error = query(Query::Degree {
+            input: input(),
+            id: 9,
+        })
+        .unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error.to_string(), "vertex 9 doesn't exist");
     }
-    println!("Traversing is finished!");
 }
@@ -0,0 +1,10 @@
+use crate::hash::HashSet;
+use crate::VertexId;
+
+// vertices and edges touched since the graph was last written to a store, whether they
+// still exist is looked up when the changes are flushed
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangeSet {
+    pub(crate) vertices: HashSet<VertexId>,
+    pub(crate) edges: HashSet<(VertexId, VertexId)>,
+}
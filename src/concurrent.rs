@@ -0,0 +1,311 @@
+use crate::adjacency::Adjacency;
+use crate::hash::{HashMap, HashSet};
+use crate::{Graph, OrientedEdge, VertexId};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+const DEFAULT_SHARDS: usize = 64;
+
+#[derive(Debug)]
+struct Shard<V, E> {
+    vertices: HashMap<VertexId, V>,
+    adj_list: HashMap<VertexId, Adjacency<E>>,
+    incoming: HashMap<VertexId, HashSet<VertexId>>,
+}
+
+impl<V, E> Default for Shard<V, E> {
+    fn default() -> Self {
+        Shard {
+            vertices: HashMap::default(),
+            adj_list: HashMap::default(),
+            incoming: HashMap::default(),
+        }
+    }
+}
+
+// Graph that can be shared between threads: the vertices are spread over shards behind
+// their own RwLock, an edge locks the shards of its two endpoints, always in shard order,
+// and only vertex removals and snapshots lock every shard at once
+#[derive(Debug)]
+pub struct ConcurrentGraph<V, E> {
+    shards: Vec<RwLock<Shard<V, E>>>,
+    vertex_count: AtomicUsize,
+    edge_count: AtomicUsize,
+}
+
+impl<V, E> Default for ConcurrentGraph<V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, E> ConcurrentGraph<V, E> {
+    pub fn new() -> ConcurrentGraph<V, E> {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    // more shards mean less contention between writers and slower removals and snapshots
+    pub fn with_shards(shards: usize) -> ConcurrentGraph<V, E> {
+        ConcurrentGraph {
+            shards: (0..shards.max(1)).map(|_| RwLock::default()).collect(),
+            vertex_count: AtomicUsize::new(0),
+            edge_count: AtomicUsize::new(0),
+        }
+    }
+
+    // ids are usually dense, so consecutive ones land in different shards
+    fn shard_of(&self, vertex: VertexId) -> usize {
+        vertex as usize % self.shards.len()
+    }
+
+    fn read(&self, vertex: VertexId) -> RwLockReadGuard<'_, Shard<V, E>> {
+        self.shards[self.shard_of(vertex)].read().unwrap()
+    }
+
+    fn write(&self, vertex: VertexId) -> RwLockWriteGuard<'_, Shard<V, E>> {
+        self.shards[self.shard_of(vertex)].write().unwrap()
+    }
+
+    fn write_all(&self) -> Vec<RwLockWriteGuard<'_, Shard<V, E>>> {
+        self.shards.iter().map(|s| s.write().unwrap()).collect()
+    }
+
+    pub fn insert_node(&self, vertex_id: VertexId, value: V) -> Option<V> {
+        let mut shard = self.write(vertex_id);
+        shard.adj_list.entry(vertex_id).or_default();
+        shard.incoming.entry(vertex_id).or_default();
+        let previous = shard.vertices.insert(vertex_id, value);
+        if previous.is_none() {
+            self.vertex_count.fetch_add(1, Ordering::Relaxed);
+        }
+        previous
+    }
+
+    pub fn remove_node(&self, vertex_id: VertexId) -> Option<V> {
+        let mut shards = self.write_all();
+        let home = self.shard_of(vertex_id);
+        let value = shards[home].vertices.remove(&vertex_id)?;
+        let adjacency = shards[home].adj_list.remove(&vertex_id).unwrap();
+        let predecessors = shards[home].incoming.remove(&vertex_id).unwrap();
+        let mut removed = 0;
+        // the vertex's own entries are already out of its shard, so a self-loop is only
+        // found, and counted, through the predecessors
+        for predecessor in predecessors {
+            let shard = &mut shards[self.shard_of(predecessor)];
+            if let Some(adjacency) = shard.adj_list.get_mut(&predecessor) {
+                adjacency.remove(&vertex_id);
+            }
+            removed += 1;
+        }
+        for adjacent in adjacency.keys() {
+            let shard = &mut shards[self.shard_of(*adjacent)];
+            if let Some(predecessors) = shard.incoming.get_mut(adjacent) {
+                predecessors.remove(&vertex_id);
+                removed += 1;
+            }
+        }
+        self.vertex_count.fetch_sub(1, Ordering::Relaxed);
+        self.edge_count.fetch_sub(removed, Ordering::Relaxed);
+        Some(value)
+    }
+
+    // runs f with the shards of both endpoints write-locked, the first argument is the
+    // shard of `from`
+    fn with_edge<T>(
+        &self,
+        from: VertexId,
+        to: VertexId,
+        f: impl FnOnce(&mut Shard<V, E>, Option<&mut Shard<V, E>>) -> T,
+    ) -> T {
+        let (a, b) = (self.shard_of(from), self.shard_of(to));
+        if a == b {
+            return f(&mut self.shards[a].write().unwrap(), None);
+        }
+        let (first, second) = (a.min(b), a.max(b));
+        let mut first = self.shards[first].write().unwrap();
+        let mut second = self.shards[second].write().unwrap();
+        if a < b {
+            f(&mut first, Some(&mut second))
+        } else {
+            f(&mut second, Some(&mut first))
+        }
+    }
+
+    // `to` is looked up with both shards locked, so a concurrent remove_node can't take it
+    // away before the edge is in; an edge to a missing vertex is ignored
+    pub fn insert_edge(&self, edge: OrientedEdge, value: E) -> Option<E> {
+        let OrientedEdge(from, to) = edge;
+        self.with_edge(from, to, |source, target| {
+            let target_vertices = match &target {
+                Some(target) => &target.vertices,
+                None => &source.vertices,
+            };
+            if !target_vertices.contains_key(&to) {
+                return None;
+            }
+            let previous = source.adj_list.get_mut(&from)?.insert(to, value);
+            if previous.is_none() {
+                let shard = target.unwrap_or(source);
+                shard.incoming.get_mut(&to).unwrap().insert(from);
+                self.edge_count.fetch_add(1, Ordering::Relaxed);
+            }
+            previous
+        })
+    }
+
+    pub fn remove_edge(&self, edge: OrientedEdge) -> Option<E> {
+        let OrientedEdge(from, to) = edge;
+        self.with_edge(from, to, |source, target| {
+            let removed = source.adj_list.get_mut(&from)?.remove(&to)?;
+            let shard = target.unwrap_or(source);
+            shard.incoming.get_mut(&to).unwrap().remove(&from);
+            self.edge_count.fetch_sub(1, Ordering::Relaxed);
+            Some(removed)
+        })
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count.load(Ordering::Relaxed)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count.load(Ordering::Relaxed)
+    }
+
+    pub fn contains_vertex(&self, vertex: VertexId) -> bool {
+        self.read(vertex).vertices.contains_key(&vertex)
+    }
+
+    pub fn in_degree(&self, vertex: VertexId) -> Option<usize> {
+        Some(self.read(vertex).incoming.get(&vertex)?.len())
+    }
+
+    pub fn out_degree(&self, vertex: VertexId) -> Option<usize> {
+        Some(self.read(vertex).adj_list.get(&vertex)?.len())
+    }
+
+    // owned, the shard lock is released before returning
+    pub fn get_adjacents(&self, vertex: VertexId) -> Option<Vec<VertexId>> {
+        Some(
+            self.read(vertex)
+                .adj_list
+                .get(&vertex)?
+                .keys()
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn get_predecessors(&self, vertex: VertexId) -> Option<Vec<VertexId>> {
+        Some(
+            self.read(vertex)
+                .incoming
+                .get(&vertex)?
+                .iter()
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn get_vertex_value(&self, vertex: VertexId) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.read(vertex).vertices.get(&vertex).cloned()
+    }
+
+    // plain Graph copy for running algorithms on, consistent because every shard is
+    // read-locked while it is taken
+    pub fn snapshot(&self) -> Graph<V, E>
+    where
+        V: Clone,
+        E: Clone,
+    {
+        let shards: Vec<_> = self.shards.iter().map(|s| s.read().unwrap()).collect();
+        let mut graph = Graph::new();
+        for shard in &shards {
+            for (id, value) in &shard.vertices {
+                graph.insert_node(*id, value.clone());
+            }
+        }
+        for shard in &shards {
+            for (from, adjacency) in &shard.adj_list {
+                for (to, value) in adjacency {
+                    graph.insert_edge(OrientedEdge(*from, *to), value.clone());
+                }
+            }
+        }
+        graph
+    }
+}
+
+impl<V, E> From<Graph<V, E>> for ConcurrentGraph<V, E> {
+    fn from(graph: Graph<V, E>) -> Self {
+        let Graph {
+            vertices, adj_list, ..
+        } = graph;
+        let concurrent = ConcurrentGraph::new();
+        for (id, value) in vertices {
+            concurrent.insert_node(id, value);
+        }
+        for (from, adjacency) in adj_list {
+            for (to, value) in adjacency {
+                concurrent.insert_edge(OrientedEdge(from, to), value);
+            }
+        }
+        concurrent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn concurrent_writers() {
+        let g = ConcurrentGraph::<u32, u32>::with_shards(4);
+        std::thread::scope(|s| {
+            for t in 0..8 {
+                let g = &g;
+                s.spawn(move || {
+                    for v in (t..100).step_by(8) {
+                        g.insert_node(v, v * 10);
+                    }
+                });
+            }
+        });
+        assert_eq!(g.vertex_count(), 100);
+        std::thread::scope(|s| {
+            for t in 0..8 {
+                let g = &g;
+                s.spawn(move || {
+                    for from in (t..100).step_by(8) {
+                        for to in [(from + 1) % 100, (from + 37) % 100, from] {
+                            g.insert_edge(OrientedEdge(from, to), from + to);
+                        }
+                        g.insert_edge(OrientedEdge(from, 500), 0);
+                    }
+                });
+            }
+        });
+        assert_eq!(g.edge_count(), 300);
+        assert_eq!(g.in_degree(37), Some(3));
+        assert_eq!(g.get_vertex_value(4), Some(40));
+
+        let snapshot = g.snapshot();
+        assert_eq!(snapshot.edge_count(), 300);
+        assert_eq!(snapshot.get_adjacents(99).unwrap().len(), 3);
+
+        assert_eq!(g.remove_node(1), Some(10));
+        assert_eq!(g.edge_count(), 300 - 5);
+        let mut adjacents = g.get_adjacents(0).unwrap();
+        adjacents.sort();
+        assert_eq!(adjacents, vec![0, 37]);
+        assert_eq!(g.remove_edge(OrientedEdge(0, 0)), Some(0));
+        assert_eq!(g.remove_edge(OrientedEdge(0, 0)), None);
+        assert_eq!(g.in_degree(0), Some(2));
+
+        let back = ConcurrentGraph::from(snapshot.clone());
+        assert_eq!(back.snapshot(), snapshot);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::hash::HashMap;
+use crate::{Graph, VertexId};
+
+// union-find over the weakly connected components, union by size with path halving;
+// insertions are applied in place, removals can split a component and only mark the
+// index stale so that the next query rebuilds it
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Connectivity {
+    parent: HashMap<VertexId, VertexId>,
+    size: HashMap<VertexId, usize>,
+    components: usize,
+    stale: bool,
+}
+
+impl Connectivity {
+    fn build<'a>(
+        vertices: impl Iterator<Item = &'a VertexId>,
+        edges: impl Iterator<Item = (VertexId, VertexId)>,
+    ) -> Connectivity {
+        let mut connectivity = Connectivity::default();
+        for v in vertices {
+            connectivity.insert_vertex(*v);
+        }
+        for (from, to) in edges {
+            connectivity.insert_edge(from, to);
+        }
+        connectivity
+    }
+
+    // without path halving, for queries through a shared reference
+    fn root(&self, mut vertex: VertexId) -> VertexId {
+        loop {
+            let parent = self.parent[&vertex];
+            if parent == vertex {
+                return vertex;
+            }
+            vertex = parent;
+        }
+    }
+
+    fn find(&mut self, mut vertex: VertexId) -> VertexId {
+        loop {
+            let parent = self.parent[&vertex];
+            if parent == vertex {
+                return vertex;
+            }
+            let grandparent = self.parent[&parent];
+            self.parent.insert(vertex, grandparent);
+            vertex = grandparent;
+        }
+    }
+
+    pub(crate) fn insert_vertex(&mut self, vertex: VertexId) {
+        if self.stale || self.parent.contains_key(&vertex) {
+            return;
+        }
+        self.parent.insert(vertex, vertex);
+        self.size.insert(vertex, 1);
+        self.components += 1;
+    }
+
+    pub(crate) fn insert_edge(&mut self, from: VertexId, to: VertexId) {
+        if self.stale {
+            return;
+        }
+        let (mut a, mut b) = (self.find(from), self.find(to));
+        if a == b {
+            return;
+        }
+        if self.size[&a] < self.size[&b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent.insert(b, a);
+        let merged = self.size.remove(&b).unwrap();
+        *self.size.get_mut(&a).unwrap() += merged;
+        self.components -= 1;
+    }
+
+    // called once the vertex and all of its edges are gone; a vertex that was alone in its
+    // component can't be on anyone's path to the root, so it is dropped without a rebuild
+    pub(crate) fn remove_vertex(&mut self, vertex: VertexId) {
+        if self.stale || !self.parent.contains_key(&vertex) {
+            return;
+        }
+        if self.parent[&vertex] == vertex && self.size[&vertex] == 1 {
+            self.parent.remove(&vertex);
+            self.size.remove(&vertex);
+            self.components -= 1;
+        } else {
+            self.stale = true;
+        }
+    }
+
+    pub(crate) fn components(&self) -> usize {
+        self.components
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.stale = true;
+    }
+}
+
+impl<V, E> Graph<V, E> {
+    fn connectivity(&mut self) -> &mut Connectivity {
+        if self.connectivity.as_ref().is_none_or(|c| c.stale) {
+            let edges = self
+                .adj_list
+                .iter()
+                .flat_map(|(from, adjacency)| adjacency.keys().map(move |to| (*from, *to)));
+            self.connectivity = Some(Connectivity::build(self.vertices.keys(), edges));
+        }
+        self.connectivity.as_mut().unwrap()
+    }
+
+    // edges are treated as undirected; the first query builds the index, insertions keep it
+    // up to date in place until it is disabled again. Removing an edge, or a vertex that
+    // has any, can split a component and leaves the index stale, so the next query
+    // rebuilds it in O(V + E)
+    pub fn same_component(&mut self, u: VertexId, v: VertexId) -> Option<bool> {
+        if !self.vertices.contains_key(&u) || !self.vertices.contains_key(&v) {
+            return None;
+        }
+        let connectivity = self.connectivity();
+        Some(connectivity.find(u) == connectivity.find(v))
+    }
+
+    pub fn component_count(&mut self) -> usize {
+        self.connectivity().components()
+    }
+
+    fn fresh_connectivity(&self) -> Option<&Connectivity> {
+        self.connectivity.as_ref().filter(|c| !c.stale)
+    }
+
+    // the same queries through a shared reference, answered only while the index is built
+    // and up to date; None otherwise, or when a vertex doesn't exist
+    pub fn cached_same_component(&self, u: VertexId, v: VertexId) -> Option<bool> {
+        if !self.vertices.contains_key(&u) || !self.vertices.contains_key(&v) {
+            return None;
+        }
+        let connectivity = self.fresh_connectivity()?;
+        Some(connectivity.root(u) == connectivity.root(v))
+    }
+
+    pub fn cached_component_count(&self) -> Option<usize> {
+        self.fresh_connectivity().map(Connectivity::components)
+    }
+
+    pub fn disable_connectivity_index(&mut self) {
+        self.connectivity = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn incremental_components() {
+        let mut g = Graph::<(), ()>::new();
+        assert_eq!(g.component_count(), 0);
+        for v in 1..=6 {
+            g.insert_node(v, ());
+        }
+        assert_eq!(g.component_count(), 6);
+        g.insert_edge(OrientedEdge(1, 2), ());
+        g.insert_edge(OrientedEdge(3, 2), ());
+        g.insert_edge(OrientedEdge(4, 5), ());
+        assert_eq!(g.component_count(), 3);
+        assert_eq!(g.same_component(1, 3), Some(true));
+        assert_eq!(g.same_component(1, 4), Some(false));
+        assert_eq!(g.same_component(1, 7), None);
+
+        g.remove_node(6);
+        assert_eq!(g.component_count(), 2);
+        g.remove_edge(OrientedEdge(3, 2));
+        assert_eq!(g.same_component(1, 3), Some(false));
+        assert_eq!(g.component_count(), 3);
+        g.remove_node(2);
+        g.insert_edge(OrientedEdge(3, 1), ());
+        assert_eq!(g.same_component(1, 3), Some(true));
+        assert_eq!(g.component_count(), 2);
+
+        g.disable_connectivity_index();
+        g.insert_node(7, ());
+        assert_eq!(g.component_count(), 3);
+    }
+
+    #[test]
+    fn shared_queries() {
+        let mut g = Graph::<(), ()>::new();
+        for v in 1..=4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), ());
+        assert_eq!(g.cached_component_count(), None);
+        assert_eq!(g.component_count(), 3);
+
+        // insertions keep the index fresh
+        g.insert_edge(OrientedEdge(3, 2), ());
+        let shared = &g;
+        assert_eq!(shared.cached_component_count(), Some(2));
+        assert_eq!(shared.cached_same_component(1, 3), Some(true));
+        assert_eq!(shared.cached_same_component(1, 4), Some(false));
+        assert_eq!(shared.cached_same_component(1, 9), None);
+
+        // a removal leaves it stale until the next &mut query
+        g.remove_edge(OrientedEdge(3, 2));
+        assert_eq!(g.cached_same_component(1, 3), None);
+        assert_eq!(g.same_component(1, 3), Some(false));
+        assert_eq!(g.cached_same_component(1, 3), Some(false));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::measure::MinScored;
+use crate::{Graph, Measure, Path, VertexId};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::sync::Arc;
+
+// immutable compressed sparse row snapshot of a Graph, vertices are kept sorted by id and
+// addressed internally by their position, so lookups are a binary search and adjacency is a
+// contiguous slice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrGraph<V, E> {
+    pub(crate) ids: Vec<VertexId>,
+    pub(crate) values: Vec<V>,
+    pub(crate) offsets: Vec<usize>,
+    pub(crate) targets: Vec<u32>,
+    pub(crate) weights: Vec<E>,
+}
+
+impl<V: Clone, E: Clone> Graph<V, E> {
+    pub fn freeze(&self) -> CsrGraph<V, E> {
+        let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let index_of = |id: &VertexId| ids.binary_search(id).unwrap() as u32;
+
+        let mut values = Vec::with_capacity(ids.len());
+        let mut offsets = Vec::with_capacity(ids.len() + 1);
+        let mut targets = Vec::with_capacity(self.edge_count);
+        let mut weights = Vec::with_capacity(self.edge_count);
+        offsets.push(0);
+        for id in &ids {
+            values.push(self.vertices[id].clone());
+            let mut adjacents: Vec<_> = self.adj_list[id].iter().collect();
+            adjacents.sort_unstable_by_key(|(to, _)| **to);
+            for (to, weight) in adjacents {
+                targets.push(index_of(to));
+                weights.push(weight.clone());
+            }
+            offsets.push(targets.len());
+        }
+        CsrGraph {
+            ids,
+            values,
+            offsets,
+            targets,
+            weights,
+        }
+    }
+
+    // one compact copy that any number of threads can query through clones of the Arc
+    // while this graph keeps changing
+    pub fn snapshot(&self) -> Arc<FrozenGraph<V, E>> {
+        Arc::new(self.freeze())
+    }
+}
+
+// the read-only side of Graph::snapshot, Send + Sync whenever V and E are
+pub type FrozenGraph<V, E> = CsrGraph<V, E>;
+
+impl<V, E> CsrGraph<V, E> {
+    pub fn vertex_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub(crate) fn index(&self, vertex: VertexId) -> Option<usize> {
+        self.ids.binary_search(&vertex).ok()
+    }
+
+    pub(crate) fn row(&self, index: usize) -> std::ops::Range<usize> {
+        self.offsets[index]..self.offsets[index + 1]
+    }
+
+    pub fn get_vertex_value(&self, vertex: VertexId) -> Option<&V> {
+        Some(&self.values[self.index(vertex)?])
+    }
+
+    // neighbors are yielded in ascending id order
+    pub fn get_adjacents(&self, vertex: VertexId) -> Option<Vec<VertexId>> {
+        let row = self.row(self.index(vertex)?);
+        Some(
+            self.targets[row]
+                .iter()
+                .map(|t| self.ids[*t as usize])
+                .collect(),
+        )
+    }
+
+    pub fn get_edge_value(&self, from: VertexId, to: VertexId) -> Option<&E> {
+        let row = self.row(self.index(from)?);
+        let to = self.index(to)? as u32;
+        let pos = self.targets[row.clone()].binary_search(&to).ok()?;
+        Some(&self.weights[row.start + pos])
+    }
+
+    pub fn traverse_bfs(&self) -> Vec<VertexId> {
+        let mut traverse = Vec::with_capacity(self.ids.len());
+        let mut queue = VecDeque::new();
+        let mut used = vec![false; self.ids.len()];
+
+        for start in 0..self.ids.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+            queue.push_back(start);
+            while let Some(current) = queue.pop_front() {
+                traverse.push(self.ids[current]);
+                for adjacent in &self.targets[self.row(current)] {
+                    let adjacent = *adjacent as usize;
+                    if !used[adjacent] {
+                        used[adjacent] = true;
+                        queue.push_back(adjacent);
+                    }
+                }
+            }
+        }
+        traverse
+    }
+
+    // Dijkstra, weights are expected to be non-negative
+    pub fn shortest_path(&self, from: VertexId, to: VertexId) -> Option<Path<E>>
+    where
+        E: Measure,
+    {
+        let (source, target) = (self.index(from)?, self.index(to)?);
+        let mut distance: Vec<Option<E>> = vec![None; self.ids.len()];
+        // the previous vertex and the position of the edge taken from it
+        let mut parent = vec![(usize::MAX, usize::MAX); self.ids.len()];
+        let mut heap = BinaryHeap::new();
+        distance[source] = Some(E::zero());
+        heap.push(MinScored(E::zero(), source));
+
+        while let Some(MinScored(dist, current)) = heap.pop() {
+            if distance[current]
+                .as_ref()
+                .is_some_and(|d| d.compare(&dist) == Ordering::Less)
+            {
+                continue;
+            }
+            if current == target {
+                let mut steps = Vec::new();
+                let mut v = target;
+                while v != source {
+                    let (previous, pos) = parent[v];
+                    steps.push((v, self.weights[pos].clone()));
+                    v = previous;
+                }
+                let mut path = Path::new(self.ids[source]);
+                for (v, weight) in steps.into_iter().rev() {
+                    path.push(self.ids[v], weight);
+                }
+                return Some(path);
+            }
+            for pos in self.row(current) {
+                let adjacent = self.targets[pos] as usize;
+                let Some(candidate) = dist.checked_add(&self.weights[pos]) else {
+                    continue;
+                };
+                let shorter = distance[adjacent]
+                    .as_ref()
+                    .is_none_or(|d| candidate.compare(d) == Ordering::Less);
+                if shorter {
+                    distance[adjacent] = Some(candidate.clone());
+                    parent[adjacent] = (current, pos);
+                    heap.push(MinScored(candidate, adjacent));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::sync::Arc;
+
+    fn sample() -> Graph<String, u32> {
+        let mut g = Graph::new();
+        for v in [10, 20, 30, 40, 50] {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(OrientedEdge(10, 20), 7);
+        g.insert_edge(OrientedEdge(10, 30), 2);
+        g.insert_edge(OrientedEdge(30, 20), 3);
+        g.insert_edge(OrientedEdge(20, 40), 1);
+        g.insert_edge(OrientedEdge(40, 10), 1);
+        g
+    }
+
+    #[test]
+    fn freeze() {
+        let g = sample();
+        let csr = g.freeze();
+        assert_eq!(csr.vertex_count(), 5);
+        assert_eq!(csr.edge_count(), 5);
+        assert_eq!(csr.get_vertex_value(30).unwrap(), "v30");
+        assert_eq!(csr.get_vertex_value(31), None);
+        assert_eq!(csr.get_adjacents(10).unwrap(), vec![20, 30]);
+        assert_eq!(csr.get_adjacents(50).unwrap(), Vec::<VertexId>::new());
+        assert_eq!(csr.get_edge_value(30, 20), Some(&3));
+        assert_eq!(csr.get_edge_value(20, 30), None);
+
+        let mut traverse = csr.traverse_bfs();
+        assert_eq!(traverse, vec![10, 20, 30, 40, 50]);
+        traverse.sort();
+        let mut expected = g.traverse_bfs();
+        expected.sort();
+        assert_eq!(traverse, expected);
+    }
+
+    #[test]
+    fn shortest_path() {
+        let csr = sample().freeze();
+        let path = csr.shortest_path(10, 40).unwrap();
+        assert_eq!(path.cost(), 6);
+        assert_eq!(path.vertices(), [10, 30, 20, 40]);
+        assert_eq!(path.weights(), [2, 3, 1]);
+        assert!(path.is_valid_in(&csr));
+        assert_eq!(
+            csr.shortest_path(40, 20).unwrap().vertices(),
+            [40, 10, 30, 20]
+        );
+        assert_eq!(csr.shortest_path(20, 20), Some(Path::new(20)));
+        assert_eq!(csr.shortest_path(10, 50), None);
+        assert_eq!(csr.shortest_path(10, 60), None);
+    }
+
+    #[test]
+    fn shared_snapshot() {
+        let mut g = sample();
+        let snapshot = g.snapshot();
+        g.remove_node(30);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let snapshot = Arc::clone(&snapshot);
+                s.spawn(move || {
+                    assert_eq!(snapshot.shortest_path(10, 40).unwrap().cost(), 6);
+                    assert_eq!(snapshot.get_adjacents(30), Some(vec![20]));
+                });
+            }
+        });
+        assert_eq!(g.vertex_count(), 4);
+        assert_eq!(snapshot.vertex_count(), 5);
+    }
+}
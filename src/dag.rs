@@ -0,0 +1,258 @@
+use crate::hash::{HashMap, HashSet};
+use crate::{Graph, OrientedEdge, VertexId};
+use std::fmt::Display;
+
+// the rejected edge together with the existing path from its head back to its tail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub from: VertexId,
+    pub to: VertexId,
+    pub path: Vec<VertexId>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "edge {} -> {} would close the cycle ",
+            self.from, self.to
+        )?;
+        for v in &self.path {
+            write!(f, "{} -> ", v)?;
+        }
+        write!(f, "{}", self.to)
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+// Graph wrapper that stays acyclic: a topological order is kept up to date with the
+// Pearce-Kelly algorithm, which on every insertion only searches and reorders the vertices
+// between the two endpoints in the current order
+#[derive(Debug, Clone)]
+pub struct DagGraph<V, E> {
+    graph: Graph<V, E>,
+    order: HashMap<VertexId, usize>,
+    next_order: usize,
+}
+
+impl<V, E> Default for DagGraph<V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, E> DagGraph<V, E> {
+    pub fn new() -> DagGraph<V, E> {
+        DagGraph {
+            graph: Graph::new(),
+            order: HashMap::default(),
+            next_order: 0,
+        }
+    }
+
+    pub fn graph(&self) -> &Graph<V, E> {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> Graph<V, E> {
+        self.graph
+    }
+
+    pub fn insert_node(&mut self, vertex_id: VertexId, value: V) -> Option<V> {
+        if !self.order.contains_key(&vertex_id) {
+            self.order.insert(vertex_id, self.next_order);
+            self.next_order += 1;
+        }
+        self.graph.insert_node(vertex_id, value)
+    }
+
+    // removals can't create cycles, so the order just loses an entry
+    pub fn remove_node(&mut self, vertex_id: VertexId) -> Option<V> {
+        self.order.remove(&vertex_id)?;
+        self.graph.remove_node(vertex_id)
+    }
+
+    // like Graph::insert_edge the edge is ignored unless both of its vertices exist
+    pub fn insert_edge(&mut self, edge: OrientedEdge, value: E) -> Result<Option<E>, CycleError> {
+        let OrientedEdge(from, to) = edge;
+        let (Some(&upper), Some(&lower)) = (self.order.get(&from), self.order.get(&to)) else {
+            return Ok(None);
+        };
+        if from == to {
+            return Err(CycleError {
+                from,
+                to,
+                path: vec![from],
+            });
+        }
+        if lower < upper {
+            self.reorder(from, to, lower, upper)?;
+        }
+        Ok(self.graph.insert_edge(edge, value))
+    }
+
+    pub fn remove_edge(&mut self, edge: OrientedEdge) -> Option<E> {
+        self.graph.remove_edge(edge)
+    }
+
+    // vertices ordered so that every edge points forward
+    pub fn topological_order(&self) -> Vec<VertexId> {
+        let mut vertices: Vec<VertexId> = self.order.keys().copied().collect();
+        vertices.sort_unstable_by_key(|v| self.order[v]);
+        vertices
+    }
+
+    // the new edge from -> to goes backwards in the order: collect what is reachable from
+    // `to` and what reaches `from` inside the affected window, then hand the window's slots
+    // to the backward set first
+    fn reorder(
+        &mut self,
+        from: VertexId,
+        to: VertexId,
+        lower: usize,
+        upper: usize,
+    ) -> Result<(), CycleError> {
+        let mut forward = vec![to];
+        let mut parent = HashMap::default();
+        let mut stack = vec![to];
+        while let Some(current) = stack.pop() {
+            for next in self.graph.adj_list[&current].keys() {
+                if *next == from {
+                    let mut path = vec![current];
+                    let mut v = current;
+                    while let Some(p) = parent.get(&v) {
+                        v = *p;
+                        path.push(v);
+                    }
+                    path.reverse();
+                    path.push(from);
+                    return Err(CycleError { from, to, path });
+                }
+                let order = self.order[next];
+                if order < upper && *next != to && !parent.contains_key(next) {
+                    parent.insert(*next, current);
+                    forward.push(*next);
+                    stack.push(*next);
+                }
+            }
+        }
+
+        let mut backward = vec![from];
+        let mut seen = HashSet::default();
+        seen.insert(from);
+        let mut stack = vec![from];
+        while let Some(current) = stack.pop() {
+            for previous in &self.graph.incoming[&current] {
+                if self.order[previous] > lower && seen.insert(*previous) {
+                    backward.push(*previous);
+                    stack.push(*previous);
+                }
+            }
+        }
+
+        backward.sort_unstable_by_key(|v| self.order[v]);
+        forward.sort_unstable_by_key(|v| self.order[v]);
+        let mut slots: Vec<usize> = backward
+            .iter()
+            .chain(&forward)
+            .map(|v| self.order[v])
+            .collect();
+        slots.sort_unstable();
+        for (v, slot) in backward.into_iter().chain(forward).zip(slots) {
+            self.order.insert(v, slot);
+        }
+        Ok(())
+    }
+}
+
+// fails with the first cycle found when the graph isn't acyclic
+impl<V, E> TryFrom<Graph<V, E>> for DagGraph<V, E> {
+    type Error = CycleError;
+
+    fn try_from(graph: Graph<V, E>) -> Result<Self, Self::Error> {
+        let Graph {
+            vertices, adj_list, ..
+        } = graph;
+        let mut dag = DagGraph::new();
+        for (id, value) in vertices {
+            dag.insert_node(id, value);
+        }
+        for (from, adjacency) in adj_list {
+            for (to, value) in adjacency {
+                dag.insert_edge(OrientedEdge(from, to), value)?;
+            }
+        }
+        Ok(dag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dag::CycleError;
+    use crate::*;
+
+    #[test]
+    fn rejects_cycles() {
+        let mut dag = DagGraph::<(), u32>::new();
+        for v in 1..=5 {
+            dag.insert_node(v, ());
+        }
+        // inserted against the initial order, so every edge forces a reorder
+        assert_eq!(dag.insert_edge(OrientedEdge(5, 4), 1), Ok(None));
+        assert_eq!(dag.insert_edge(OrientedEdge(4, 3), 1), Ok(None));
+        assert_eq!(dag.insert_edge(OrientedEdge(3, 1), 1), Ok(None));
+        assert_eq!(dag.insert_edge(OrientedEdge(2, 3), 1), Ok(None));
+        assert_eq!(dag.insert_edge(OrientedEdge(2, 3), 2), Ok(Some(1)));
+        assert_eq!(
+            dag.insert_edge(OrientedEdge(1, 5), 1),
+            Err(CycleError {
+                from: 1,
+                to: 5,
+                path: vec![5, 4, 3, 1]
+            })
+        );
+        let error = dag.insert_edge(OrientedEdge(2, 2), 1).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "edge 2 -> 2 would close the cycle 2 -> 2"
+        );
+        assert_eq!(dag.insert_edge(OrientedEdge(1, 9), 1), Ok(None));
+        assert_eq!(dag.graph().edge_count(), 4);
+
+        let order = dag.topological_order();
+        let position = |v| order.iter().position(|o| *o == v).unwrap();
+        for (from, to) in [(5, 4), (4, 3), (3, 1), (2, 3)] {
+            assert!(position(from) < position(to));
+        }
+
+        dag.remove_edge(OrientedEdge(4, 3));
+        assert_eq!(dag.insert_edge(OrientedEdge(1, 5), 1), Ok(None));
+        dag.remove_node(3);
+        assert_eq!(
+            dag.insert_edge(OrientedEdge(4, 1), 1),
+            Err(CycleError {
+                from: 4,
+                to: 1,
+                path: vec![1, 5, 4]
+            })
+        );
+    }
+
+    #[test]
+    fn from_graph() {
+        let mut g = Graph::<(), ()>::new();
+        for v in 0..50 {
+            g.insert_node(v, ());
+        }
+        for v in 0..49 {
+            g.insert_edge(OrientedEdge(v + 1, v), ());
+        }
+        let dag = DagGraph::try_from(g.clone()).unwrap();
+        assert_eq!(dag.topological_order(), (0..50).rev().collect::<Vec<_>>());
+        assert_eq!(dag.graph(), &g);
+
+        g.insert_edge(OrientedEdge(0, 49), ());
+        assert!(DagGraph::try_from(g).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+use crate::hash::HashSet;
+use crate::{GenericError, OrientedEdge, VertexId};
+use sled::transaction::{ConflictableTransactionResult, TransactionalTree};
+use sled::Transactional;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+fn edge_key(from: VertexId, to: VertexId) -> [u8; 8] {
+    let mut key = [0; 8];
+    key[..4].copy_from_slice(&from.to_be_bytes());
+    key[4..].copy_from_slice(&to.to_be_bytes());
+    key
+}
+
+fn second_id(key: &[u8]) -> VertexId {
+    VertexId::from_be_bytes(key[4..8].try_into().unwrap())
+}
+
+fn decode<T: FromStr>(bytes: &[u8]) -> Result<T, GenericError>
+where
+    <T as FromStr>::Err: Display,
+{
+    let text = std::str::from_utf8(bytes)?;
+    Ok(text
+        .parse::<T>()
+        .map_err(|e| format!("invalid stored value `{}`: {}", text, e))?)
+}
+
+// Graph with the vertices and edges kept in a sled database instead of memory: payloads are
+// stored as text, edges under (from, to) keys with a reverse (to, from) index for removals,
+// and sled's page cache keeps the hot part in memory; every call can fail with a storage
+// error, otherwise it behaves like the matching Graph method. Each mutation is a single
+// transaction over the trees, so a failure never leaves half of it behind
+pub struct DiskGraph<V, E> {
+    db: sled::Db,
+    vertices: sled::Tree,
+    edges: sled::Tree,
+    incoming: sled::Tree,
+    vertex_count: usize,
+    edge_count: usize,
+    payloads: PhantomData<(V, E)>,
+    // declared last so that it is dropped after every sled handle
+    _release: Release,
+}
+
+// sled's worker threads can hold the database file for a moment after the last handle is
+// gone; dropping this polls the file lock until they have let go, so that the same path can
+// be opened again as soon as the graph is dropped. It gives up after about a second, so a
+// process that opens the path in the meantime can't keep the drop waiting
+struct Release(Option<PathBuf>);
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        let Some(file) = self.0.as_ref().and_then(|db| std::fs::File::open(db).ok()) else {
+            return;
+        };
+        for _ in 0..100 {
+            match file.try_lock() {
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    std::thread::sleep(std::time::Duration::from_millis(10))
+                }
+                _ => return,
+            }
+        }
+    }
+}
+
+impl<V, E> DiskGraph<V, E>
+where
+    V: Display + FromStr,
+    E: Display + FromStr,
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    pub fn open(path: &str) -> Result<DiskGraph<V, E>, GenericError> {
+        Self::with_config(sled::Config::new().path(path), Some(path))
+    }
+
+    // cache_capacity is in bytes, sled's default is 1 GiB
+    pub fn open_with_cache(
+        path: &str,
+        cache_capacity: u64,
+    ) -> Result<DiskGraph<V, E>, GenericError> {
+        Self::with_config(
+            sled::Config::new()
+                .path(path)
+                .cache_capacity(cache_capacity),
+            Some(path),
+        )
+    }
+
+    // removed from disk when dropped
+    pub fn temporary() -> Result<DiskGraph<V, E>, GenericError> {
+        Self::with_config(sled::Config::new().temporary(true), None)
+    }
+
+    // the counts are taken from the trees, which are the only thing a mutation commits
+    fn with_config(
+        config: sled::Config,
+        path: Option<&str>,
+    ) -> Result<DiskGraph<V, E>, GenericError> {
+        let db = config.open()?;
+        let vertices = db.open_tree("vertices")?;
+        let edges = db.open_tree("edges")?;
+        Ok(DiskGraph {
+            vertex_count: vertices.len(),
+            edge_count: edges.len(),
+            vertices,
+            edges,
+            incoming: db.open_tree("incoming")?,
+            db,
+            payloads: PhantomData,
+            _release: Release(path.map(|path| PathBuf::from(path).join("db"))),
+        })
+    }
+
+    // f may run more than once when the transaction conflicts
+    fn transaction<T>(
+        &self,
+        f: impl Fn(
+            &TransactionalTree,
+            &TransactionalTree,
+            &TransactionalTree,
+        ) -> ConflictableTransactionResult<T, Infallible>,
+    ) -> Result<T, GenericError> {
+        let trees = (&self.vertices, &self.edges, &self.incoming);
+        Ok(trees.transaction(|(vertices, edges, incoming)| f(vertices, edges, incoming))?)
+    }
+
+    // blocks until everything written so far is durable
+    pub fn flush(&self) -> Result<(), GenericError> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn insert_node(
+        &mut self,
+        vertex_id: VertexId,
+        value: V,
+    ) -> Result<Option<V>, GenericError> {
+        let previous = self
+            .vertices
+            .insert(vertex_id.to_be_bytes(), value.to_string().as_bytes())?;
+        match previous {
+            Some(bytes) => Ok(Some(decode(&bytes)?)),
+            None => {
+                self.vertex_count += 1;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn remove_node(&mut self, vertex_id: VertexId) -> Result<Option<V>, GenericError> {
+        let prefix = vertex_id.to_be_bytes();
+        let mut adjacents = Vec::new();
+        for key in self.edges.scan_prefix(prefix).keys() {
+            adjacents.push(second_id(&key?));
+        }
+        let mut predecessors = Vec::new();
+        for key in self.incoming.scan_prefix(prefix).keys() {
+            predecessors.push(second_id(&key?));
+        }
+        let removed = self.transaction(|vertices, edges, incoming| {
+            let Some(value) = vertices.remove(&prefix)? else {
+                return Ok(None);
+            };
+            let mut removed_edges = 0;
+            for to in &adjacents {
+                edges.remove(&edge_key(vertex_id, *to))?;
+                incoming.remove(&edge_key(*to, vertex_id))?;
+                removed_edges += 1;
+            }
+            // a self-loop is gone by now, so it isn't counted twice
+            for from in &predecessors {
+                incoming.remove(&edge_key(vertex_id, *from))?;
+                if edges.remove(&edge_key(*from, vertex_id))?.is_some() {
+                    removed_edges += 1;
+                }
+            }
+            Ok(Some((value, removed_edges)))
+        })?;
+        let Some((value, removed_edges)) = removed else {
+            return Ok(None);
+        };
+        self.vertex_count -= 1;
+        self.edge_count -= removed_edges;
+        Ok(Some(decode(&value)?))
+    }
+
+    // the endpoints are checked in the transaction that writes the edge, so neither tree
+    // gets a key for a vertex that isn't stored
+    pub fn insert_edge(&mut self, edge: OrientedEdge, value: E) -> Result<Option<E>, GenericError> {
+        let OrientedEdge(from, to) = edge;
+        let value = value.to_string();
+        let inserted = self.transaction(|vertices, edges, incoming| {
+            if vertices.get(from.to_be_bytes())?.is_none()
+                || vertices.get(to.to_be_bytes())?.is_none()
+            {
+                return Ok(None);
+            }
+            let previous = edges.insert(&edge_key(from, to), value.as_bytes())?;
+            if previous.is_none() {
+                incoming.insert(&edge_key(to, from), &[])?;
+            }
+            Ok(Some(previous))
+        })?;
+        match inserted {
+            Some(Some(bytes)) => Ok(Some(decode(&bytes)?)),
+            Some(None) => {
+                self.edge_count += 1;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn remove_edge(&mut self, edge: OrientedEdge) -> Result<Option<E>, GenericError> {
+        let OrientedEdge(from, to) = edge;
+        let removed = self.transaction(|_, edges, incoming| {
+            let removed = edges.remove(&edge_key(from, to))?;
+            if removed.is_some() {
+                incoming.remove(&edge_key(to, from))?;
+            }
+            Ok(removed)
+        })?;
+        let Some(value) = removed else {
+            return Ok(None);
+        };
+        self.edge_count -= 1;
+        Ok(Some(decode(&value)?))
+    }
+
+    pub fn get_vertex_value(&self, vertex: VertexId) -> Result<Option<V>, GenericError> {
+        match self.vertices.get(vertex.to_be_bytes())? {
+            Some(bytes) => Ok(Some(decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_edge_value(&self, from: VertexId, to: VertexId) -> Result<Option<E>, GenericError> {
+        match self.edges.get(edge_key(from, to))? {
+            Some(bytes) => Ok(Some(decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    // neighbors are yielded in ascending id order
+    pub fn get_adjacents(&self, vertex: VertexId) -> Result<Option<Vec<VertexId>>, GenericError> {
+        if !self.vertices.contains_key(vertex.to_be_bytes())? {
+            return Ok(None);
+        }
+        let mut adjacents = Vec::new();
+        for key in self.edges.scan_prefix(vertex.to_be_bytes()).keys() {
+            adjacents.push(second_id(&key?));
+        }
+        Ok(Some(adjacents))
+    }
+
+    pub fn traverse_bfs(&self) -> Result<Vec<VertexId>, GenericError> {
+        let mut traverse = Vec::with_capacity(self.vertex_count);
+        let mut queue = VecDeque::new();
+        let mut used = HashSet::default();
+        for key in self.vertices.iter().keys() {
+            let start = VertexId::from_be_bytes(key?.as_ref().try_into()?);
+            if !used.insert(start) {
+                continue;
+            }
+            queue.push_back(start);
+            while let Some(current) = queue.pop_front() {
+                traverse.push(current);
+                for key in self.edges.scan_prefix(current.to_be_bytes()).keys() {
+                    let adjacent = second_id(&key?);
+                    if used.insert(adjacent) {
+                        queue.push_back(adjacent);
+                    }
+                }
+            }
+        }
+        Ok(traverse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disk::Release;
+    use crate::*;
+
+    #[test]
+    fn mutations() {
+        let mut g = DiskGraph::<String, u32>::temporary().unwrap();
+        for v in 1..=3 {
+            assert_eq!(g.insert_node(v, format!("v{}", v)).unwrap(), None);
+        }
+        assert_eq!(
+            g.insert_node(1, "first".to_string()).unwrap(),
+            Some("v1".to_string())
+        );
+        assert_eq!(g.insert_edge(OrientedEdge(1, 3), 13).unwrap(), None);
+        assert_eq!(g.insert_edge(OrientedEdge(1, 2), 12).unwrap(), None);
+        assert_eq!(g.insert_edge(OrientedEdge(1, 2), 21).unwrap(), Some(12));
+        assert_eq!(g.insert_edge(OrientedEdge(1, 5), 15).unwrap(), None);
+        assert_eq!(g.insert_edge(OrientedEdge(3, 3), 33).unwrap(), None);
+        assert_eq!(g.insert_edge(OrientedEdge(3, 1), 31).unwrap(), None);
+        assert_eq!(g.edge_count(), 4);
+        assert_eq!(g.get_adjacents(1).unwrap(), Some(vec![2, 3]));
+        assert_eq!(g.get_edge_value(1, 2).unwrap(), Some(21));
+
+        let mut traverse = g.traverse_bfs().unwrap();
+        traverse.sort();
+        assert_eq!(traverse, vec![1, 2, 3]);
+
+        assert_eq!(g.remove_node(3).unwrap(), Some("v3".to_string()));
+        assert_eq!(g.edge_count(), 1);
+        assert_eq!(g.get_adjacents(1).unwrap(), Some(vec![2]));
+        assert_eq!(g.get_adjacents(3).unwrap(), None);
+        assert_eq!(g.remove_edge(OrientedEdge(1, 2)).unwrap(), Some(21));
+        assert_eq!(g.remove_edge(OrientedEdge(1, 2)).unwrap(), None);
+        assert_eq!((g.vertex_count(), g.edge_count()), (2, 0));
+    }
+
+    #[test]
+    fn reopen() {
+        let path = std::env::temp_dir().join(format!("graph_disk_{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        {
+            let mut g = DiskGraph::<String, u32>::open(path).unwrap();
+            g.insert_node(1, "a".to_string()).unwrap();
+            g.insert_node(2, "b".to_string()).unwrap();
+            g.insert_edge(OrientedEdge(2, 1), 7).unwrap();
+            g.insert_edge(OrientedEdge(1, 1), 11).unwrap();
+            g.flush().unwrap();
+        }
+        // dropping waited for the database to be released, it can be opened again right away
+        let mut g = DiskGraph::<String, u32>::open_with_cache(path, 1 << 20).unwrap();
+        assert_eq!((g.vertex_count(), g.edge_count()), (2, 2));
+        g.remove_edge(OrientedEdge(1, 1)).unwrap();
+        drop(g);
+        let g = DiskGraph::<String, u32>::open(path).unwrap();
+        assert_eq!((g.vertex_count(), g.edge_count()), (2, 1));
+        assert_eq!(g.get_vertex_value(2).unwrap(), Some("b".to_string()));
+        assert_eq!(g.get_edge_value(2, 1).unwrap(), Some(7));
+        drop(g);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn release_gives_up() {
+        let path = std::env::temp_dir().join(format!("graph_disk_lock_{}", std::process::id()));
+        let held = std::fs::File::create(&path).unwrap();
+        held.lock().unwrap();
+        // someone else holding the file only delays the drop
+        let start = std::time::Instant::now();
+        drop(Release(Some(path.clone())));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        drop(held);
+        std::fs::remove_file(path).unwrap();
+    }
+}
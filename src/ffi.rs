@@ -0,0 +1,150 @@
+// C ABI over Graph<String, String>: the graph is an opaque pointer owned by the caller
+// between graph_new and graph_free, strings go in as NUL-terminated UTF-8 and come out
+// allocated by the library, to be released with graph_string_free.
+//
+// Every function expects the pointers it gets to be either NULL or valid as described
+// above, which is the whole safety contract, so it isn't repeated on each of them.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::{Graph, OrientedEdge, VertexId};
+use std::ffi::{c_char, c_int, CStr, CString};
+
+pub struct FfiGraph(Graph<String, String>);
+
+unsafe fn label(text: *const c_char) -> Option<String> {
+    if text.is_null() {
+        return Some(String::new());
+    }
+    CStr::from_ptr(text).to_str().ok().map(str::to_string)
+}
+
+#[no_mangle]
+pub extern "C" fn graph_new() -> *mut FfiGraph {
+    Box::into_raw(Box::new(FfiGraph(Graph::new())))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn graph_free(graph: *mut FfiGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+// 0 for a new vertex, 1 when an existing label was replaced, -1 on invalid arguments;
+// a NULL label is the empty one
+#[no_mangle]
+pub unsafe extern "C" fn graph_insert_node(
+    graph: *mut FfiGraph,
+    vertex: VertexId,
+    text: *const c_char,
+) -> c_int {
+    let (Some(graph), Some(text)) = (graph.as_mut(), label(text)) else {
+        return -1;
+    };
+    match graph.0.insert_node(vertex, text) {
+        None => 0,
+        Some(_) => 1,
+    }
+}
+
+// 0 for a new edge, 1 when an existing label was replaced, -1 on invalid arguments or when
+// one of the vertices doesn't exist
+#[no_mangle]
+pub unsafe extern "C" fn graph_insert_edge(
+    graph: *mut FfiGraph,
+    from: VertexId,
+    to: VertexId,
+    text: *const c_char,
+) -> c_int {
+    let (Some(graph), Some(text)) = (graph.as_mut(), label(text)) else {
+        return -1;
+    };
+    let endpoints = graph
+        .0
+        .get_vertex_value(from)
+        .and(graph.0.get_vertex_value(to));
+    if endpoints.is_none() {
+        return -1;
+    }
+    match graph.0.insert_edge(OrientedEdge(from, to), text) {
+        None => 0,
+        Some(_) => 1,
+    }
+}
+
+// fills up to `capacity` ids in BFS order and returns how many vertices there are, so a
+// call with capacity 0 sizes the buffer
+#[no_mangle]
+pub unsafe extern "C" fn graph_bfs(
+    graph: *const FfiGraph,
+    out: *mut VertexId,
+    capacity: usize,
+) -> usize {
+    let Some(graph) = graph.as_ref() else {
+        return 0;
+    };
+    let traverse = graph.0.traverse_bfs();
+    if !out.is_null() {
+        let count = traverse.len().min(capacity);
+        std::ptr::copy_nonoverlapping(traverse.as_ptr(), out, count);
+    }
+    traverse.len()
+}
+
+// NULL when a label can't be written as TGF or contains a NUL byte
+#[no_mangle]
+pub unsafe extern "C" fn graph_serialize_tgf(graph: *const FfiGraph) -> *mut c_char {
+    let Some(graph) = graph.as_ref() else {
+        return std::ptr::null_mut();
+    };
+    let mut tgf = Vec::new();
+    if graph.0.serialize_into(&mut tgf).is_err() {
+        return std::ptr::null_mut();
+    }
+    match CString::new(tgf) {
+        Ok(tgf) => tgf.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn graph_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+
+    #[test]
+    fn c_api() {
+        unsafe {
+            let g = graph_new();
+            assert_eq!(graph_insert_node(g, 1, c"first".as_ptr()), 0);
+            assert_eq!(graph_insert_node(g, 2, std::ptr::null()), 0);
+            assert_eq!(graph_insert_node(g, 2, c"second".as_ptr()), 1);
+            assert_eq!(graph_insert_edge(g, 1, 2, c"edge".as_ptr()), 0);
+            assert_eq!(graph_insert_edge(g, 1, 3, c"edge".as_ptr()), -1);
+            assert_eq!(
+                graph_insert_node(std::ptr::null_mut(), 1, c"x".as_ptr()),
+                -1
+            );
+
+            assert_eq!(graph_bfs(g, std::ptr::null_mut(), 0), 2);
+            let mut out = [0; 2];
+            assert_eq!(graph_bfs(g, out.as_mut_ptr(), out.len()), 2);
+            out.sort();
+            assert_eq!(out, [1, 2]);
+
+            let tgf = graph_serialize_tgf(g);
+            assert_eq!(
+                CStr::from_ptr(tgf).to_str().unwrap(),
+                "1 first\n2 second\n#\n1 2 edge\n"
+            );
+            graph_string_free(tgf);
+            graph_free(g);
+        }
+    }
+}
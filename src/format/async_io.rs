@@ -0,0 +1,162 @@
+use crate::format::csv::CsvLoader;
+use crate::format::tgf::GraphLoader;
+use crate::format::Format;
+use crate::{GenericError, Graph};
+use std::fmt::Display;
+use std::str::FromStr;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// TGF and CSV are parsed chunk by chunk as they arrive, JSON has no incremental parser
+// and is collected first
+enum Loader<V, E> {
+    Tgf(GraphLoader<V, E>),
+    Csv(CsvLoader<V, E>),
+    Json(Vec<u8>),
+}
+
+impl<V, E> Loader<V, E>
+where
+    V: Display + FromStr + Default,
+    E: Display + FromStr + Default,
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    fn new(format: Format) -> Loader<V, E> {
+        match format {
+            Format::Tgf => Loader::Tgf(GraphLoader::new()),
+            Format::Csv => Loader::Csv(CsvLoader::new()),
+            Format::Json => Loader::Json(Vec::new()),
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Result<(), GenericError> {
+        match self {
+            Loader::Tgf(loader) => loader.feed(chunk)?,
+            Loader::Csv(loader) => loader.feed(chunk)?,
+            Loader::Json(buffer) => buffer.extend_from_slice(chunk),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Graph<V, E>, GenericError> {
+        match self {
+            Loader::Tgf(loader) => loader.finish(),
+            Loader::Csv(loader) => loader.finish(),
+            Loader::Json(buffer) => Graph::import_cytoscape_from_reader(&buffer[..]),
+        }
+    }
+}
+
+// non-blocking counterparts of deserialize_from_reader, deserialize_csv_from_reader and
+// import_cytoscape_from_reader
+impl<V, E> Graph<V, E>
+where
+    V: Display + FromStr + Default,
+    E: Display + FromStr + Default,
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    pub async fn deserialize_from_async_reader<R: AsyncRead + Unpin>(
+        mut reader: R,
+        format: Format,
+    ) -> Result<Graph<V, E>, GenericError> {
+        let mut loader = Loader::new(format);
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut buffer).await?;
+            if read == 0 {
+                return loader.finish();
+            }
+            loader.feed(&buffer[..read])?;
+        }
+    }
+
+    // http or https, anything but a 2xx status is an error
+    pub async fn deserialize_from_url(
+        url: &str,
+        format: Format,
+    ) -> Result<Graph<V, E>, GenericError> {
+        let mut response = reqwest::get(url).await?.error_for_status()?;
+        let mut loader = Loader::new(format);
+        while let Some(chunk) = response.chunk().await? {
+            loader.feed(&chunk)?;
+        }
+        loader.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    // answers a single request with the given status line and body
+    fn serve(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut byte = [0];
+            while !request.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+                request.push(byte[0]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        format!("http://{}/graph", address)
+    }
+
+    #[tokio::test]
+    async fn async_reader() {
+        let tgf = "1 a\n2 b\n#\n1 2 7\n";
+        let g = Graph::<String, u32>::deserialize_from_async_reader(tgf.as_bytes(), Format::Tgf)
+            .await
+            .unwrap();
+        assert_eq!(g.edge_count(), 1);
+        assert_eq!(g.get_vertex_value(2).unwrap(), "b");
+
+        let csv = "source,target,value\n1,2,7\n2,3,8\n";
+        let g = Graph::<String, u32>::deserialize_from_async_reader(csv.as_bytes(), Format::Csv)
+            .await
+            .unwrap();
+        assert_eq!((g.vertex_count(), g.edge_count()), (3, 2));
+
+        let json = r#"{"nodes": [{"data": {"id": "1"}}, {"data": {"id": "2"}}],
+            "edges": [{"data": {"source": "1", "target": "2", "label": "7"}}]}"#;
+        let g = Graph::<String, u32>::deserialize_from_async_reader(json.as_bytes(), Format::Json)
+            .await
+            .unwrap();
+        assert_eq!(g.get_adjacents(1).unwrap(), vec![&2]);
+
+        let error = Graph::<String, u32>::deserialize_from_async_reader(&b"1\n"[..], Format::Tgf)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "2:1 (nodes section): # is missing");
+    }
+
+    #[tokio::test]
+    async fn url() {
+        let url = serve("200 OK", "1\n2\n#\n2 1\n");
+        let g = Graph::<String, u32>::deserialize_from_url(&url, Format::Tgf)
+            .await
+            .unwrap();
+        assert_eq!(g.get_adjacents(2).unwrap(), vec![&1]);
+
+        let url = serve("404 Not Found", "");
+        let error = Graph::<String, u32>::deserialize_from_url(&url, Format::Tgf)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("404"));
+    }
+}
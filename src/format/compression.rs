@@ -0,0 +1,258 @@
+use crate::GenericError;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    // "graph.tgf.gz" is gzip, "graph.tgf.zst" is zstd, anything else is read as is
+    pub fn from_extension(filename: &str) -> Compression {
+        match Path::new(filename).extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    pub fn reader<'a, R: Read + 'a>(
+        self,
+        reader: R,
+    ) -> Result<Box<dyn BufRead + 'a>, GenericError> {
+        match self {
+            Compression::None => Ok(Box::new(BufReader::new(reader))),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(
+                reader,
+            )))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Box::new(BufReader::new(zstd::Decoder::new(reader)?))),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    pub fn writer<W: Write>(self, writer: W) -> Result<Encoder<W>, GenericError> {
+        match self {
+            Compression::None => Ok(Encoder::Plain(BufWriter::new(writer))),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Encoder::Gzip(flate2::write::GzEncoder::new(
+                BufWriter::new(writer),
+                flate2::Compression::default(),
+            ))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Encoder::Zstd(zstd::Encoder::new(
+                BufWriter::new(writer),
+                0,
+            )?)),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    pub fn open(self, filename: &str) -> Result<Box<dyn BufRead>, GenericError> {
+        self.reader(File::open(filename)?)
+    }
+
+    // the file is only replaced once everything, codec trailer included, has been written;
+    // a failed save leaves whatever was there before untouched
+    pub fn write_file<F>(self, filename: &str, write: F) -> Result<(), GenericError>
+    where
+        F: FnOnce(&mut Encoder<File>) -> Result<(), GenericError>,
+    {
+        let (temp, file) = TempFile::create(filename)?;
+        let mut encoder = self.writer(file)?;
+        write(&mut encoder)?;
+        temp.commit(encoder.finish()?)?;
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    fn unsupported(self) -> GenericError {
+        format!(
+            "{:?} compression requires the `{}` feature",
+            self,
+            match self {
+                Compression::Gzip => "gzip",
+                _ => "zstd",
+            }
+        )
+        .into()
+    }
+}
+
+// written under a temporary name next to its target and renamed over it on commit, so
+// readers see either the old file or the complete new one; dropped uncommitted, the
+// temporary file is removed
+pub(crate) struct TempFile {
+    path: PathBuf,
+    target: PathBuf,
+    committed: bool,
+}
+
+impl TempFile {
+    pub(crate) fn create(target: &str) -> Result<(TempFile, File), GenericError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let target = PathBuf::from(target);
+        let name = target
+            .file_name()
+            .ok_or_else(|| format!("{} is not a file name", target.display()))?;
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path = target.with_file_name(temp_name);
+        let file = File::create(&path)?;
+        let temp = TempFile {
+            path,
+            target,
+            committed: false,
+        };
+        Ok((temp, file))
+    }
+
+    pub(crate) fn commit(mut self, file: File) -> io::Result<()> {
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&self.path, &self.target)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+// buffered writer that has to be finished explicitly so that codec trailers and I/O errors
+// aren't lost on drop
+pub enum Encoder<W: Write> {
+    Plain(BufWriter<W>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<BufWriter<W>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, BufWriter<W>>),
+}
+
+impl<W: Write> Encoder<W> {
+    #[allow(clippy::infallible_destructuring_match)]
+    pub fn finish(self) -> io::Result<W> {
+        let buffered = match self {
+            Encoder::Plain(w) => w,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(w) => w.finish()?,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(w) => w.finish()?,
+        };
+        buffered.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(w) => w.write(buf),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(w) => w.write(buf),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(w) => w.flush(),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(w) => w.flush(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::compression::Compression;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn detect_extension() {
+        assert_eq!(Compression::from_extension("a.tgf"), Compression::None);
+        assert_eq!(Compression::from_extension("a.tgf.gz"), Compression::Gzip);
+        assert_eq!(Compression::from_extension("a.tgf.zst"), Compression::Zstd);
+        assert_eq!(Compression::from_extension("gz"), Compression::None);
+    }
+
+    fn round_trip(compression: Compression) {
+        let mut encoder = compression.writer(Vec::new()).unwrap();
+        encoder.write_all(b"1 first\n#\n").unwrap();
+        let bytes = encoder.finish().unwrap();
+        let mut decoded = String::new();
+        compression
+            .reader(bytes.as_slice())
+            .unwrap()
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "1 first\n#\n");
+    }
+
+    #[test]
+    fn plain_round_trip() {
+        round_trip(Compression::None);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        round_trip(Compression::Gzip);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        round_trip(Compression::Zstd);
+    }
+
+    #[test]
+    fn failed_write_keeps_the_file() {
+        let dir = std::env::temp_dir().join(format!("graph-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("g.tgf");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "1 old\n#\n").unwrap();
+
+        let result = Compression::None.write_file(path, |w| {
+            w.write_all(b"1 new")?;
+            Err("label can't be written".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "1 old\n#\n");
+
+        Compression::None
+            .write_file(path, |w| Ok(w.write_all(b"1 new\n#\n")?))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "1 new\n#\n");
+        // nothing but the target is left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn gzip_requires_feature() {
+        assert!(Compression::Gzip.writer(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::format::compression::Compression;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+pub(crate) const HEADER: &str = "source,target,value";
+
+// quoted only when it has to be, so that numeric columns stay plain
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) || text.trim() != text {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// splits one record, quoted fields may contain commas, doubled quotes and line breaks; None
+// while a quoted field is still open at the end of the text, the record goes on on the next
+// line
+fn split_record(line: &str) -> Result<Option<Vec<String>>, String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Ok(None),
+                }
+            }
+            if !matches!(chars.peek(), None | Some(',')) {
+                return Err("unexpected text after a quoted field".to_string());
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                field.push(c);
+            }
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(Some(fields));
+        }
+    }
+}
+
+// edge list with a `source,target,value` header; vertices have no values of their own and
+// are created with the default one, a row with an empty target is an isolated vertex
+impl<V, E: Display> Graph<V, E> {
+    pub fn serialize_csv_to(&self, filename: &str) -> Result<(), GenericError> {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.serialize_csv_into(encoder))
+    }
+
+    pub fn serialize_csv_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.keys().copied().collect();
+        vertices.sort_unstable();
+        writeln!(writer, "{}", HEADER)?;
+        for from in vertices {
+            let adjacency = &self.adj_list[&from];
+            if adjacency.len() == 0 && self.incoming[&from].is_empty() {
+                writeln!(writer, "{},,", from)?;
+            }
+            let mut edges: Vec<_> = adjacency.iter().collect();
+            edges.sort_unstable_by_key(|(to, _)| **to);
+            for (to, value) in edges {
+                writeln!(writer, "{},{},{}", from, to, csv_field(&value.to_string()))?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<V: Default, E: FromStr + Default> Graph<V, E>
+where
+    <E as FromStr>::Err: Display,
+{
+    pub fn deserialize_csv_from(filename: &str) -> Result<Graph<V, E>, GenericError> {
+        Self::deserialize_csv_from_reader(Compression::from_extension(filename).open(filename)?)
+    }
+
+    pub fn deserialize_csv_from_reader<R: BufRead>(reader: R) -> Result<Graph<V, E>, GenericError> {
+        let mut loader = CsvLoader::new();
+        for line in reader.lines() {
+            loader.feed_line(&line?)?;
+        }
+        loader.finish()
+    }
+}
+
+// incremental CSV parser, input may be fed line by line or in arbitrary chunks
+pub struct CsvLoader<V, E> {
+    graph: Graph<V, E>,
+    line: usize,
+    pending: Vec<u8>,
+    // the lines of a record whose quoted field spans line breaks, with the line it started on
+    record: Option<(usize, String)>,
+}
+
+impl<V: Default, E: FromStr + Default> CsvLoader<V, E>
+where
+    <E as FromStr>::Err: Display,
+{
+    pub fn new() -> CsvLoader<V, E> {
+        CsvLoader {
+            graph: Graph::new(),
+            line: 0,
+            pending: Vec::new(),
+            record: None,
+        }
+    }
+
+    // chunks don't have to end on a line boundary, an incomplete tail is kept until the next call
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), GenericError> {
+        let mut rest = chunk;
+        while let Some(pos) = rest.iter().position(|&b| b == b'\n') {
+            let mut line = std::mem::take(&mut self.pending);
+            line.extend_from_slice(&rest[..pos]);
+            self.feed_bytes(&line)?;
+            rest = &rest[pos + 1..];
+        }
+        self.pending.extend_from_slice(rest);
+        Ok(())
+    }
+
+    // the header is optional, a first line that doesn't start with an id is skipped; line
+    // breaks inside quoted fields are read back as '\n'
+    pub fn feed_line(&mut self, line: &str) -> Result<(), GenericError> {
+        self.line += 1;
+        let line = line.trim_end_matches('\r');
+        let (start, record) = match self.record.take() {
+            Some((start, mut record)) => {
+                record.push('\n');
+                record.push_str(line);
+                (start, record)
+            }
+            None if line.trim().is_empty() => return Ok(()),
+            None => (self.line, line.to_string()),
+        };
+        let error = |message: String| format!("line {}: {}", start, message);
+        let Some(fields) = split_record(&record).map_err(error)? else {
+            self.record = Some((start, record));
+            return Ok(());
+        };
+        let id = |field: &str| field.trim().parse::<VertexId>();
+        let from = match id(&fields[0]) {
+            Ok(from) => from,
+            Err(_) if start == 1 => return Ok(()),
+            Err(e) => return Err(error(format!("invalid vertex id `{}`: {}", fields[0], e)).into()),
+        };
+        if fields.len() > 3 {
+            return Err(error(format!("expected at most 3 fields, got {}", fields.len())).into());
+        }
+        if self.graph.get_vertex_value(from).is_none() {
+            self.graph.insert_node(from, V::default());
+        }
+        let target = fields.get(1).map(|f| f.trim()).unwrap_or("");
+        if target.is_empty() {
+            return Ok(());
+        }
+        let to = id(target).map_err(|e| error(format!("invalid vertex id `{}`: {}", target, e)))?;
+        let value = match fields.get(2).map(String::as_str).unwrap_or("") {
+            "" => E::default(),
+            text => text
+                .parse::<E>()
+                .map_err(|e| error(format!("invalid value `{}`: {}", text, e)))?,
+        };
+        if self.graph.get_vertex_value(to).is_none() {
+            self.graph.insert_node(to, V::default());
+        }
+        self.graph.insert_edge(OrientedEdge(from, to), value);
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Graph<V, E>, GenericError> {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.feed_bytes(&line)?;
+        }
+        if let Some((start, _)) = self.record {
+            return Err(format!("line {}: unterminated quoted field", start).into());
+        }
+        Ok(self.graph)
+    }
+
+    fn feed_bytes(&mut self, line: &[u8]) -> Result<(), GenericError> {
+        match std::str::from_utf8(line) {
+            Ok(line) => self.feed_line(line),
+            Err(_) => Err(format!("line {}: invalid UTF-8", self.line + 1).into()),
+        }
+    }
+}
+
+impl<V: Default, E: FromStr + Default> Default for CsvLoader<V, E>
+where
+    <E as FromStr>::Err: Display,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::csv::CsvLoader;
+    use crate::*;
+
+    #[test]
+    fn round_trip() {
+        let mut g = Graph::<(), String>::new();
+        for v in [3, 1, 2, 7] {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), "plain".to_string());
+        g.insert_edge(OrientedEdge(2, 1), "with, \"quotes\"".to_string());
+        g.insert_edge(OrientedEdge(2, 3), String::new());
+
+        let mut csv = Vec::new();
+        g.serialize_csv_into(&mut csv).unwrap();
+        let text = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            text,
+            "source,target,value\n1,2,plain\n2,1,\"with, \"\"quotes\"\"\"\n2,3,\n7,,\n"
+        );
+        let back = Graph::<(), String>::deserialize_csv_from_reader(text.as_bytes()).unwrap();
+        assert_eq!(back, g);
+
+        // quoted line breaks, read back across lines and across chunks
+        g.insert_edge(
+            OrientedEdge(3, 7),
+            "first\n\nthird, \"quoted\"\n".to_string(),
+        );
+        let mut csv = Vec::new();
+        g.serialize_csv_into(&mut csv).unwrap();
+        let back = Graph::<(), String>::deserialize_csv_from_reader(csv.as_slice()).unwrap();
+        assert_eq!(back, g);
+        let mut loader = CsvLoader::<(), String>::new();
+        for chunk in csv.chunks(5) {
+            loader.feed(chunk).unwrap();
+        }
+        assert_eq!(loader.finish().unwrap(), g);
+
+        // no header, implicit vertices and chunks split mid-line
+        let mut loader = CsvLoader::<(), u32>::new();
+        loader.feed(b"1,2,5\r\n2,").unwrap();
+        loader.feed(b"3\n4").unwrap();
+        let g = loader.finish().unwrap();
+        assert_eq!((g.vertex_count(), g.edge_count()), (4, 2));
+        assert_eq!(g.get_adjacents(2).unwrap(), vec![&3]);
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |text: &str| Graph::<(), u32>::deserialize_csv_from_reader(text.as_bytes());
+        let error = |text: &str| parse(text).unwrap_err().to_string();
+        assert_eq!(
+            error("source,target\n1,x\n"),
+            "line 2: invalid vertex id `x`: invalid digit found in string"
+        );
+        assert_eq!(error("1,2,\"3\n"), "line 1: unterminated quoted field");
+        assert_eq!(
+            error("1,2\n2,3,\"4\n\n5\"x\n"),
+            "line 2: unexpected text after a quoted field"
+        );
+        assert_eq!(
+            error("1,2,3,4\n"),
+            "line 1: expected at most 3 fields, got 4"
+        );
+        assert_eq!(
+            error("1,2,x\n"),
+            "line 1: invalid value `x`: invalid digit found in string"
+        );
+        assert_eq!(
+            error("x\ny\n"),
+            "line 2: invalid vertex id `y`: invalid digit found in string"
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::format::compression::Compression;
+use crate::{GenericError, Graph};
+#[cfg(feature = "json")]
+use crate::{OrientedEdge, VertexId};
+use std::fmt::{Display, Write as _};
+#[cfg(feature = "json")]
+use std::io::Read;
+use std::io::Write;
+#[cfg(feature = "json")]
+use std::str::FromStr;
+
+pub(crate) fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// Cytoscape.js elements JSON: ids are strings, payloads are exported as the `label` field,
+// edge ids are "<source>-<target>"
+impl<V: Display, E: Display> Graph<V, E> {
+    pub fn export_cytoscape_to(&self, filename: &str) -> Result<(), GenericError> {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.export_cytoscape_into(encoder))
+    }
+
+    pub fn export_cytoscape_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        let mut edges: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        write!(writer, "{{\"nodes\":[")?;
+        for (i, (id, value)) in vertices.into_iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            write!(
+                writer,
+                "{{\"data\":{{\"id\":\"{}\",\"label\":{}}}}}",
+                id,
+                json_string(&value.to_string())
+            )?;
+        }
+        write!(writer, "],\"edges\":[")?;
+        for (i, (from, to, value)) in edges.into_iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            write!(
+                writer,
+                "{{\"data\":{{\"id\":\"{}-{}\",\"source\":\"{}\",\"target\":\"{}\",\"label\":{}}}}}",
+                from,
+                to,
+                from,
+                to,
+                json_string(&value.to_string())
+            )?;
+        }
+        writeln!(writer, "]}}")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn to_cytoscape_json(&self) -> String {
+        let mut json = Vec::new();
+        self.export_cytoscape_into(&mut json)
+            .expect("writing into memory doesn't fail");
+        String::from_utf8(json).expect("the output is valid UTF-8")
+    }
+}
+
+#[cfg(feature = "json")]
+fn element_field<'a>(element: &'a serde_json::Value, field: &str) -> Option<&'a serde_json::Value> {
+    element.get("data")?.get(field)
+}
+
+// ids may be given as strings (what Cytoscape.js uses) or as numbers
+#[cfg(feature = "json")]
+fn parse_element_id(element: &serde_json::Value, field: &str) -> Result<VertexId, GenericError> {
+    let id = element_field(element, field).ok_or(format!("element without `{}`", field))?;
+    let parsed = match id {
+        serde_json::Value::String(text) => text.parse().ok(),
+        serde_json::Value::Number(number) => number.as_u64().and_then(|n| n.try_into().ok()),
+        _ => None,
+    };
+    Ok(parsed.ok_or(format!("invalid {} {}", field, id))?)
+}
+
+#[cfg(feature = "json")]
+fn parse_element_label<T: FromStr + Default>(element: &serde_json::Value) -> Result<T, GenericError>
+where
+    <T as FromStr>::Err: Display,
+{
+    let text = match element_field(element, "label") {
+        None | Some(serde_json::Value::Null) => return Ok(T::default()),
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    };
+    Ok(text
+        .parse::<T>()
+        .map_err(|e| format!("invalid label `{}`: {}", text, e))?)
+}
+
+// reads what export_cytoscape_into writes, the elements may also be wrapped in an
+// `elements` object as Cytoscape desktop does; other data fields are ignored
+#[cfg(feature = "json")]
+impl<V: FromStr + Default, E: FromStr + Default> Graph<V, E>
+where
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    pub fn import_cytoscape_from(filename: &str) -> Result<Graph<V, E>, GenericError> {
+        Self::import_cytoscape_from_reader(Compression::from_extension(filename).open(filename)?)
+    }
+
+    pub fn import_cytoscape_from_reader<R: Read>(reader: R) -> Result<Graph<V, E>, GenericError> {
+        let root: serde_json::Value = serde_json::from_reader(reader)?;
+        let elements = root.get("elements").unwrap_or(&root);
+        let list = |key: &str| match elements.get(key) {
+            None => Ok(&[][..]),
+            Some(serde_json::Value::Array(list)) => Ok(&list[..]),
+            Some(_) => Err(format!("`{}` is not an array", key)),
+        };
+
+        let mut g = Graph::new();
+        for node in list("nodes")? {
+            g.insert_node(parse_element_id(node, "id")?, parse_element_label(node)?);
+        }
+        for edge in list("edges")? {
+            let from = parse_element_id(edge, "source")?;
+            let to = parse_element_id(edge, "target")?;
+            for id in [from, to] {
+                if g.get_vertex_value(id).is_none() {
+                    return Err(format!("edge {} -> {} uses unknown node {}", from, to, id).into());
+                }
+            }
+            g.insert_edge(OrientedEdge(from, to), parse_element_label(edge)?);
+        }
+        Ok(g)
+    }
+
+    pub fn from_cytoscape_json(json: &str) -> Result<Graph<V, E>, GenericError> {
+        Self::import_cytoscape_from_reader(json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn export() {
+        let mut g = Graph::<String, u32>::new();
+        g.insert_node(2, "say \"hi\"\n".to_string());
+        g.insert_node(1, "first".to_string());
+        g.insert_edge(OrientedEdge(1, 2), 7);
+        assert_eq!(
+            g.to_cytoscape_json(),
+            concat!(
+                r#"{"nodes":[{"data":{"id":"1","label":"first"}},"#,
+                r#"{"data":{"id":"2","label":"say \"hi\"\n"}}],"#,
+                r#""edges":[{"data":{"id":"1-2","source":"1","target":"2","label":"7"}}]}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn export_empty() {
+        assert_eq!(
+            Graph::<u32, u32>::new().to_cytoscape_json(),
+            "{\"nodes\":[],\"edges\":[]}\n"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn import() {
+        let mut g = Graph::<String, u32>::new();
+        g.insert_node(2, "say \"hi\"\n".to_string());
+        g.insert_node(1, "first".to_string());
+        g.insert_edge(OrientedEdge(1, 2), 7);
+        g.insert_edge(OrientedEdge(2, 2), 0);
+        assert_eq!(
+            Graph::from_cytoscape_json(&g.to_cytoscape_json()).unwrap(),
+            g
+        );
+
+        let wrapped = r#"{"elements":{"nodes":[{"data":{"id":5,"label":3}},{"data":{"id":"6"}}],
+            "edges":[{"data":{"source":5,"target":"6","label":"2","extra":true}}]}}"#;
+        let g = Graph::<u32, u32>::from_cytoscape_json(wrapped).unwrap();
+        assert_eq!(g.get_vertex_value(5), Some(&3));
+        assert_eq!(g.get_vertex_value(6), Some(&0));
+        assert_eq!(g.get_adjacents(5).unwrap(), vec![&6]);
+
+        for (json, message) in [
+            (r#"{"nodes":[{"data":{"id":"x"}}]}"#, "invalid id \"x\""),
+            (r#"{"nodes":[{"data":{}}]}"#, "element without `id`"),
+            (r#"{"nodes":{}}"#, "`nodes` is not an array"),
+            (
+                r#"{"edges":[{"data":{"source":"1","target":"2"}}]}"#,
+                "edge 1 -> 2 uses unknown node 1",
+            ),
+        ] {
+            let error = Graph::<u32, u32>::from_cytoscape_json(json).err().unwrap();
+            assert_eq!(error.to_string(), message);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::attributes::AttrMap;
+use crate::format::compression::{Compression, TempFile};
+use crate::{GenericError, Graph};
+use std::fmt::Display;
+use std::io::{BufWriter, Read, Write};
+use std::process::{Command, Stdio};
+
+pub(crate) fn dot_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// names that are DOT identifiers stay bare, anything else (keywords included) is quoted
+fn dot_id(name: &str) -> String {
+    const KEYWORDS: [&str; 6] = ["node", "edge", "graph", "digraph", "subgraph", "strict"];
+    let mut chars = name.chars();
+    let identifier = !KEYWORDS.contains(&name.to_ascii_lowercase().as_str())
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if identifier {
+        name.to_string()
+    } else {
+        dot_string(name)
+    }
+}
+
+// the label followed by the attributes, which Graphviz uses for styling (color, shape, ...);
+// a "label" attribute takes the place of the payload
+fn dot_attrs(label: &str, attrs: Option<&AttrMap>) -> String {
+    let label = match attrs.and_then(|attrs| attrs.get("label")) {
+        Some(value) => value.to_string(),
+        None => label.to_string(),
+    };
+    let mut list = format!("label={}", dot_string(&label));
+    for (key, value) in attrs
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| *key != "label")
+    {
+        list.push_str(&format!(
+            ", {}={}",
+            dot_id(key),
+            dot_string(&value.to_string())
+        ));
+    }
+    list
+}
+
+// Graphviz DOT: vertices are named by their ids and labeled with their payloads, edges are
+// labeled with theirs
+impl<V: Display, E: Display> Graph<V, E> {
+    pub fn serialize_dot_to(&self, filename: &str) -> Result<(), GenericError> {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.serialize_dot_into(encoder))
+    }
+
+    pub fn serialize_dot_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        let mut edges: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        writeln!(writer, "digraph {{")?;
+        for (id, value) in vertices {
+            let attrs = dot_attrs(&value.to_string(), self.attributes.nodes.get(id));
+            writeln!(writer, "  {} [{}];", id, attrs)?;
+        }
+        for (from, to, value) in edges {
+            let attrs = dot_attrs(&value.to_string(), self.attributes.edges.get(&(from, to)));
+            writeln!(writer, "  {} -> {} [{}];", from, to, attrs)?;
+        }
+        writeln!(writer, "}}")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn render_svg(&self, filename: &str) -> Result<(), GenericError> {
+        self.render(filename, "svg")
+    }
+
+    pub fn render_png(&self, filename: &str) -> Result<(), GenericError> {
+        self.render(filename, "png")
+    }
+
+    // lays the graph out with the `dot` binary of an installed Graphviz, `format` is anything
+    // its -T option accepts
+    pub fn render(&self, filename: &str, format: &str) -> Result<(), GenericError> {
+        self.render_with("dot", filename, format)
+    }
+
+    // the output goes to a temporary file that only replaces `filename` once the program has
+    // succeeded, and its warnings are drained on a thread so that a chatty run can't block
+    // on a full stderr pipe while we are still writing its input
+    fn render_with(&self, program: &str, filename: &str, format: &str) -> Result<(), GenericError> {
+        let (temp, file) = TempFile::create(filename)?;
+        let mut child = Command::new(program)
+            .arg(format!("-T{}", format))
+            .stdin(Stdio::piped())
+            .stdout(file.try_clone()?)
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("couldn't run `{}`, is Graphviz installed? {}", program, e))?;
+        let mut stderr = child.stderr.take().unwrap();
+        let warnings = std::thread::spawn(move || {
+            let mut text = Vec::new();
+            stderr.read_to_end(&mut text).map(|_| text)
+        });
+        // dot only starts writing once its input is closed, so the pipe is dropped first
+        let written = self.serialize_dot_into(BufWriter::new(child.stdin.take().unwrap()));
+        let status = child.wait()?;
+        let warnings = warnings.join().map_err(|_| "reading stderr panicked")??;
+        if !status.success() {
+            let warnings = String::from_utf8_lossy(&warnings);
+            return Err(format!("{} failed ({}): {}", program, status, warnings.trim()).into());
+        }
+        written?;
+        temp.commit(file)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn sample() -> Graph<String, u32> {
+        let mut g = Graph::new();
+        g.insert_node(2, "say \"hi\"\n".to_string());
+        g.insert_node(1, "first".to_string());
+        g.insert_edge(OrientedEdge(1, 2), 7);
+        g.insert_edge(OrientedEdge(2, 2), 1);
+        g
+    }
+
+    #[test]
+    fn serialize() {
+        let mut dot = Vec::new();
+        sample().serialize_dot_into(&mut dot).unwrap();
+        assert_eq!(
+            String::from_utf8(dot).unwrap(),
+            concat!(
+                "digraph {\n",
+                "  1 [label=\"first\"];\n",
+                "  2 [label=\"say \\\"hi\\\"\\n\"];\n",
+                "  1 -> 2 [label=\"7\"];\n",
+                "  2 -> 2 [label=\"1\"];\n",
+                "}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let mut g = sample();
+        g.set_node_attr(1, "color", "red");
+        g.set_node_attr(1, "penwidth", 2.5);
+        g.set_edge_attr(OrientedEdge(1, 2), "odd key", true);
+        g.set_edge_attr(OrientedEdge(1, 2), "label", "seven");
+        let mut dot = Vec::new();
+        g.serialize_dot_into(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        let lines: Vec<&str> = dot.lines().collect();
+        assert_eq!(
+            lines[1],
+            "  1 [label=\"first\", color=\"red\", penwidth=\"2.5\"];"
+        );
+        assert_eq!(
+            lines[3],
+            "  1 -> 2 [label=\"seven\", \"odd key\"=\"true\"];"
+        );
+    }
+
+    // depends on Graphviz being installed, without it only the error is checked
+    #[test]
+    fn render() {
+        let path = std::env::temp_dir().join(format!("graph_dot_{}.svg", std::process::id()));
+        let path = path.to_str().unwrap();
+        match sample().render_svg(path) {
+            Ok(()) => {
+                let svg = std::fs::read_to_string(path).unwrap();
+                assert!(svg.contains("<svg"));
+                std::fs::remove_file(path).unwrap();
+            }
+            Err(e) => assert!(e.to_string().contains("Graphviz")),
+        }
+    }
+
+    // a stand-in for dot that copies its input, after more warnings than a pipe holds
+    #[cfg(unix)]
+    #[test]
+    fn render_through_a_temporary_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("graph_render_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("fake-dot");
+        std::fs::write(
+            &program,
+            "#!/bin/sh\nhead -c 200000 /dev/zero | tr '\\0' w >&2\ncat\n[ \"$1\" = -Tsvg ]\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let (program, target) = (program.to_str().unwrap(), dir.join("g.svg"));
+        let target = target.to_str().unwrap();
+
+        sample().render_with(program, target, "svg").unwrap();
+        let mut dot = Vec::new();
+        sample().serialize_dot_into(&mut dot).unwrap();
+        assert_eq!(std::fs::read(target).unwrap(), dot);
+
+        // a failed run leaves the previous output alone
+        let err = Graph::<String, u32>::new()
+            .render_with(program, target, "png")
+            .unwrap_err();
+        assert!(err.to_string().contains("wwww"));
+        assert_eq!(std::fs::read(target).unwrap(), dot);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+use crate::attributes::{declared_types, AttrMap};
+use crate::format::compression::Compression;
+use crate::format::graphml::xml_escape;
+use crate::{GenericError, Graph};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Write;
+
+fn write_attributes<W: Write>(
+    writer: &mut W,
+    class: &str,
+    types: &BTreeMap<&str, &str>,
+) -> Result<(), GenericError> {
+    if types.is_empty() {
+        return Ok(());
+    }
+    writeln!(writer, "    <attributes class=\"{}\">", class)?;
+    for (i, (name, kind)) in types.iter().enumerate() {
+        writeln!(
+            writer,
+            "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+            i,
+            xml_escape(name),
+            kind
+        )?;
+    }
+    writeln!(writer, "    </attributes>")?;
+    Ok(())
+}
+
+// closes the element that the caller has left open after its attributes
+fn write_attvalues<W: Write>(
+    writer: &mut W,
+    element: &str,
+    types: &BTreeMap<&str, &str>,
+    attrs: Option<&AttrMap>,
+) -> Result<(), GenericError> {
+    let Some(attrs) = attrs else {
+        writeln!(writer, "/>")?;
+        return Ok(());
+    };
+    writeln!(writer, ">")?;
+    writeln!(writer, "        <attvalues>")?;
+    for (key, value) in attrs {
+        let index = types.keys().position(|k| k == key).unwrap();
+        writeln!(
+            writer,
+            "          <attvalue for=\"{}\" value=\"{}\"/>",
+            index,
+            xml_escape(&value.to_string())
+        )?;
+    }
+    writeln!(writer, "        </attvalues>")?;
+    writeln!(writer, "      </{}>", element)?;
+    Ok(())
+}
+
+// GEXF 1.3 for Gephi: payloads are the labels, attributes are declared per class and
+// attached as attvalues
+impl<V: Display, E: Display> Graph<V, E> {
+    pub fn export_gexf_to(&self, filename: &str) -> Result<(), GenericError> {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.export_gexf_into(encoder))
+    }
+
+    pub fn export_gexf_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        let mut edges: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+        let node_types = declared_types(self.attributes.nodes.values());
+        let edge_types = declared_types(self.attributes.edges.values());
+
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">"
+        )?;
+        writeln!(writer, "  <graph defaultedgetype=\"directed\">")?;
+        write_attributes(&mut writer, "node", &node_types)?;
+        write_attributes(&mut writer, "edge", &edge_types)?;
+        writeln!(writer, "    <nodes>")?;
+        for (id, value) in vertices {
+            write!(
+                writer,
+                "      <node id=\"{}\" label=\"{}\"",
+                id,
+                xml_escape(&value.to_string())
+            )?;
+            write_attvalues(
+                &mut writer,
+                "node",
+                &node_types,
+                self.attributes.nodes.get(id),
+            )?;
+        }
+        writeln!(writer, "    </nodes>")?;
+        writeln!(writer, "    <edges>")?;
+        for (i, (from, to, value)) in edges.into_iter().enumerate() {
+            write!(
+                writer,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{}\"",
+                i,
+                from,
+                to,
+                xml_escape(&value.to_string())
+            )?;
+            let attrs = self.attributes.edges.get(&(from, to));
+            write_attvalues(&mut writer, "edge", &edge_types, attrs)?;
+        }
+        writeln!(writer, "    </edges>")?;
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</gexf>")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn export() {
+        let mut g = Graph::<String, u32>::new();
+        g.insert_node(1, "a & b".to_string());
+        g.insert_node(2, "c".to_string());
+        g.insert_edge(OrientedEdge(1, 2), 7);
+        g.insert_edge(OrientedEdge(2, 1), 8);
+        g.set_node_attr(2, "rank", 4);
+        g.set_edge_attr(OrientedEdge(2, 1), "kind", "reply");
+
+        let mut xml = Vec::new();
+        g.export_gexf_into(&mut xml).unwrap();
+        assert_eq!(
+            String::from_utf8(xml).unwrap(),
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n",
+                "  <graph defaultedgetype=\"directed\">\n",
+                "    <attributes class=\"node\">\n",
+                "      <attribute id=\"0\" title=\"rank\" type=\"long\"/>\n",
+                "    </attributes>\n",
+                "    <attributes class=\"edge\">\n",
+                "      <attribute id=\"0\" title=\"kind\" type=\"string\"/>\n",
+                "    </attributes>\n",
+                "    <nodes>\n",
+                "      <node id=\"1\" label=\"a &amp; b\"/>\n",
+                "      <node id=\"2\" label=\"c\">\n",
+                "        <attvalues>\n",
+                "          <attvalue for=\"0\" value=\"4\"/>\n",
+                "        </attvalues>\n",
+                "      </node>\n",
+                "    </nodes>\n",
+                "    <edges>\n",
+                "      <edge id=\"0\" source=\"1\" target=\"2\" label=\"7\"/>\n",
+                "      <edge id=\"1\" source=\"2\" target=\"1\" label=\"8\">\n",
+                "        <attvalues>\n",
+                "          <attvalue for=\"0\" value=\"reply\"/>\n",
+                "        </attvalues>\n",
+                "      </edge>\n",
+                "    </edges>\n",
+                "  </graph>\n",
+                "</gexf>\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use crate::format::compression::Compression;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+enum Token {
+    Open,
+    Close,
+    Word(String),
+    Quoted(String),
+}
+
+enum Value {
+    Scalar(String),
+    List(Vec<(String, Value)>),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, GenericError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '[' => tokens.push((line, Token::Open)),
+            ']' => tokens.push((line, Token::Close)),
+            '"' => {
+                let start = line;
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                        }
+                        None => return Err(format!("line {}: unterminated string", start).into()),
+                    }
+                }
+                tokens.push((start, Token::Quoted(unescape(&text))));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"[]\"".contains(*c)) {
+                    word.push(c);
+                }
+                tokens.push((line, Token::Word(word)));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find('&') {
+        result += &rest[..pos];
+        rest = &rest[pos..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "quot" => Some('"'),
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "apos" => Some('\''),
+                _ => match entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity
+                        .strip_prefix('#')
+                        .and_then(|dec| dec.parse::<u32>().ok())
+                        .and_then(char::from_u32),
+                },
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result + rest
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+// far deeper than any real file, but keeps crafted input from overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+// depth 0 is the top level, which ends with the input instead of a ]
+fn parse_list(
+    tokens: &mut std::vec::IntoIter<(usize, Token)>,
+    depth: usize,
+) -> Result<Vec<(String, Value)>, GenericError> {
+    let nested = depth > 0;
+    let mut list = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some((_, Token::Word(key))) => key,
+            Some((_, Token::Close)) if nested => return Ok(list),
+            None if !nested => return Ok(list),
+            None => return Err("unexpected end of input, ] is missing".into()),
+            Some((line, _)) => return Err(format!("line {}: key expected", line).into()),
+        };
+        let value = match tokens.next() {
+            Some((line, Token::Open)) if depth == MAX_DEPTH => {
+                return Err(format!(
+                    "line {}: lists nested deeper than {} levels",
+                    line, MAX_DEPTH
+                )
+                .into());
+            }
+            Some((_, Token::Open)) => Value::List(parse_list(tokens, depth + 1)?),
+            Some((_, Token::Word(value))) | Some((_, Token::Quoted(value))) => Value::Scalar(value),
+            _ => return Err(format!("value for `{}` is missing", key).into()),
+        };
+        list.push((key, value));
+    }
+}
+
+fn scalar<'a>(list: &'a [(String, Value)], key: &str) -> Option<&'a str> {
+    list.iter().find_map(|(k, v)| match v {
+        Value::Scalar(s) if k == key => Some(s.as_str()),
+        _ => None,
+    })
+}
+
+fn parse_id(list: &[(String, Value)], key: &str, what: &str) -> Result<VertexId, GenericError> {
+    let id = scalar(list, key).ok_or(format!("{} `{}` is missing", what, key))?;
+    Ok(id
+        .parse::<VertexId>()
+        .map_err(|e| format!("invalid {} `{}` {}: {}", what, key, id, e))?)
+}
+
+fn parse_payload<T: FromStr + Default>(text: Option<&str>) -> Result<T, GenericError>
+where
+    <T as FromStr>::Err: Display,
+{
+    match text {
+        Some(text) => Ok(text
+            .parse::<T>()
+            .map_err(|e| format!("invalid label `{}`: {}", text, e))?),
+        None => Ok(T::default()),
+    }
+}
+
+// GML subset understood by NetworkX and most other tools: node ids and labels, edge sources,
+// targets and labels (or weights); unknown keys are ignored
+impl<V, E> Graph<V, E> {
+    pub fn serialize_gml_to(&self, filename: &str) -> Result<(), GenericError>
+    where
+        V: Display,
+        E: Display,
+    {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.serialize_gml_into(encoder))
+    }
+
+    pub fn serialize_gml_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError>
+    where
+        V: Display,
+        E: Display,
+    {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        let mut edges: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        writeln!(writer, "graph [")?;
+        writeln!(writer, "  directed 1")?;
+        for (id, value) in vertices {
+            writeln!(writer, "  node [")?;
+            writeln!(writer, "    id {}", id)?;
+            writeln!(writer, "    label \"{}\"", escape(&value.to_string()))?;
+            writeln!(writer, "  ]")?;
+        }
+        for (from, to, value) in edges {
+            writeln!(writer, "  edge [")?;
+            writeln!(writer, "    source {}", from)?;
+            writeln!(writer, "    target {}", to)?;
+            writeln!(writer, "    label \"{}\"", escape(&value.to_string()))?;
+            writeln!(writer, "  ]")?;
+        }
+        writeln!(writer, "]")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn deserialize_gml_from(filename: &str) -> Result<Graph<V, E>, GenericError>
+    where
+        V: FromStr + Default,
+        E: FromStr + Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        Self::deserialize_gml_from_reader(Compression::from_extension(filename).open(filename)?)
+    }
+
+    // undirected graphs get both directions for every edge
+    pub fn deserialize_gml_from_reader<R: Read>(mut reader: R) -> Result<Graph<V, E>, GenericError>
+    where
+        V: FromStr + Default,
+        E: FromStr + Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let root = parse_list(&mut tokenize(&input)?.into_iter(), 0)?;
+        let graph = root
+            .iter()
+            .find_map(|(key, value)| match value {
+                Value::List(list) if key == "graph" => Some(list),
+                _ => None,
+            })
+            .ok_or("graph [ ... ] is missing")?;
+        let directed = scalar(graph, "directed").is_some_and(|d| d != "0");
+
+        let mut g = Graph::new();
+        for (key, value) in graph {
+            if let (Value::List(node), "node") = (value, key.as_str()) {
+                let id = parse_id(node, "id", "node")?;
+                g.insert_node(id, parse_payload::<V>(scalar(node, "label"))?);
+            }
+        }
+        for (key, value) in graph {
+            if let (Value::List(edge), "edge") = (value, key.as_str()) {
+                let from = parse_id(edge, "source", "edge")?;
+                let to = parse_id(edge, "target", "edge")?;
+                for id in [from, to] {
+                    if g.get_vertex_value(id).is_none() {
+                        return Err(
+                            format!("edge {} -> {} uses unknown node {}", from, to, id).into()
+                        );
+                    }
+                }
+                let label = scalar(edge, "label").or_else(|| scalar(edge, "weight"));
+                if !directed && from != to {
+                    g.insert_edge(OrientedEdge(to, from), parse_payload::<E>(label)?);
+                }
+                g.insert_edge(OrientedEdge(from, to), parse_payload::<E>(label)?);
+            }
+        }
+        Ok(g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn read_networkx_output() {
+        let gml = r#"
+            # written by NetworkX
+            graph [
+              name "test"
+              node [
+                id 0
+                label "New &quot;York&quot; &#38; co"
+                graphics [ x 1.0 y 2.0 ]
+              ]
+              node [ id 1 label "Boston" ]
+              node [ id 2 ]
+              edge [ source 0 target 1 weight 2.5 ]
+              edge [ source 1 target 2 label "3" weight 7 ]
+            ]
+        "#;
+        let g = Graph::<String, f64>::deserialize_gml_from_reader(gml.as_bytes()).unwrap();
+        assert_eq!(g.get_vertex_value(0).unwrap(), "New \"York\" & co");
+        assert_eq!(g.get_vertex_value(2).unwrap(), "");
+        assert_eq!(g.adj_list[&0][&1], 2.5);
+        assert_eq!(g.adj_list[&1][&0], 2.5);
+        assert_eq!(g.adj_list[&2][&1], 3.0);
+    }
+
+    #[test]
+    fn read_errors() {
+        let read = |gml: &str| Graph::<String, f64>::deserialize_gml_from_reader(gml.as_bytes());
+        assert!(read("graph [ node [ id 0 ]").is_err());
+        assert!(read("graph [ node [ label \"x\" ] ]").is_err());
+        assert!(read("graph [ node [ id 0 ] edge [ source 0 target 1 ] ]").is_err());
+        assert!(read("graph [ node [ id 0 label \"x ] ]").is_err());
+        assert!(read("creator \"nobody\"").is_err());
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let read = |gml: &str| Graph::<String, f64>::deserialize_gml_from_reader(gml.as_bytes());
+        let deep = format!(
+            "graph [ {} ]",
+            "a [ ".repeat(100_000) + &"] ".repeat(100_000)
+        );
+        let err = read(&deep).err().unwrap().to_string();
+        assert_eq!(err, "line 1: lists nested deeper than 64 levels");
+        let nested =
+            |depth: usize| format!("graph [ {}{}]", "a [ ".repeat(depth), "] ".repeat(depth));
+        assert_eq!(read(&nested(63)).unwrap().vertex_count(), 0);
+        assert!(read(&nested(64)).is_err());
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut g = Graph::<String, String>::new();
+        g.insert_node(1, "a \"quoted\" & ampersand".to_string());
+        g.insert_node(2, "second".to_string());
+        g.insert_edge(OrientedEdge(1, 2), "one way".to_string());
+        g.insert_edge(OrientedEdge(2, 2), "loop".to_string());
+        let mut gml = Vec::new();
+        g.serialize_gml_into(&mut gml).unwrap();
+        let read = Graph::<String, String>::deserialize_gml_from_reader(gml.as_slice()).unwrap();
+        assert_eq!(read, g);
+    }
+}
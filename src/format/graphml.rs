@@ -0,0 +1,245 @@
+use crate::attributes::{declared_types, AttrMap};
+use crate::format::compression::Compression;
+use crate::{GenericError, Graph, VertexId};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Write;
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // not representable in XML 1.0 at all
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// the attribute keys are declared after the labels as v0, v1, ... and e0, e1, ... in the
+// order they are passed
+pub(crate) fn write_graphml_header<W: Write>(
+    writer: &mut W,
+    node_keys: &[(&str, &str)],
+    edge_keys: &[(&str, &str)],
+) -> Result<(), GenericError> {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        writer,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    writeln!(
+        writer,
+        "  <key id=\"v_label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>"
+    )?;
+    writeln!(
+        writer,
+        "  <key id=\"e_label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>"
+    )?;
+    for (prefix, target, keys) in [("v", "node", node_keys), ("e", "edge", edge_keys)] {
+        for (i, (name, kind)) in keys.iter().enumerate() {
+            writeln!(
+                writer,
+                "  <key id=\"{}{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                prefix,
+                i,
+                target,
+                xml_escape(name),
+                kind
+            )?;
+        }
+    }
+    writeln!(writer, "  <graph id=\"G\" edgedefault=\"directed\">")?;
+    Ok(())
+}
+
+pub(crate) fn write_graphml_node<W: Write>(
+    writer: &mut W,
+    id: VertexId,
+    label: &str,
+    data: &[(String, String)],
+) -> Result<(), GenericError> {
+    write!(
+        writer,
+        "    <node id=\"{}\"><data key=\"v_label\">{}</data>",
+        id,
+        xml_escape(label)
+    )?;
+    write_data(writer, data)?;
+    writeln!(writer, "</node>")?;
+    Ok(())
+}
+
+pub(crate) fn write_graphml_edge<W: Write>(
+    writer: &mut W,
+    from: VertexId,
+    to: VertexId,
+    label: &str,
+    data: &[(String, String)],
+) -> Result<(), GenericError> {
+    write!(
+        writer,
+        "    <edge source=\"{}\" target=\"{}\"><data key=\"e_label\">{}</data>",
+        from,
+        to,
+        xml_escape(label)
+    )?;
+    write_data(writer, data)?;
+    writeln!(writer, "</edge>")?;
+    Ok(())
+}
+
+// (key id, value) pairs
+fn write_data<W: Write>(writer: &mut W, data: &[(String, String)]) -> Result<(), GenericError> {
+    for (key, value) in data {
+        write!(writer, "<data key=\"{}\">{}</data>", key, xml_escape(value))?;
+    }
+    Ok(())
+}
+
+fn key_index<'a>(types: &BTreeMap<&'a str, &str>) -> BTreeMap<&'a str, usize> {
+    types.keys().enumerate().map(|(i, key)| (*key, i)).collect()
+}
+
+fn data(
+    prefix: &str,
+    keys: &BTreeMap<&str, usize>,
+    attrs: Option<&AttrMap>,
+) -> Vec<(String, String)> {
+    attrs
+        .into_iter()
+        .flatten()
+        .map(|(key, value)| {
+            (
+                format!("{}{}", prefix, keys[key.as_str()]),
+                value.to_string(),
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn write_graphml_footer<W: Write>(writer: &mut W) -> Result<(), GenericError> {
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")?;
+    Ok(())
+}
+
+// GraphML with the payloads as string `label` data on nodes and edges, readable by yEd,
+// Gephi and NetworkX; attributes become typed data keys of their own
+impl<V: Display, E: Display> Graph<V, E> {
+    pub fn export_graphml_to(&self, filename: &str) -> Result<(), GenericError> {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.export_graphml_into(encoder))
+    }
+
+    pub fn export_graphml_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        let mut edges: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        let node_types = declared_types(self.attributes.nodes.values());
+        let edge_types = declared_types(self.attributes.edges.values());
+        let node_keys = key_index(&node_types);
+        let edge_keys = key_index(&edge_types);
+
+        write_graphml_header(
+            &mut writer,
+            &node_types.into_iter().collect::<Vec<_>>(),
+            &edge_types.into_iter().collect::<Vec<_>>(),
+        )?;
+        for (id, value) in vertices {
+            let data = data("v", &node_keys, self.attributes.nodes.get(id));
+            write_graphml_node(&mut writer, *id, &value.to_string(), &data)?;
+        }
+        for (from, to, value) in edges {
+            let data = data("e", &edge_keys, self.attributes.edges.get(&(from, to)));
+            write_graphml_edge(&mut writer, from, to, &value.to_string(), &data)?;
+        }
+        write_graphml_footer(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn export() {
+        let mut g = Graph::<String, u32>::new();
+        g.insert_node(2, "<b> & \"c\"".to_string());
+        g.insert_node(1, "a".to_string());
+        g.insert_edge(OrientedEdge(2, 1), 7);
+
+        let mut xml = Vec::new();
+        g.export_graphml_into(&mut xml).unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+        let lines: Vec<&str> = xml.lines().collect();
+        assert_eq!(lines[4], "  <graph id=\"G\" edgedefault=\"directed\">");
+        assert_eq!(
+            lines[5],
+            "    <node id=\"1\"><data key=\"v_label\">a</data></node>"
+        );
+        assert_eq!(
+            lines[6],
+            "    <node id=\"2\"><data key=\"v_label\">&lt;b&gt; &amp; &quot;c&quot;</data></node>"
+        );
+        assert_eq!(
+            lines[7],
+            "    <edge source=\"2\" target=\"1\"><data key=\"e_label\">7</data></edge>"
+        );
+        assert_eq!(lines[8..], ["  </graph>", "</graphml>"]);
+    }
+
+    #[test]
+    fn attributes() {
+        let mut g = Graph::<String, u32>::new();
+        g.insert_node(1, "a".to_string());
+        g.insert_node(2, "b".to_string());
+        g.insert_edge(OrientedEdge(1, 2), 7);
+        g.set_node_attr(1, "size", 3);
+        g.set_node_attr(2, "size", 0.5);
+        g.set_node_attr(2, "kind", "<entry>");
+        g.set_edge_attr(OrientedEdge(1, 2), "seen", false);
+
+        let mut xml = Vec::new();
+        g.export_graphml_into(&mut xml).unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+        let lines: Vec<&str> = xml.lines().collect();
+        assert_eq!(
+            lines[4..7],
+            [
+                "  <key id=\"v0\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>",
+                "  <key id=\"v1\" for=\"node\" attr.name=\"size\" attr.type=\"double\"/>",
+                "  <key id=\"e0\" for=\"edge\" attr.name=\"seen\" attr.type=\"boolean\"/>",
+            ]
+        );
+        assert_eq!(
+            lines[8],
+            "    <node id=\"1\"><data key=\"v_label\">a</data><data key=\"v1\">3</data></node>"
+        );
+        assert_eq!(
+            lines[9],
+            concat!(
+                "    <node id=\"2\"><data key=\"v_label\">b</data>",
+                "<data key=\"v0\">&lt;entry&gt;</data><data key=\"v1\">0.5</data></node>"
+            )
+        );
+        assert_eq!(
+            lines[10],
+            "    <edge source=\"1\" target=\"2\"><data key=\"e_label\">7</data><data key=\"e0\">false</data></edge>"
+        );
+    }
+}
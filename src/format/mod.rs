@@ -0,0 +1,28 @@
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod compression;
+pub mod csv;
+pub mod cytoscape;
+pub mod dot;
+pub mod gexf;
+pub mod gml;
+pub mod graphml;
+pub mod mtx;
+pub mod neo4j;
+pub mod stream;
+pub mod tgf;
+pub mod tikz;
+
+pub use compression::Compression;
+pub use mtx::MtxOptions;
+pub use neo4j::Neo4jOptions;
+pub use tikz::TikzLayout;
+
+// the formats that can be read from a single stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Tgf,
+    Csv,
+    #[cfg(feature = "json")]
+    Json,
+}
@@ -0,0 +1,349 @@
+use crate::format::compression::Compression;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MtxOptions {
+    // rows and columns without a single entry are only vouched for by the size line, so at
+    // most this many of them are created, 2^20 by default; everything else is bounded by the
+    // entries actually read. Raise it for matrices known to have more empty rows/columns
+    pub max_isolated_vertices: u64,
+}
+
+impl Default for MtxOptions {
+    fn default() -> Self {
+        MtxOptions {
+            max_isolated_vertices: 1 << 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Real,
+    Integer,
+    Pattern,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symmetry {
+    General,
+    Symmetric,
+    SkewSymmetric,
+}
+
+fn parse_header(line: &str) -> Result<(Field, Symmetry), GenericError> {
+    let tokens: Vec<String> = line.split_whitespace().map(str::to_lowercase).collect();
+    let tokens: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    match tokens[..] {
+        ["%%matrixmarket", "matrix", "coordinate", field, symmetry] => {
+            let field = match field {
+                "real" => Field::Real,
+                "integer" => Field::Integer,
+                "pattern" => Field::Pattern,
+                _ => return Err(format!("unsupported field `{}`", field).into()),
+            };
+            let symmetry = match symmetry {
+                "general" => Symmetry::General,
+                "symmetric" => Symmetry::Symmetric,
+                "skew-symmetric" => Symmetry::SkewSymmetric,
+                _ => return Err(format!("unsupported symmetry `{}`", symmetry).into()),
+            };
+            Ok((field, symmetry))
+        }
+        ["%%matrixmarket", "matrix", format, ..] if format != "coordinate" => {
+            Err(format!("unsupported format `{}`, only coordinate is", format).into())
+        }
+        _ => Err("invalid Matrix Market header".into()),
+    }
+}
+
+fn negate(value: &str) -> String {
+    match value.strip_prefix('-') {
+        Some(positive) => positive.to_string(),
+        None => format!("-{}", value.strip_prefix('+').unwrap_or(value)),
+    }
+}
+
+// Matrix Market coordinate format, entry (i, j) is the edge i - 1 -> j - 1
+impl<V, E> Graph<V, E> {
+    pub fn serialize_mtx_to(&self, filename: &str) -> Result<(), GenericError>
+    where
+        E: Display,
+    {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.serialize_mtx_into(encoder))
+    }
+
+    // the matrix is square with a row for every id up to the largest one, entries are sorted
+    pub fn serialize_mtx_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError>
+    where
+        E: Display,
+    {
+        let size = self.vertices.keys().max().map_or(0, |max| *max as u64 + 1);
+        let mut entries: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        entries.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        writeln!(writer, "%%MatrixMarket matrix coordinate real general")?;
+        writeln!(writer, "{} {} {}", size, size, entries.len())?;
+        for (from, to, value) in entries {
+            writeln!(writer, "{} {} {}", from as u64 + 1, to as u64 + 1, value)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn deserialize_mtx_from(filename: &str) -> Result<Graph<V, E>, GenericError>
+    where
+        V: Default,
+        E: FromStr + Default,
+        <E as FromStr>::Err: Display,
+    {
+        Self::deserialize_mtx_from_reader(Compression::from_extension(filename).open(filename)?)
+    }
+
+    pub fn deserialize_mtx_from_reader<R: BufRead>(reader: R) -> Result<Graph<V, E>, GenericError>
+    where
+        V: Default,
+        E: FromStr + Default,
+        <E as FromStr>::Err: Display,
+    {
+        Self::deserialize_mtx_from_reader_with(reader, &MtxOptions::default())
+    }
+
+    // every row/column becomes a vertex with the default value, pattern entries get the
+    // default weight and symmetric matrices produce edges in both directions; vertices are
+    // created as the entries name them and the isolated ones once all entries are read
+    pub fn deserialize_mtx_from_reader_with<R: BufRead>(
+        reader: R,
+        options: &MtxOptions,
+    ) -> Result<Graph<V, E>, GenericError>
+    where
+        V: Default,
+        E: FromStr + Default,
+        <E as FromStr>::Err: Display,
+    {
+        let mut lines = reader.lines().enumerate();
+        let (field, symmetry) = match lines.next() {
+            Some((_, line)) => parse_header(&line?).map_err(|e| format!("line 1: {}", e))?,
+            None => return Err("line 1: Matrix Market header is missing".into()),
+        };
+
+        let mut g = Graph::new();
+        let mut size = None;
+        let mut entries = 0;
+        for (i, line) in lines {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('%') {
+                continue;
+            }
+            let error =
+                |message: String| -> GenericError { format!("line {}: {}", i + 1, message).into() };
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            let (rows, cols, nnz) = match size {
+                Some(size) => size,
+                None => {
+                    let parsed: Vec<u64> = match tokens[..] {
+                        [_, _, _] => tokens
+                            .iter()
+                            .map(|t| t.parse::<u64>())
+                            .collect::<Result<_, _>>()
+                            .map_err(|e| error(format!("invalid size line: {}", e)))?,
+                        _ => {
+                            return Err(error("size line must be `rows cols entries`".to_string()))
+                        }
+                    };
+                    let dimension = parsed[0].max(parsed[1]);
+                    if dimension > VertexId::MAX as u64 + 1 {
+                        return Err(error(format!(
+                            "{} rows/columns don't fit into vertex ids",
+                            dimension
+                        )));
+                    }
+                    size = Some((parsed[0], parsed[1], parsed[2]));
+                    continue;
+                }
+            };
+
+            let (row, col, value) = match (field, &tokens[..]) {
+                (Field::Pattern, [row, col]) => (*row, *col, None),
+                (Field::Real | Field::Integer, [row, col, value]) => (*row, *col, Some(*value)),
+                _ => return Err(error(format!("malformed entry `{}`", line))),
+            };
+            let index = |token: &str, bound: u64| -> Result<VertexId, GenericError> {
+                match token.parse::<u64>() {
+                    Ok(index) if (1..=bound).contains(&index) => Ok((index - 1) as VertexId),
+                    Ok(index) => Err(error(format!("index {} is out of 1..={}", index, bound))),
+                    Err(e) => Err(error(format!("invalid index `{}`: {}", token, e))),
+                }
+            };
+            let (from, to) = (index(row, rows)?, index(col, cols)?);
+            let weight = |value: &str| -> Result<E, GenericError> {
+                value
+                    .parse::<E>()
+                    .map_err(|e| error(format!("invalid value `{}`: {}", value, e)))
+            };
+
+            entries += 1;
+            if entries > nnz {
+                return Err(error(format!("more than the declared {} entries", nnz)));
+            }
+            for v in [from, to] {
+                if !g.vertices.contains_key(&v) {
+                    g.insert_node(v, V::default());
+                }
+            }
+            match value {
+                Some(value) => {
+                    if from != to {
+                        match symmetry {
+                            Symmetry::General => {}
+                            Symmetry::Symmetric => {
+                                g.insert_edge(OrientedEdge(to, from), weight(value)?);
+                            }
+                            Symmetry::SkewSymmetric => {
+                                g.insert_edge(OrientedEdge(to, from), weight(&negate(value))?);
+                            }
+                        }
+                    }
+                    g.insert_edge(OrientedEdge(from, to), weight(value)?);
+                }
+                None => {
+                    if from != to && symmetry != Symmetry::General {
+                        g.insert_edge(OrientedEdge(to, from), E::default());
+                    }
+                    g.insert_edge(OrientedEdge(from, to), E::default());
+                }
+            }
+        }
+        match size {
+            None => Err("size line is missing".into()),
+            Some((_, _, nnz)) if entries != nnz => {
+                Err(format!("expected {} entries, found {}", nnz, entries).into())
+            }
+            Some((rows, cols, _)) => {
+                let dimension = rows.max(cols);
+                let isolated = dimension - g.vertices.len() as u64;
+                if isolated > options.max_isolated_vertices {
+                    return Err(format!(
+                        "{} of the {} rows/columns have no entries, max_isolated_vertices is {}",
+                        isolated, dimension, options.max_isolated_vertices
+                    )
+                    .into());
+                }
+                for v in 0..dimension {
+                    if !g.vertices.contains_key(&(v as VertexId)) {
+                        g.insert_node(v as VertexId, V::default());
+                    }
+                }
+                Ok(g)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn load(input: &str) -> Result<Graph<(), f64>, GenericError> {
+        Graph::deserialize_mtx_from_reader(input.as_bytes())
+    }
+
+    #[test]
+    fn read_general() {
+        let g = load(
+            "%%MatrixMarket matrix coordinate real general\n\
+             % a comment\n\
+             \n\
+             4 4 3\n\
+             1 2 0.5\n\
+             2 3 -1e3\n\
+             4 4 2\n",
+        )
+        .unwrap();
+        let mut vertices = g.traverse_bfs();
+        vertices.sort();
+        assert_eq!(vertices, vec![0, 1, 2, 3]);
+        assert_eq!(g.adj_list[&0][&1], 0.5);
+        assert_eq!(g.adj_list[&1][&2], -1000.0);
+        assert_eq!(g.adj_list[&3][&3], 2.0);
+        assert!(!g.adj_list[&1].contains_key(&0));
+    }
+
+    #[test]
+    fn read_symmetric_and_pattern() {
+        let g =
+            load("%%MatrixMarket matrix coordinate real skew-symmetric\n3 3 1\n2 1 1.5\n").unwrap();
+        assert_eq!(g.adj_list[&1][&0], 1.5);
+        assert_eq!(g.adj_list[&0][&1], -1.5);
+
+        let g =
+            load("%%MatrixMarket matrix coordinate pattern symmetric\n3 3 2\n2 1\n3 3\n").unwrap();
+        assert_eq!(g.adj_list[&0][&1], 0.0);
+        assert_eq!(g.adj_list[&1][&0], 0.0);
+        assert_eq!(g.adj_list[&2].len(), 1);
+    }
+
+    #[test]
+    fn read_errors() {
+        assert!(load("%%MatrixMarket matrix array real general\n2 2\n1\n2\n3\n4\n").is_err());
+        assert!(load("%%MatrixMarket matrix coordinate complex general\n1 1 0\n").is_err());
+        let err = load("%%MatrixMarket matrix coordinate real general\n2 2 1\n3 1 1\n")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "line 3: index 3 is out of 1..=2");
+        assert!(load("%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1\n").is_err());
+        // the size line alone doesn't get to allocate billions of vertices
+        let err = load("%%MatrixMarket matrix coordinate real general\n4294967296 1 1\n1 1 1\n")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "4294967295 of the 4294967296 rows/columns have no entries, max_isolated_vertices \
+             is 1048576"
+        );
+        // the limit is the caller's to choose
+        let input = "%%MatrixMarket matrix coordinate pattern general\n100 100 1\n1 2\n";
+        assert_eq!(load(input).unwrap().vertex_count(), 100);
+        let strict = format::MtxOptions {
+            max_isolated_vertices: 10,
+        };
+        let err = Graph::<(), f64>::deserialize_mtx_from_reader_with(input.as_bytes(), &strict)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "98 of the 100 rows/columns have no entries, max_isolated_vertices is 10"
+        );
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut g = Graph::<(), f64>::new();
+        g.insert_node(0, ());
+        g.insert_node(2, ());
+        g.insert_edge(OrientedEdge(2, 0), 1.25);
+        g.insert_edge(OrientedEdge(0, 2), -3.0);
+        let mut mtx = Vec::new();
+        g.serialize_mtx_into(&mut mtx).unwrap();
+        assert_eq!(
+            String::from_utf8(mtx.clone()).unwrap(),
+            "%%MatrixMarket matrix coordinate real general\n3 3 2\n1 3 -3\n3 1 1.25\n"
+        );
+        let read = Graph::<(), f64>::deserialize_mtx_from_reader(mtx.as_slice()).unwrap();
+        assert_eq!(read.adj_list, {
+            let mut expected = g.adj_list.clone();
+            expected.insert(1, Default::default());
+            expected
+        });
+    }
+}
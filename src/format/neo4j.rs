@@ -0,0 +1,123 @@
+use crate::format::compression::{Compression, TempFile};
+use crate::format::csv::csv_field;
+use crate::{GenericError, Graph};
+use std::fmt::Display;
+use std::io::Write;
+
+// names and types used in the CSV headers, property columns are "<name>:<type>" with any
+// type neo4j-admin understands (string, int, float, boolean, ...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neo4jOptions {
+    pub node_label: String,
+    pub relationship_type: String,
+    pub node_property: String,
+    pub relationship_property: String,
+}
+
+impl Default for Neo4jOptions {
+    fn default() -> Self {
+        Neo4jOptions {
+            node_label: "Vertex".to_string(),
+            relationship_type: "EDGE".to_string(),
+            node_property: "value:string".to_string(),
+            relationship_property: "value:string".to_string(),
+        }
+    }
+}
+
+// the node/relationship file pair for `neo4j-admin database import full --nodes=...
+// --relationships=...`; values with line breaks additionally need --multiline-fields=true
+impl<V: Display, E: Display> Graph<V, E> {
+    pub fn export_neo4j_to(
+        &self,
+        nodes_filename: &str,
+        relationships_filename: &str,
+        options: &Neo4jOptions,
+    ) -> Result<(), GenericError> {
+        // both files are only replaced once both have been written in full
+        let (nodes_temp, nodes_file) = TempFile::create(nodes_filename)?;
+        let (relationships_temp, relationships_file) = TempFile::create(relationships_filename)?;
+        let mut nodes = Compression::from_extension(nodes_filename).writer(nodes_file)?;
+        let mut relationships =
+            Compression::from_extension(relationships_filename).writer(relationships_file)?;
+        self.export_neo4j_into(&mut nodes, &mut relationships, options)?;
+        let (nodes_file, relationships_file) = (nodes.finish()?, relationships.finish()?);
+        nodes_temp.commit(nodes_file)?;
+        relationships_temp.commit(relationships_file)?;
+        Ok(())
+    }
+
+    pub fn export_neo4j_into<N: Write, R: Write>(
+        &self,
+        mut nodes: N,
+        mut relationships: R,
+        options: &Neo4jOptions,
+    ) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        let mut edges: Vec<_> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.iter().map(move |(to, value)| (*from, *to, value)))
+            .collect();
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        writeln!(nodes, ":ID,{},:LABEL", csv_field(&options.node_property))?;
+        let label = csv_field(&options.node_label);
+        for (id, value) in vertices {
+            writeln!(nodes, "{},{},{}", id, csv_field(&value.to_string()), label)?;
+        }
+        writeln!(
+            relationships,
+            ":START_ID,:END_ID,{},:TYPE",
+            csv_field(&options.relationship_property)
+        )?;
+        let relationship_type = csv_field(&options.relationship_type);
+        for (from, to, value) in edges {
+            writeln!(
+                relationships,
+                "{},{},{},{}",
+                from,
+                to,
+                csv_field(&value.to_string()),
+                relationship_type
+            )?;
+        }
+        nodes.flush()?;
+        relationships.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::neo4j::Neo4jOptions;
+    use crate::*;
+
+    #[test]
+    fn export() {
+        let mut g = Graph::<String, u32>::new();
+        g.insert_node(2, "say \"hi\", bye".to_string());
+        g.insert_node(1, "first".to_string());
+        g.insert_edge(OrientedEdge(1, 2), 7);
+        g.insert_edge(OrientedEdge(2, 1), 3);
+
+        let (mut nodes, mut relationships) = (Vec::new(), Vec::new());
+        let options = Neo4jOptions {
+            node_label: "Person".to_string(),
+            relationship_type: "KNOWS".to_string(),
+            node_property: "name:string".to_string(),
+            relationship_property: "weight:int".to_string(),
+        };
+        g.export_neo4j_into(&mut nodes, &mut relationships, &options)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(nodes).unwrap(),
+            ":ID,name:string,:LABEL\n1,first,Person\n2,\"say \"\"hi\"\", bye\",Person\n"
+        );
+        assert_eq!(
+            String::from_utf8(relationships).unwrap(),
+            ":START_ID,:END_ID,weight:int,:TYPE\n1,2,7,KNOWS\n2,1,3,KNOWS\n"
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::format::csv::{csv_field, HEADER};
+use crate::format::cytoscape::json_string;
+use crate::format::dot::dot_string;
+use crate::format::graphml::{
+    write_graphml_edge, write_graphml_footer, write_graphml_header, write_graphml_node,
+};
+use crate::format::tgf::{write_label, TgfReader, TgfRecord};
+use crate::hash::HashSet;
+use crate::{GenericError, VertexId};
+use std::io::{BufRead, Write};
+
+// the formats a TGF stream can be converted to record by record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    Tgf,
+    Csv,
+    Json,
+    Dot,
+    GraphMl,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StreamOptions {
+    // an edge listed twice is written out twice unless this is set, in which case it's
+    // rejected; off by default since it means keeping every edge seen so far
+    pub reject_duplicate_edges: bool,
+}
+
+pub fn convert_tgf<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    format: StreamFormat,
+) -> Result<(), GenericError> {
+    convert_tgf_with(reader, writer, format, &StreamOptions::default())
+}
+
+// converts without ever holding the graph: only the ids of the vertices seen so far are
+// kept, so a vertex listed twice is rejected, and the edges are written as they're read;
+// unlike the serializers the records keep their input order, and CSV lists every vertex as
+// an `id,,` row since it can't know yet which ones stay isolated
+pub fn convert_tgf_with<R: BufRead, W: Write>(
+    reader: R,
+    mut writer: W,
+    format: StreamFormat,
+    options: &StreamOptions,
+) -> Result<(), GenericError> {
+    let mut separated = false;
+    let mut first = true;
+    let mut vertices: HashSet<VertexId> = HashSet::default();
+    let mut edges: HashSet<(VertexId, VertexId)> = HashSet::default();
+    match format {
+        StreamFormat::Csv => writeln!(writer, "{}", HEADER)?,
+        StreamFormat::Json => write!(writer, "{{\"nodes\":[")?,
+        StreamFormat::Dot => writeln!(writer, "digraph {{")?,
+        StreamFormat::GraphMl => write_graphml_header(&mut writer, &[], &[])?,
+        StreamFormat::Tgf => {}
+    }
+    for record in TgfReader::new(reader) {
+        match record? {
+            TgfRecord::Node { id, .. } if !vertices.insert(id) => {
+                return Err(format!("vertex {} is listed twice", id).into());
+            }
+            TgfRecord::Edge { from, to, .. }
+                if options.reject_duplicate_edges && !edges.insert((from, to)) =>
+            {
+                return Err(format!("edge {} -> {} is listed twice", from, to).into());
+            }
+            TgfRecord::Node { id, label } => match format {
+                StreamFormat::Tgf => {
+                    write!(writer, "{}", id)?;
+                    write_label(&mut writer, &label)?;
+                    writeln!(writer)?;
+                }
+                StreamFormat::Csv => writeln!(writer, "{},,", id)?,
+                StreamFormat::Json => {
+                    if !std::mem::take(&mut first) {
+                        write!(writer, ",")?;
+                    }
+                    write!(
+                        writer,
+                        "{{\"data\":{{\"id\":\"{}\",\"label\":{}}}}}",
+                        id,
+                        json_string(&label)
+                    )?;
+                }
+                StreamFormat::Dot => writeln!(writer, "  {} [label={}];", id, dot_string(&label))?,
+                StreamFormat::GraphMl => write_graphml_node(&mut writer, id, &label, &[])?,
+            },
+            TgfRecord::Edge { from, to, label } => {
+                if !std::mem::replace(&mut separated, true) {
+                    close_nodes(&mut writer, format)?;
+                    first = true;
+                }
+                match format {
+                    StreamFormat::Tgf => {
+                        write!(writer, "{} {}", from, to)?;
+                        write_label(&mut writer, &label)?;
+                        writeln!(writer)?;
+                    }
+                    StreamFormat::Csv => writeln!(writer, "{},{},{}", from, to, csv_field(&label))?,
+                    StreamFormat::Json => {
+                        if !std::mem::take(&mut first) {
+                            write!(writer, ",")?;
+                        }
+                        write!(
+                            writer,
+                            "{{\"data\":{{\"id\":\"{}-{}\",\"source\":\"{}\",\"target\":\"{}\",\"label\":{}}}}}",
+                            from,
+                            to,
+                            from,
+                            to,
+                            json_string(&label)
+                        )?;
+                    }
+                    StreamFormat::Dot => writeln!(
+                        writer,
+                        "  {} -> {} [label={}];",
+                        from,
+                        to,
+                        dot_string(&label)
+                    )?,
+                    StreamFormat::GraphMl => {
+                        write_graphml_edge(&mut writer, from, to, &label, &[])?
+                    }
+                }
+            }
+        }
+    }
+    if !separated {
+        close_nodes(&mut writer, format)?;
+    }
+    match format {
+        StreamFormat::Json => writeln!(writer, "]}}")?,
+        StreamFormat::Dot => writeln!(writer, "}}")?,
+        StreamFormat::GraphMl => write_graphml_footer(&mut writer)?,
+        StreamFormat::Tgf | StreamFormat::Csv => {}
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn close_nodes<W: Write>(writer: &mut W, format: StreamFormat) -> Result<(), GenericError> {
+    match format {
+        StreamFormat::Tgf => writeln!(writer, "#")?,
+        StreamFormat::Json => write!(writer, "],\"edges\":[")?,
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::stream::{convert_tgf, convert_tgf_with, StreamFormat, StreamOptions};
+    use crate::*;
+
+    const INPUT: &str = "2 b\n1 \"a\"\n3\n#\n2 1 x, y\n1 3\n";
+
+    fn convert(format: StreamFormat) -> String {
+        let mut output = Vec::new();
+        convert_tgf(INPUT.as_bytes(), &mut output, format).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn expected(format: StreamFormat) -> String {
+        let g = Graph::<String, String>::deserialize_from_reader(INPUT.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        match format {
+            StreamFormat::Tgf => g.serialize_into(&mut output),
+            StreamFormat::Csv => g.serialize_csv_into(&mut output),
+            StreamFormat::Json => g.export_cytoscape_into(&mut output),
+            StreamFormat::Dot => g.serialize_dot_into(&mut output),
+            StreamFormat::GraphMl => g.export_graphml_into(&mut output),
+        }
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn sorted_lines(text: &str) -> Vec<&str> {
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[test]
+    fn matches_the_serializers() {
+        // same records, only the order differs
+        for format in [StreamFormat::Tgf, StreamFormat::Dot, StreamFormat::GraphMl] {
+            assert_eq!(
+                sorted_lines(&convert(format)),
+                sorted_lines(&expected(format))
+            );
+        }
+        assert_eq!(
+            convert(StreamFormat::Csv),
+            "source,target,value\n2,,\n1,,\n3,,\n2,1,\"x, y\"\n1,3,\n"
+        );
+        assert_eq!(
+            convert(StreamFormat::Json),
+            concat!(
+                "{\"nodes\":[{\"data\":{\"id\":\"2\",\"label\":\"b\"}},",
+                "{\"data\":{\"id\":\"1\",\"label\":\"\\\"a\\\"\"}},",
+                "{\"data\":{\"id\":\"3\",\"label\":\"\"}}],",
+                "\"edges\":[{\"data\":{\"id\":\"2-1\",\"source\":\"2\",\"target\":\"1\",\"label\":\"x, y\"}},",
+                "{\"data\":{\"id\":\"1-3\",\"source\":\"1\",\"target\":\"3\",\"label\":\"\"}}]}\n"
+            )
+        );
+        let csv = convert(StreamFormat::Csv);
+        let g = Graph::<String, String>::deserialize_csv_from_reader(csv.as_bytes()).unwrap();
+        assert_eq!(g.edge_count(), 2);
+        assert_eq!(g.vertex_count(), 3);
+    }
+
+    #[test]
+    fn without_edges() {
+        let mut output = Vec::new();
+        convert_tgf("1 a\n#\n".as_bytes(), &mut output, StreamFormat::Json).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"nodes\":[{\"data\":{\"id\":\"1\",\"label\":\"a\"}}],\"edges\":[]}\n"
+        );
+        let mut output = Vec::new();
+        convert_tgf("1 a\n#\n".as_bytes(), &mut output, StreamFormat::Tgf).unwrap();
+        assert_eq!(output, b"1 a\n#\n");
+    }
+
+    #[test]
+    fn invalid_input() {
+        let error = convert_tgf("1\n#\n1 2\n".as_bytes(), Vec::new(), StreamFormat::Dot);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "3:3 (edges section): unknown vertex near `2`"
+        );
+        let error = convert_tgf("1\n1 a\n#\n".as_bytes(), Vec::new(), StreamFormat::Csv);
+        assert_eq!(error.unwrap_err().to_string(), "vertex 1 is listed twice");
+        let input = "1\n2\n#\n1 2\n2 1\n1 2 x\n";
+        let mut output = Vec::new();
+        convert_tgf(input.as_bytes(), &mut output, StreamFormat::Tgf).unwrap();
+        assert_eq!(output, input.as_bytes());
+        let options = StreamOptions {
+            reject_duplicate_edges: true,
+        };
+        let error = convert_tgf_with(input.as_bytes(), Vec::new(), StreamFormat::Tgf, &options);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "edge 1 -> 2 is listed twice"
+        );
+    }
+}
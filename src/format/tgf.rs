@@ -0,0 +1,692 @@
+use crate::format::compression::Compression;
+use crate::hash::HashSet;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Nodes,
+    Edges,
+}
+
+// line and column are 1-based, text is the offending token (or the whole line)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub section: Section,
+    pub text: String,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let section = match self.section {
+            Section::Nodes => "nodes",
+            Section::Edges => "edges",
+        };
+        write!(
+            f,
+            "{}:{} ({} section): {}",
+            self.line, self.column, section, self.message
+        )?;
+        if !self.text.is_empty() {
+            write!(f, " near `{}`", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// one line split into its tokens, labels are still unparsed
+enum Line<'a> {
+    Blank,
+    Separator,
+    Node(VertexId, &'a str),
+    // the endpoint tokens are kept for error reporting
+    Edge {
+        from: (VertexId, &'a str),
+        to: (VertexId, &'a str),
+        label: &'a str,
+    },
+}
+
+struct LineContext<'a> {
+    number: usize,
+    line: &'a str,
+    section: Section,
+}
+
+impl<'a> LineContext<'a> {
+    fn split(&self) -> Result<Line<'a>, ParseError> {
+        let line = self.line;
+        if line.trim().is_empty() {
+            return Ok(Line::Blank);
+        }
+        match self.section {
+            Section::Nodes if line.trim() == "#" => Ok(Line::Separator),
+            Section::Nodes => {
+                let (id, label) = split_token(line);
+                Ok(Line::Node(self.parse_id(id)?, label))
+            }
+            Section::Edges => {
+                let (from, suffix) = split_token(line);
+                let (to, label) = split_token(suffix);
+                if to.is_empty() {
+                    return Err(self.error(line.trim(), "vertex_to is missing"));
+                }
+                Ok(Line::Edge {
+                    from: (self.parse_id(from)?, from),
+                    to: (self.parse_id(to)?, to),
+                    label,
+                })
+            }
+        }
+    }
+
+    fn error(&self, token: &str, message: impl Display) -> ParseError {
+        // tokens are always subslices of the line, so the offset is well-defined
+        let offset = token.as_ptr() as usize - self.line.as_ptr() as usize;
+        ParseError {
+            line: self.number,
+            column: self.line[..offset].chars().count() + 1,
+            section: self.section,
+            text: token.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn parse_id(&self, token: &str) -> Result<VertexId, ParseError> {
+        token
+            .parse::<VertexId>()
+            .map_err(|e| self.error(token, format!("invalid vertex id: {}", e)))
+    }
+
+    fn parse_label<T: FromStr + Default>(&self, label: &str) -> Result<T, ParseError>
+    where
+        <T as FromStr>::Err: Display,
+    {
+        if label.is_empty() {
+            Ok(T::default())
+        } else {
+            label
+                .parse::<T>()
+                .map_err(|e| self.error(label, format!("invalid label: {}", e)))
+        }
+    }
+}
+
+// splits "<token> <rest>" on the first run of whitespace, the rest may be empty
+fn split_token(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    match line.find(char::is_whitespace) {
+        Some(pos) => (&line[..pos], line[pos..].trim()),
+        None => (line, ""),
+    }
+}
+
+pub(crate) fn write_label<W: Write, T: Display>(
+    writer: &mut W,
+    value: &T,
+) -> Result<(), GenericError> {
+    let label = value.to_string();
+    if label.contains(['\n', '\r']) {
+        return Err(format!("label {:?} must not contain line breaks", label).into());
+    }
+    // surrounding whitespace is trimmed on read, so it couldn't survive a round trip
+    if label.trim() != label {
+        return Err(format!("label {:?} must not start or end with whitespace", label).into());
+    }
+    if !label.is_empty() {
+        write!(writer, " {}", label)?;
+    }
+    Ok(())
+}
+
+impl<V: Display + FromStr, E: Display + FromStr> Graph<V, E> {
+    // compression is picked by the file extension, see Compression::from_extension
+    pub fn serialize_to(&self, filename: &str) -> Result<(), GenericError> {
+        self.serialize_to_with(filename, Compression::from_extension(filename))
+    }
+
+    pub fn serialize_to_with(
+        &self,
+        filename: &str,
+        compression: Compression,
+    ) -> Result<(), GenericError> {
+        compression.write_file(filename, |encoder| self.serialize_into(encoder))
+    }
+
+    // the output is canonical: nodes and edges are sorted by id, so equal graphs always
+    // produce identical files
+    pub fn serialize_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(v_id, _)| **v_id);
+        for (v_id, v_value) in vertices {
+            write!(writer, "{}", v_id)?;
+            write_label(&mut writer, v_value)?;
+            writeln!(writer)?;
+        }
+        writeln!(writer, "#")?;
+        let mut sources: Vec<_> = self.adj_list.iter().collect();
+        sources.sort_unstable_by_key(|(v_from, _)| **v_from);
+        for (v_from, v_map) in sources {
+            let mut edges: Vec<_> = v_map.iter().collect();
+            edges.sort_unstable_by_key(|(v_to, _)| **v_to);
+            for (v_to, e_value) in edges {
+                write!(writer, "{} {}", v_from, v_to)?;
+                write_label(&mut writer, e_value)?;
+                writeln!(writer)?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    // a missing label is read as the default value of the payload type,
+    // malformed input is reported as a boxed ParseError
+    pub fn deserialize_from(filename: &str) -> Result<Graph<V, E>, GenericError>
+    where
+        V: Default,
+        E: Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        Self::deserialize_from_with(filename, Compression::from_extension(filename))
+    }
+
+    pub fn deserialize_from_with(
+        filename: &str,
+        compression: Compression,
+    ) -> Result<Graph<V, E>, GenericError>
+    where
+        V: Default,
+        E: Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        Self::deserialize_from_reader(compression.open(filename)?)
+    }
+
+    pub fn deserialize_from_reader<R: BufRead>(mut reader: R) -> Result<Graph<V, E>, GenericError>
+    where
+        V: Default,
+        E: Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        let mut loader = GraphLoader::new();
+        let mut line = String::new();
+        while reader.read_line(&mut line)? != 0 {
+            loader.feed_line(&line)?;
+            line.clear();
+        }
+        loader.finish()
+    }
+}
+
+// incremental TGF parser, input may be fed line by line or in arbitrary chunks
+pub struct GraphLoader<V, E> {
+    graph: Graph<V, E>,
+    section: Section,
+    line: usize,
+    pending: Vec<u8>,
+}
+
+impl<V, E> GraphLoader<V, E>
+where
+    V: Display + FromStr + Default,
+    E: Display + FromStr + Default,
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    pub fn new() -> GraphLoader<V, E> {
+        GraphLoader {
+            graph: Graph::new(),
+            section: Section::Nodes,
+            line: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    // chunks don't have to end on a line boundary, an incomplete tail is kept until the next call
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ParseError> {
+        let mut rest = chunk;
+        while let Some(pos) = rest.iter().position(|&b| b == b'\n') {
+            if self.pending.is_empty() {
+                self.feed_bytes(&rest[..pos])?;
+            } else {
+                let mut line = std::mem::take(&mut self.pending);
+                line.extend_from_slice(&rest[..pos]);
+                self.feed_bytes(&line)?;
+            }
+            rest = &rest[pos + 1..];
+        }
+        self.pending.extend_from_slice(rest);
+        Ok(())
+    }
+
+    pub fn feed_line(&mut self, line: &str) -> Result<(), ParseError> {
+        self.line += 1;
+        let ctx = LineContext {
+            number: self.line,
+            line,
+            section: self.section,
+        };
+        match ctx.split()? {
+            Line::Blank => {}
+            Line::Separator => self.section = Section::Edges,
+            Line::Node(id, label) => {
+                self.graph.insert_node(id, ctx.parse_label::<V>(label)?);
+            }
+            Line::Edge { from, to, label } => {
+                for (id, token) in [from, to] {
+                    if self.graph.get_vertex_value(id).is_none() {
+                        return Err(ctx.error(token, "unknown vertex"));
+                    }
+                }
+                self.graph
+                    .insert_edge(OrientedEdge(from.0, to.0), ctx.parse_label::<E>(label)?);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Graph<V, E>, GenericError> {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.feed_bytes(&line)?;
+        }
+        if self.section == Section::Nodes {
+            return Err(Box::new(ParseError {
+                line: self.line + 1,
+                column: 1,
+                section: Section::Nodes,
+                text: String::new(),
+                message: "# is missing".to_string(),
+            }));
+        }
+        Ok(self.graph)
+    }
+
+    fn feed_bytes(&mut self, line: &[u8]) -> Result<(), ParseError> {
+        match std::str::from_utf8(line) {
+            Ok(line) => self.feed_line(line),
+            Err(e) => Err(ParseError {
+                line: self.line + 1,
+                column: e.valid_up_to() + 1,
+                section: self.section,
+                text: String::new(),
+                message: "invalid UTF-8".to_string(),
+            }),
+        }
+    }
+}
+
+impl<V, E> Default for GraphLoader<V, E>
+where
+    V: Display + FromStr + Default,
+    E: Display + FromStr + Default,
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TgfRecord {
+    Node {
+        id: VertexId,
+        label: String,
+    },
+    Edge {
+        from: VertexId,
+        to: VertexId,
+        label: String,
+    },
+}
+
+// yields the records of a TGF stream one by one without building the graph, only the
+// vertex ids are kept to check the edges against; input is validated like GraphLoader does
+pub struct TgfReader<R> {
+    reader: R,
+    section: Section,
+    line: usize,
+    buffer: String,
+    vertices: HashSet<VertexId>,
+    done: bool,
+}
+
+impl<R: BufRead> TgfReader<R> {
+    pub fn new(reader: R) -> TgfReader<R> {
+        TgfReader {
+            reader,
+            section: Section::Nodes,
+            line: 0,
+            buffer: String::new(),
+            vertices: HashSet::default(),
+            done: false,
+        }
+    }
+
+    fn next_record(&mut self) -> Result<Option<TgfRecord>, GenericError> {
+        loop {
+            self.buffer.clear();
+            if self.reader.read_line(&mut self.buffer)? == 0 {
+                self.done = true;
+                if self.section == Section::Nodes {
+                    return Err(Box::new(ParseError {
+                        line: self.line + 1,
+                        column: 1,
+                        section: Section::Nodes,
+                        text: String::new(),
+                        message: "# is missing".to_string(),
+                    }));
+                }
+                return Ok(None);
+            }
+            self.line += 1;
+            let ctx = LineContext {
+                number: self.line,
+                line: &self.buffer,
+                section: self.section,
+            };
+            match ctx.split()? {
+                Line::Blank => {}
+                Line::Separator => self.section = Section::Edges,
+                Line::Node(id, label) => {
+                    self.vertices.insert(id);
+                    let label = label.to_string();
+                    return Ok(Some(TgfRecord::Node { id, label }));
+                }
+                Line::Edge { from, to, label } => {
+                    for (id, token) in [from, to] {
+                        if !self.vertices.contains(&id) {
+                            return Err(Box::new(ctx.error(token, "unknown vertex")));
+                        }
+                    }
+                    return Ok(Some(TgfRecord::Edge {
+                        from: from.0,
+                        to: to.0,
+                        label: label.to_string(),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+// stops after the first error
+impl<R: BufRead> Iterator for TgfReader<R> {
+    type Item = Result<TgfRecord, GenericError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.next_record();
+        if record.is_err() {
+            self.done = true;
+        }
+        record.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("graph_tgf_{}_{}", std::process::id(), name))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn labels_with_spaces() {
+        let path = temp_path("spaces.tgf");
+        std::fs::write(&path, "1 New York\n2 San Francisco\n#\n1 2 direct flight\n").unwrap();
+        let g = Graph::<String, String>::deserialize_from(&path).unwrap();
+        assert_eq!(g.get_vertex_value(1).unwrap(), "New York");
+        assert_eq!(g.get_vertex_value(2).unwrap(), "San Francisco");
+
+        g.serialize_to(&path).unwrap();
+        let g = Graph::<String, String>::deserialize_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(g.get_vertex_value(1).unwrap(), "New York");
+        assert_eq!(g.get_adjacents(1).unwrap(), vec![&2]);
+        assert_eq!(g.adj_list[&1][&2], "direct flight");
+    }
+
+    #[test]
+    fn missing_labels() {
+        let path = temp_path("missing.tgf");
+        std::fs::write(
+            &path,
+            "1\n2 second\n3\t third \n#\n1 2\n2\t3\tlabel\n3 1 \n",
+        )
+        .unwrap();
+        let g = Graph::<String, String>::deserialize_from(&path).unwrap();
+        assert_eq!(g.get_vertex_value(1).unwrap(), "");
+        assert_eq!(g.get_vertex_value(3).unwrap(), "third");
+        assert_eq!(g.adj_list[&1][&2], "");
+        assert_eq!(g.adj_list[&2][&3], "label");
+        assert_eq!(g.adj_list[&3][&1], "");
+
+        g.serialize_to(&path).unwrap();
+        let tgf = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(tgf.lines().any(|line| line == "1"));
+        assert!(tgf.lines().any(|line| line == "1 2"));
+    }
+
+    #[test]
+    fn hash_inside_label() {
+        let path = temp_path("hash.tgf");
+        std::fs::write(&path, "1 item #1\n2 item #2\n#\n1 2 #\n").unwrap();
+        let g = Graph::<String, String>::deserialize_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(g.get_vertex_value(2).unwrap(), "item #2");
+        assert_eq!(g.adj_list[&1][&2], "#");
+    }
+
+    #[test]
+    fn multiline_label_is_rejected() {
+        let path = temp_path("multiline.tgf");
+        let mut g = Graph::<String, String>::new();
+        g.insert_node(1, "first\nsecond".to_string());
+        assert!(g.serialize_to(&path).is_err());
+        // written aside, so the failed save didn't even create the file
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    fn parse_error(input: &str) -> format::tgf::ParseError {
+        let path = temp_path("error.tgf");
+        std::fs::write(&path, input).unwrap();
+        let err = Graph::<String, u32>::deserialize_from(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        *err.downcast::<format::tgf::ParseError>().unwrap()
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let err = parse_error("1 first\n  x2 second\n#\n");
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.section, format::tgf::Section::Nodes);
+        assert_eq!(err.text, "x2");
+
+        let err = parse_error("1 first\n2 second\n#\n1 2 5\n\n2  1 five\n");
+        assert_eq!((err.line, err.column), (6, 6));
+        assert_eq!(err.section, format::tgf::Section::Edges);
+        assert_eq!(err.text, "five");
+
+        let err = parse_error("1 first\n#\n1\n");
+        assert_eq!((err.line, err.column), (3, 1));
+        assert_eq!(err.message, "vertex_to is missing");
+
+        let err = parse_error("1 first\n2 second\n");
+        assert_eq!(err.line, 3);
+        assert_eq!(err.message, "# is missing");
+    }
+
+    #[test]
+    fn loader_accepts_arbitrary_chunks() {
+        let input = "1 first\r\n2 sec\u{f6}nd\n#\n1 2 edge\n2 1".as_bytes();
+        let mut loader = format::tgf::GraphLoader::<String, String>::new();
+        for chunk in input.chunks(3) {
+            loader.feed(chunk).unwrap();
+        }
+        let g = loader.finish().unwrap();
+        assert_eq!(g.get_vertex_value(2).unwrap(), "sec\u{f6}nd");
+        assert_eq!(g.adj_list[&1][&2], "edge");
+        assert_eq!(g.adj_list[&2][&1], "");
+    }
+
+    #[test]
+    fn deserialize_from_reader() {
+        let input = "1 first\n2 second\n#\n1 2 edge\n";
+        let g = Graph::<String, String>::deserialize_from_reader(input.as_bytes()).unwrap();
+        assert_eq!(g.get_adjacents(1).unwrap(), vec![&2]);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compressed_file() {
+        let path = temp_path("compressed.tgf.gz");
+        let mut g = Graph::<String, String>::new();
+        g.insert_node(1, "first".to_string());
+        g.insert_node(2, "second".to_string());
+        g.insert_edge(OrientedEdge(1, 2), "edge".to_string());
+        g.serialize_to(&path).unwrap();
+        assert_eq!(&std::fs::read(&path).unwrap()[..2], &[0x1f, 0x8b]);
+        let g = Graph::<String, String>::deserialize_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(g.adj_list[&1][&2], "edge");
+    }
+
+    fn round_trip<V, E>(g: &Graph<V, E>) -> Graph<V, E>
+    where
+        V: std::fmt::Display + std::str::FromStr + Default,
+        E: std::fmt::Display + std::str::FromStr + Default,
+        <V as std::str::FromStr>::Err: std::fmt::Display,
+        <E as std::str::FromStr>::Err: std::fmt::Display,
+    {
+        let mut tgf = Vec::new();
+        g.serialize_into(&mut tgf).unwrap();
+        Graph::deserialize_from_reader(tgf.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn lossless_round_trip() {
+        let mut g = Graph::<String, String>::new();
+        g.insert_node(1, "New York".to_string());
+        g.insert_node(2, "".to_string());
+        g.insert_node(30, "#".to_string());
+        g.insert_node(4, "tab\tinside".to_string());
+        g.insert_node(5, "isolated".to_string());
+        g.insert_edge(OrientedEdge(1, 2), "a b c".to_string());
+        g.insert_edge(OrientedEdge(2, 1), "".to_string());
+        g.insert_edge(OrientedEdge(30, 30), "self loop".to_string());
+        g.insert_edge(OrientedEdge(4, 30), "#".to_string());
+        assert_eq!(round_trip(&g), g);
+
+        let mut g = Graph::<f64, i64>::new();
+        for v in 0..50 {
+            g.insert_node(v, v as f64 / 7.0);
+        }
+        for v in 0..50 {
+            g.insert_edge(OrientedEdge(v, (v * 17 + 3) % 50), -(v as i64));
+            g.insert_edge(OrientedEdge(v, (v * 31 + 1) % 50), v as i64 * 1000);
+        }
+        assert_eq!(round_trip(&g), g);
+
+        assert_eq!(round_trip(&Graph::<u32, u32>::new()), Graph::new());
+    }
+
+    #[test]
+    fn canonical_output() {
+        let mut forward = Graph::<u32, u32>::new();
+        let mut backward = Graph::<u32, u32>::new();
+        for v in 0..20 {
+            forward.insert_node(v, v);
+            backward.insert_node(19 - v, 19 - v);
+        }
+        for v in 0..20 {
+            forward.insert_edge(OrientedEdge(v, (v + 1) % 20), v);
+            forward.insert_edge(OrientedEdge(v, (v + 7) % 20), v);
+            backward.insert_edge(OrientedEdge(19 - v, (26 - v) % 20), 19 - v);
+            backward.insert_edge(OrientedEdge(19 - v, (20 - v) % 20), 19 - v);
+        }
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        forward.serialize_into(&mut a).unwrap();
+        backward.serialize_into(&mut b).unwrap();
+        assert_eq!(a, b);
+        let tgf = String::from_utf8(a).unwrap();
+        assert!(tgf.starts_with("0 0\n1 1\n2 2\n"));
+        assert!(tgf.contains("#\n0 1 0\n0 7 0\n1 2 1\n"));
+    }
+
+    #[test]
+    fn unrepresentable_labels_are_rejected() {
+        let mut g = Graph::<String, String>::new();
+        g.insert_node(1, " padded".to_string());
+        assert!(g.serialize_into(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn edge_with_unknown_vertex() {
+        let err = parse_error("1 first\n#\n1 7 5\n");
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.message, "unknown vertex");
+    }
+
+    #[test]
+    fn reader_streams_records() {
+        use format::tgf::{TgfReader, TgfRecord};
+        let input = "1 first\n\n2\n#\n1 2 an edge\n";
+        let records: Vec<_> = TgfReader::new(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                TgfRecord::Node {
+                    id: 1,
+                    label: "first".to_string()
+                },
+                TgfRecord::Node {
+                    id: 2,
+                    label: "".to_string()
+                },
+                TgfRecord::Edge {
+                    from: 1,
+                    to: 2,
+                    label: "an edge".to_string()
+                },
+            ]
+        );
+
+        let mut reader = TgfReader::new("1\n#\n1 3\n1 1\n".as_bytes());
+        let err = reader
+            .next()
+            .unwrap()
+            .and(reader.next().unwrap())
+            .unwrap_err();
+        let err = err.downcast::<format::tgf::ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.message, "unknown vertex");
+        assert!(reader.next().is_none());
+
+        let mut reader = TgfReader::new("1\n".as_bytes());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{Graph, VertexId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TikzLayout {
+    // vertices on a circle ordered by id
+    Circular,
+    // BFS layers from the vertices without incoming edges, top to bottom
+    Layered,
+}
+
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// rounded to the printed precision, so tiny negative values don't show up as "-0.000"
+fn coordinate(x: f64) -> f64 {
+    (x * 1000.0).round() / 1000.0 + 0.0
+}
+
+impl<V: Display, E: Display> Graph<V, E> {
+    pub fn to_tikz(&self, layout: TikzLayout) -> String {
+        let mut vertices: Vec<VertexId> = self.vertices.keys().copied().collect();
+        vertices.sort_unstable();
+        let positions = match layout {
+            TikzLayout::Circular => self.circular_layout(&vertices),
+            TikzLayout::Layered => self.layered_layout(&vertices),
+        };
+
+        let mut tikz = String::new();
+        tikz += "\\begin{tikzpicture}[>=stealth, vertex/.style={draw, circle, minimum size=6mm}, \
+                 weight/.style={font=\\small, fill=white, inner sep=1pt}]\n";
+        for v in &vertices {
+            let (x, y) = positions[v];
+            let _ = writeln!(
+                tikz,
+                "  \\node[vertex] (v{}) at ({:.3}, {:.3}) {{{}}};",
+                v,
+                coordinate(x),
+                coordinate(y),
+                escape_latex(&self.vertices[v].to_string())
+            );
+        }
+        for from in &vertices {
+            let mut adjacents: Vec<_> = self.adj_list[from].iter().collect();
+            adjacents.sort_unstable_by_key(|(to, _)| **to);
+            for (to, value) in adjacents {
+                let path = if from == to {
+                    "to[loop above]"
+                } else if self.adj_list[to].contains_key(from) {
+                    // keep both directions of a bidirectional pair apart
+                    "to[bend left=15]"
+                } else {
+                    "--"
+                };
+                let _ = writeln!(
+                    tikz,
+                    "  \\draw[->] (v{}) {} node[weight] {{{}}} (v{});",
+                    from,
+                    path,
+                    escape_latex(&value.to_string()),
+                    to
+                );
+            }
+        }
+        tikz += "\\end{tikzpicture}\n";
+        tikz
+    }
+
+    fn circular_layout(&self, vertices: &[VertexId]) -> HashMap<VertexId, (f64, f64)> {
+        let n = vertices.len();
+        let radius = (n as f64 * 0.4).max(1.5);
+        vertices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let angle =
+                    std::f64::consts::FRAC_PI_2 - 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+                (*v, (radius * angle.cos(), radius * angle.sin()))
+            })
+            .collect()
+    }
+
+    fn layered_layout(&self, vertices: &[VertexId]) -> HashMap<VertexId, (f64, f64)> {
+        let with_incoming: HashSet<VertexId> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, map)| map.keys().filter(move |to| *to != from))
+            .copied()
+            .collect();
+        let sources = vertices.iter().filter(|v| !with_incoming.contains(v));
+
+        let mut depth = HashMap::new();
+        let mut queue = VecDeque::new();
+        // sources go first, vertices left over (only reachable through cycles) start new trees
+        for start in sources.chain(vertices.iter()) {
+            if depth.contains_key(start) {
+                continue;
+            }
+            depth.insert(*start, 0usize);
+            queue.push_back(*start);
+            while let Some(current) = queue.pop_front() {
+                let mut adjacents: Vec<_> = self.adj_list[&current].keys().copied().collect();
+                adjacents.sort_unstable();
+                for adjacent in adjacents {
+                    if !depth.contains_key(&adjacent) {
+                        depth.insert(adjacent, depth[&current] + 1);
+                        queue.push_back(adjacent);
+                    }
+                }
+            }
+        }
+
+        let mut layers: Vec<Vec<VertexId>> = Vec::new();
+        for v in vertices {
+            let d = depth[v];
+            if layers.len() <= d {
+                layers.resize(d + 1, Vec::new());
+            }
+            layers[d].push(*v);
+        }
+        let mut positions = HashMap::new();
+        for (d, layer) in layers.iter().enumerate() {
+            let offset = (layer.len() as f64 - 1.0) / 2.0;
+            for (i, v) in layer.iter().enumerate() {
+                positions.insert(*v, (2.0 * (i as f64 - offset), -1.5 * d as f64));
+            }
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::tikz::TikzLayout;
+    use crate::*;
+
+    fn sample() -> Graph<String, String> {
+        let mut g = Graph::new();
+        g.insert_node(1, "root".to_string());
+        g.insert_node(2, "left_child".to_string());
+        g.insert_node(3, "right".to_string());
+        g.insert_edge(OrientedEdge(1, 2), "5%".to_string());
+        g.insert_edge(OrientedEdge(1, 3), "".to_string());
+        g.insert_edge(OrientedEdge(3, 1), "back".to_string());
+        g.insert_edge(OrientedEdge(2, 2), "self".to_string());
+        g
+    }
+
+    #[test]
+    fn layered() {
+        let tikz = sample().to_tikz(TikzLayout::Layered);
+        assert!(tikz.starts_with("\\begin{tikzpicture}"));
+        assert!(tikz.ends_with("\\end{tikzpicture}\n"));
+        // 1 is reachable from 3, so the smallest id starts the first layer
+        assert!(tikz.contains("\\node[vertex] (v1) at (0.000, 0.000) {root};"));
+        assert!(tikz.contains("\\node[vertex] (v2) at (-1.000, -1.500) {left\\_child};"));
+        assert!(tikz.contains("\\node[vertex] (v3) at (1.000, -1.500) {right};"));
+        assert!(tikz.contains("\\draw[->] (v1) -- node[weight] {5\\%} (v2);"));
+        assert!(tikz.contains("\\draw[->] (v1) to[bend left=15] node[weight] {} (v3);"));
+        assert!(tikz.contains("\\draw[->] (v3) to[bend left=15] node[weight] {back} (v1);"));
+        assert!(tikz.contains("\\draw[->] (v2) to[loop above] node[weight] {self} (v2);"));
+    }
+
+    #[test]
+    fn circular() {
+        let tikz = sample().to_tikz(TikzLayout::Circular);
+        assert!(tikz.contains("(v1) at (0.000, 1.500)"));
+        assert_eq!(tikz.matches("\\node[vertex]").count(), 3);
+        assert_eq!(tikz.matches("\\draw").count(), 4);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Graph, OrientedEdge, VertexId};
+
+fn with_vertices<V: Default, E>(n: usize) -> Graph<V, E> {
+    let mut graph = Graph::new();
+    for id in 0..n as VertexId {
+        graph.insert_node(id, V::default());
+    }
+    graph
+}
+
+// both directions between every pair of distinct vertices
+pub fn complete<V: Default, E: Default>(n: usize) -> Graph<V, E> {
+    let mut graph = with_vertices(n);
+    for from in 0..n as VertexId {
+        for to in 0..n as VertexId {
+            if from != to {
+                graph.insert_edge(OrientedEdge(from, to), E::default());
+            }
+        }
+    }
+    graph
+}
+
+// 0 -> 1 -> ... -> n - 1
+pub fn path<V: Default, E: Default>(n: usize) -> Graph<V, E> {
+    let mut graph = with_vertices(n);
+    for from in 1..n as VertexId {
+        graph.insert_edge(OrientedEdge(from - 1, from), E::default());
+    }
+    graph
+}
+
+// the path closed with n - 1 -> 0, a single vertex gets a self-loop
+pub fn cycle<V: Default, E: Default>(n: usize) -> Graph<V, E> {
+    let mut graph = path(n);
+    if n > 0 {
+        graph.insert_edge(OrientedEdge(n as VertexId - 1, 0), E::default());
+    }
+    graph
+}
+
+// n vertices, 0 in the center pointing at each of the others
+pub fn star<V: Default, E: Default>(n: usize) -> Graph<V, E> {
+    let mut graph = with_vertices(n);
+    for leaf in 1..n as VertexId {
+        graph.insert_edge(OrientedEdge(0, leaf), E::default());
+    }
+    graph
+}
+
+// row-major ids, row * cols + col, with edges pointing right and down
+pub fn grid<V: Default, E: Default>(rows: usize, cols: usize) -> Graph<V, E> {
+    let mut graph = with_vertices(rows * cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            let id = (row * cols + col) as VertexId;
+            if col + 1 < cols {
+                graph.insert_edge(OrientedEdge(id, id + 1), E::default());
+            }
+            if row + 1 < rows {
+                graph.insert_edge(OrientedEdge(id, id + cols as VertexId), E::default());
+            }
+        }
+    }
+    graph
+}
+
+// perfect tree in heap order, vertex i has the children 2i + 1 and 2i + 2; depth 0 is a
+// lone root
+pub fn binary_tree<V: Default, E: Default>(depth: u32) -> Graph<V, E> {
+    let n = (1usize << (depth + 1)) - 1;
+    let mut graph = with_vertices(n);
+    for child in 1..n as VertexId {
+        graph.insert_edge(OrientedEdge((child - 1) / 2, child), E::default());
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::*;
+    use crate::*;
+
+    #[test]
+    fn topologies() {
+        let g: Graph<(), ()> = complete(5);
+        assert_eq!((g.vertex_count(), g.edge_count()), (5, 20));
+        let g: Graph<(), ()> = path(4);
+        assert_eq!(g.get_adjacents(2).unwrap(), vec![&3]);
+        assert_eq!(g.edge_count(), 3);
+        let g: Graph<(), ()> = cycle(4);
+        assert_eq!(g.get_adjacents(3).unwrap(), vec![&0]);
+        assert_eq!(cycle::<(), ()>(1).edge_count(), 1);
+        assert_eq!(cycle::<(), ()>(0).vertex_count(), 0);
+        let g: Graph<(), ()> = star(6);
+        assert_eq!(g.out_degree(0), Some(5));
+        assert_eq!(g.in_degree(5), Some(1));
+
+        let g: Graph<(), ()> = grid(3, 4);
+        assert_eq!((g.vertex_count(), g.edge_count()), (12, 17));
+        let mut adjacents = g.get_adjacents(5).unwrap();
+        adjacents.sort();
+        assert_eq!(adjacents, vec![&6, &9]);
+        assert_eq!(g.get_adjacents(11).unwrap(), Vec::<&VertexId>::new());
+
+        let g: Graph<(), ()> = binary_tree(3);
+        assert_eq!((g.vertex_count(), g.edge_count()), (15, 14));
+        assert_eq!(g.get_predecessors(14).unwrap(), vec![&6]);
+        assert_eq!(binary_tree::<(), ()>(0).vertex_count(), 1);
+    }
+}
@@ -0,0 +1,18 @@
+// generated graphs use the ids 0..n
+mod classic;
+#[cfg(feature = "rand")]
+mod random;
+
+pub use classic::{binary_tree, complete, cycle, grid, path, star};
+#[cfg(feature = "rand")]
+pub use random::{
+    barabasi_albert, barabasi_albert_with, gnm, gnm_with, gnp, gnp_with, random_dag, random_tree,
+    watts_strogatz, watts_strogatz_with,
+};
+
+// undirected graphs are stored with both directions of every edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Directed,
+    Undirected,
+}
@@ -0,0 +1,419 @@
+use crate::generators::Direction;
+use crate::hash::HashSet;
+use crate::{Graph, OrientedEdge, VertexId};
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+fn empty<V, E>(n: usize, vertex: &mut impl FnMut(VertexId) -> V) -> Graph<V, E> {
+    let mut graph = Graph::new();
+    for id in 0..n as VertexId {
+        graph.insert_node(id, vertex(id));
+    }
+    graph
+}
+
+fn pair_count(n: usize, direction: Direction) -> usize {
+    let ordered = n * n.saturating_sub(1);
+    match direction {
+        Direction::Directed => ordered,
+        Direction::Undirected => ordered / 2,
+    }
+}
+
+// the edge closure is called once per stored direction
+fn connect<V, E>(
+    graph: &mut Graph<V, E>,
+    from: VertexId,
+    to: VertexId,
+    direction: Direction,
+    edge: &mut impl FnMut(OrientedEdge) -> E,
+) {
+    graph.insert_edge(OrientedEdge(from, to), edge(OrientedEdge(from, to)));
+    if direction == Direction::Undirected {
+        graph.insert_edge(OrientedEdge(to, from), edge(OrientedEdge(to, from)));
+    }
+}
+
+// G(n, p): every possible edge, self-loops excluded, is present with probability p
+pub fn gnp<V: Default, E: Default, R: Rng + ?Sized>(
+    n: usize,
+    p: f64,
+    direction: Direction,
+    rng: &mut R,
+) -> Graph<V, E> {
+    gnp_with(n, p, direction, rng, |_| V::default(), |_| E::default())
+}
+
+pub fn gnp_with<V, E, R: Rng + ?Sized>(
+    n: usize,
+    p: f64,
+    direction: Direction,
+    rng: &mut R,
+    mut vertex: impl FnMut(VertexId) -> V,
+    mut edge: impl FnMut(OrientedEdge) -> E,
+) -> Graph<V, E> {
+    let p = p.clamp(0.0, 1.0);
+    let mut graph = empty(n, &mut vertex);
+    for from in 0..n as VertexId {
+        let first = match direction {
+            Direction::Directed => 0,
+            Direction::Undirected => from + 1,
+        };
+        for to in first..n as VertexId {
+            if from != to && rng.gen_bool(p) {
+                connect(&mut graph, from, to, direction, &mut edge);
+            }
+        }
+    }
+    graph
+}
+
+// G(n, m): m edges drawn uniformly without repetition, capped at the number of possible
+// edges; dense requests pick the edges to leave out instead
+pub fn gnm<V: Default, E: Default, R: Rng + ?Sized>(
+    n: usize,
+    m: usize,
+    direction: Direction,
+    rng: &mut R,
+) -> Graph<V, E> {
+    gnm_with(n, m, direction, rng, |_| V::default(), |_| E::default())
+}
+
+pub fn gnm_with<V, E, R: Rng + ?Sized>(
+    n: usize,
+    m: usize,
+    direction: Direction,
+    rng: &mut R,
+    mut vertex: impl FnMut(VertexId) -> V,
+    mut edge: impl FnMut(OrientedEdge) -> E,
+) -> Graph<V, E> {
+    let mut graph = empty(n, &mut vertex);
+    let total = pair_count(n, direction);
+    let m = m.min(total);
+    let dense = m > total / 2;
+    let wanted = if dense { total - m } else { m };
+
+    let mut picked = HashSet::default();
+    while picked.len() < wanted {
+        let (from, to) = (rng.gen_range(0..n), rng.gen_range(0..n));
+        if from == to {
+            continue;
+        }
+        let pair = match direction {
+            Direction::Undirected if from > to => (to, from),
+            _ => (from, to),
+        };
+        picked.insert((pair.0 as VertexId, pair.1 as VertexId));
+    }
+
+    if !dense {
+        let mut pairs: Vec<_> = picked.into_iter().collect();
+        pairs.sort_unstable();
+        for (from, to) in pairs {
+            connect(&mut graph, from, to, direction, &mut edge);
+        }
+        return graph;
+    }
+    for from in 0..n as VertexId {
+        let first = match direction {
+            Direction::Directed => 0,
+            Direction::Undirected => from + 1,
+        };
+        for to in first..n as VertexId {
+            if from != to && !picked.contains(&(from, to)) {
+                connect(&mut graph, from, to, direction, &mut edge);
+            }
+        }
+    }
+    graph
+}
+
+// preferential attachment: every new vertex links to m distinct earlier vertices picked
+// with probability proportional to their degree, starting from a star around vertex m;
+// undirected, m is capped at n - 1
+pub fn barabasi_albert<V: Default, E: Default, R: Rng + ?Sized>(
+    n: usize,
+    m: usize,
+    rng: &mut R,
+) -> Graph<V, E> {
+    barabasi_albert_with(n, m, rng, |_| V::default(), |_| E::default())
+}
+
+pub fn barabasi_albert_with<V, E, R: Rng + ?Sized>(
+    n: usize,
+    m: usize,
+    rng: &mut R,
+    mut vertex: impl FnMut(VertexId) -> V,
+    mut edge: impl FnMut(OrientedEdge) -> E,
+) -> Graph<V, E> {
+    let mut graph = empty(n, &mut vertex);
+    let m = m.min(n.saturating_sub(1));
+    if m == 0 {
+        return graph;
+    }
+    // every vertex appears once per incident edge, so a uniform pick is degree-weighted
+    let mut endpoints: Vec<VertexId> = Vec::with_capacity(2 * m * n);
+    let mut targets: Vec<VertexId> = (0..m as VertexId).collect();
+    for source in m as VertexId..n as VertexId {
+        for target in &targets {
+            connect(
+                &mut graph,
+                source,
+                *target,
+                Direction::Undirected,
+                &mut edge,
+            );
+            endpoints.push(*target);
+            endpoints.push(source);
+        }
+        let mut picked = HashSet::default();
+        targets.clear();
+        while targets.len() < m {
+            let target = endpoints[rng.gen_range(0..endpoints.len())];
+            if picked.insert(target) {
+                targets.push(target);
+            }
+        }
+    }
+    graph
+}
+
+// small world: a ring where every vertex is linked to its k / 2 nearest neighbors on each
+// side, after which every lattice edge has its far end moved to a uniformly random vertex
+// with probability beta, never creating self-loops or parallel edges; undirected
+pub fn watts_strogatz<V: Default, E: Default, R: Rng + ?Sized>(
+    n: usize,
+    k: usize,
+    beta: f64,
+    rng: &mut R,
+) -> Graph<V, E> {
+    watts_strogatz_with(n, k, beta, rng, |_| V::default(), |_| E::default())
+}
+
+pub fn watts_strogatz_with<V, E, R: Rng + ?Sized>(
+    n: usize,
+    k: usize,
+    beta: f64,
+    rng: &mut R,
+    mut vertex: impl FnMut(VertexId) -> V,
+    mut edge: impl FnMut(OrientedEdge) -> E,
+) -> Graph<V, E> {
+    let mut graph = empty(n, &mut vertex);
+    let beta = beta.clamp(0.0, 1.0);
+    let half = k.min(n.saturating_sub(1)) / 2;
+    let mut neighbors: Vec<HashSet<usize>> = vec![HashSet::default(); n];
+    for j in 1..=half {
+        for u in 0..n {
+            let v = (u + j) % n;
+            neighbors[u].insert(v);
+            neighbors[v].insert(u);
+        }
+    }
+    for j in 1..=half {
+        for u in 0..n {
+            let v = (u + j) % n;
+            if !neighbors[u].contains(&v) || neighbors[u].len() + 1 >= n || !rng.gen_bool(beta) {
+                continue;
+            }
+            let w = loop {
+                let w = rng.gen_range(0..n);
+                if w != u && !neighbors[u].contains(&w) {
+                    break w;
+                }
+            };
+            neighbors[u].remove(&v);
+            neighbors[v].remove(&u);
+            neighbors[u].insert(w);
+            neighbors[w].insert(u);
+        }
+    }
+    for (u, adjacent) in neighbors.iter().enumerate() {
+        let mut adjacent: Vec<usize> = adjacent.iter().copied().filter(|v| *v > u).collect();
+        adjacent.sort_unstable();
+        for v in adjacent {
+            connect(
+                &mut graph,
+                u as VertexId,
+                v as VertexId,
+                Direction::Undirected,
+                &mut edge,
+            );
+        }
+    }
+    graph
+}
+
+// vertex ids double as the ranks, every edge i -> j with i < j is present with
+// probability edge_prob, so 0..n is always a topological order
+pub fn random_dag<V: Default, E: Default, R: Rng + ?Sized>(
+    n: usize,
+    edge_prob: f64,
+    rng: &mut R,
+) -> Graph<V, E> {
+    let edge_prob = edge_prob.clamp(0.0, 1.0);
+    let mut graph = empty(n, &mut |_| V::default());
+    for from in 0..n as VertexId {
+        for to in from + 1..n as VertexId {
+            if rng.gen_bool(edge_prob) {
+                graph.insert_edge(OrientedEdge(from, to), E::default());
+            }
+        }
+    }
+    graph
+}
+
+// uniformly random labeled tree decoded from a random Prüfer sequence, with the edges
+// pointing away from the root 0
+pub fn random_tree<V: Default, E: Default, R: Rng + ?Sized>(n: usize, rng: &mut R) -> Graph<V, E> {
+    let mut graph = empty(n, &mut |_| V::default());
+    if n < 2 {
+        return graph;
+    }
+    let sequence: Vec<usize> = (0..n - 2).map(|_| rng.gen_range(0..n)).collect();
+    let mut degree = vec![1; n];
+    for v in &sequence {
+        degree[*v] += 1;
+    }
+    let mut leaves: BinaryHeap<Reverse<usize>> =
+        (0..n).filter(|v| degree[*v] == 1).map(Reverse).collect();
+    let mut neighbors = vec![Vec::new(); n];
+    for v in sequence {
+        let Reverse(leaf) = leaves.pop().unwrap();
+        neighbors[leaf].push(v);
+        neighbors[v].push(leaf);
+        degree[v] -= 1;
+        if degree[v] == 1 {
+            leaves.push(Reverse(v));
+        }
+    }
+    let (Reverse(u), Reverse(v)) = (leaves.pop().unwrap(), leaves.pop().unwrap());
+    neighbors[u].push(v);
+    neighbors[v].push(u);
+
+    let mut stack = vec![0];
+    let mut seen = vec![false; n];
+    seen[0] = true;
+    while let Some(parent) = stack.pop() {
+        for child in &neighbors[parent] {
+            if !seen[*child] {
+                seen[*child] = true;
+                graph.insert_edge(
+                    OrientedEdge(parent as VertexId, *child as VertexId),
+                    E::default(),
+                );
+                stack.push(*child);
+            }
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::*;
+    use crate::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn erdos_renyi() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let g: Graph<(), ()> = gnp(50, 0.0, Direction::Directed, &mut rng);
+        assert_eq!((g.vertex_count(), g.edge_count()), (50, 0));
+        let g: Graph<(), ()> = gnp(10, 1.0, Direction::Directed, &mut rng);
+        assert_eq!(g.edge_count(), 90);
+        let g: Graph<(), ()> = gnp(200, 0.1, Direction::Undirected, &mut rng);
+        // 19900 pairs, far outside a few standard deviations otherwise
+        assert!((3400..4600).contains(&g.edge_count()));
+
+        for m in [0, 10, 40, 45, 100] {
+            let g: Graph<(), ()> = gnm(10, m, Direction::Undirected, &mut rng);
+            assert_eq!(g.edge_count(), 2 * m.min(45));
+            for v in 0..10 {
+                for u in g.get_adjacents(v).unwrap() {
+                    assert_ne!(*u, v);
+                    assert!(g.get_adjacents(*u).unwrap().contains(&&v));
+                }
+            }
+        }
+        let g = gnm_with(
+            5,
+            15,
+            Direction::Directed,
+            &mut rng,
+            |v| v * 2,
+            |e| e.0 + e.1,
+        );
+        assert_eq!(g.edge_count(), 15);
+        assert_eq!(g.get_vertex_value(4), Some(&8));
+    }
+
+    #[test]
+    fn preferential_attachment() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let g: Graph<(), ()> = barabasi_albert(1000, 3, &mut rng);
+        assert_eq!(g.vertex_count(), 1000);
+        assert_eq!(g.edge_count(), 2 * 3 * 997);
+        let degrees: Vec<usize> = (0..1000)
+            .map(|v| g.get_adjacents(v).unwrap().len())
+            .collect();
+        assert!(degrees[3..].iter().all(|d| *d >= 3));
+        // hubs form among the early vertices
+        assert!(degrees.iter().max().unwrap() > &40);
+
+        let g: Graph<(), ()> = barabasi_albert(4, 10, &mut rng);
+        assert_eq!(g.edge_count(), 6);
+        let g: Graph<(), ()> = barabasi_albert(1, 2, &mut rng);
+        assert_eq!((g.vertex_count(), g.edge_count()), (1, 0));
+    }
+
+    #[test]
+    fn small_world() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let ring: Graph<(), ()> = watts_strogatz(20, 4, 0.0, &mut rng);
+        assert_eq!(ring.edge_count(), 2 * 40);
+        let mut adjacents: Vec<_> = ring
+            .get_adjacents(0)
+            .unwrap()
+            .into_iter()
+            .copied()
+            .collect();
+        adjacents.sort();
+        assert_eq!(adjacents, vec![1, 2, 18, 19]);
+
+        // rewiring keeps the number of edges and never adds loops or parallel edges
+        let g: Graph<(), ()> = watts_strogatz(100, 6, 0.3, &mut rng);
+        assert_eq!(g.edge_count(), 2 * 300);
+        assert_ne!(g, watts_strogatz(100, 6, 0.0, &mut rng));
+        for v in 0..100 {
+            assert!(!g.get_adjacents(v).unwrap().contains(&&v));
+        }
+
+        let g: Graph<(), ()> = watts_strogatz(5, 10, 1.0, &mut rng);
+        assert_eq!(g.edge_count(), 2 * 10);
+    }
+
+    #[test]
+    fn dags_and_trees() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let g: Graph<(), ()> = random_dag(30, 0.3, &mut rng);
+        assert!(g.edge_count() > 0);
+        for v in 0..30 {
+            assert!(g.get_adjacents(v).unwrap().iter().all(|u| **u > v));
+        }
+        assert!(DagGraph::try_from(g).is_ok());
+        assert_eq!(random_dag::<(), (), _>(10, 1.0, &mut rng).edge_count(), 45);
+
+        for n in [0, 1, 2, 3, 50] {
+            let g: Graph<(), ()> = random_tree(n, &mut rng);
+            assert_eq!(g.edge_count(), n.saturating_sub(1));
+            for v in 1..n as VertexId {
+                assert_eq!(g.in_degree(v), Some(1));
+            }
+            if n > 0 {
+                assert_eq!(g.in_degree(0), Some(0));
+            }
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// hasher of all internal maps, picked at compile time by the `ahash`/`fxhash` features
+#[cfg(feature = "ahash")]
+pub type GraphHasher = ahash::RandomState;
+#[cfg(all(feature = "fxhash", not(feature = "ahash")))]
+pub type GraphHasher = rustc_hash::FxBuildHasher;
+#[cfg(not(any(feature = "ahash", feature = "fxhash")))]
+pub type GraphHasher = std::collections::hash_map::RandomState;
+
+pub(crate) type HashMap<K, V> = std::collections::HashMap<K, V, GraphHasher>;
+pub(crate) type HashSet<K> = std::collections::HashSet<K, GraphHasher>;
@@ -0,0 +1,126 @@
+use crate::hash::HashMap;
+use crate::{Graph, OrientedEdge, VertexId};
+use petgraph::graph::NodeIndex;
+use petgraph::graphmap::DiGraphMap;
+
+// conversions to and from petgraph; petgraph::Graph nodes are laid out in ascending id
+// order, coming back every node index becomes the vertex id
+impl<V: Clone, E: Clone> Graph<V, E> {
+    pub fn to_petgraph(&self) -> (petgraph::Graph<V, E>, HashMap<VertexId, NodeIndex>) {
+        let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let mut graph = petgraph::Graph::with_capacity(ids.len(), self.edge_count);
+        let mut index = HashMap::default();
+        for id in &ids {
+            index.insert(*id, graph.add_node(self.vertices[id].clone()));
+        }
+        for from in &ids {
+            let mut adjacents: Vec<_> = self.adj_list[from].iter().collect();
+            adjacents.sort_unstable_by_key(|(to, _)| **to);
+            for (to, value) in adjacents {
+                graph.add_edge(index[from], index[to], value.clone());
+            }
+        }
+        (graph, index)
+    }
+}
+
+impl<V: Clone, E: Clone> From<&Graph<V, E>> for petgraph::Graph<V, E> {
+    fn from(graph: &Graph<V, E>) -> Self {
+        graph.to_petgraph().0
+    }
+}
+
+// parallel edges collapse into one, the last one wins
+impl<V, E> From<petgraph::Graph<V, E>> for Graph<V, E> {
+    fn from(graph: petgraph::Graph<V, E>) -> Self {
+        let (nodes, edges) = graph.into_nodes_edges();
+        let mut result = Graph::new();
+        for (i, node) in nodes.into_iter().enumerate() {
+            result.insert_node(i as VertexId, node.weight);
+        }
+        for edge in edges {
+            let (from, to) = (edge.source().index(), edge.target().index());
+            result.insert_edge(OrientedEdge(from as VertexId, to as VertexId), edge.weight);
+        }
+        result
+    }
+}
+
+// a graph map has no vertex values, they are dropped on the way there and defaulted on the
+// way back
+impl<V, E: Clone> From<&Graph<V, E>> for DiGraphMap<VertexId, E> {
+    fn from(graph: &Graph<V, E>) -> Self {
+        let mut map = DiGraphMap::with_capacity(graph.vertex_count(), graph.edge_count);
+        for id in graph.vertices.keys() {
+            map.add_node(*id);
+        }
+        for (from, adjacency) in &graph.adj_list {
+            for (to, value) in adjacency {
+                map.add_edge(*from, *to, value.clone());
+            }
+        }
+        map
+    }
+}
+
+impl<V: Default, E: Clone> From<&DiGraphMap<VertexId, E>> for Graph<V, E> {
+    fn from(map: &DiGraphMap<VertexId, E>) -> Self {
+        let mut graph = Graph::new();
+        for id in map.nodes() {
+            graph.insert_node(id, V::default());
+        }
+        for (from, to, value) in map.all_edges() {
+            graph.insert_edge(OrientedEdge(from, to), value.clone());
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use petgraph::graphmap::DiGraphMap;
+
+    fn sample() -> Graph<String, u32> {
+        let mut g = Graph::new();
+        for v in [30, 10, 20] {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(OrientedEdge(10, 20), 1);
+        g.insert_edge(OrientedEdge(20, 30), 2);
+        g.insert_edge(OrientedEdge(30, 30), 3);
+        g
+    }
+
+    #[test]
+    fn petgraph_graph() {
+        let g = sample();
+        let (pg, index) = g.to_petgraph();
+        assert_eq!(pg.node_count(), 3);
+        assert_eq!(pg.edge_count(), 3);
+        assert_eq!(index[&10].index(), 0);
+        assert_eq!(pg[index[&30]], "v30");
+        let edge = pg.find_edge(index[&20], index[&30]).unwrap();
+        assert_eq!(pg[edge], 2);
+        assert!(petgraph::algo::is_cyclic_directed(&pg));
+
+        let back = Graph::from(pg);
+        assert_eq!(back.get_vertex_value(2).unwrap(), "v30");
+        assert_eq!(back.get_adjacents(1).unwrap(), vec![&2]);
+        assert_eq!(back.edge_count(), 3);
+    }
+
+    #[test]
+    fn graph_map() {
+        let g = sample();
+        let map = DiGraphMap::from(&g);
+        assert_eq!(map.edge_weight(10, 20), Some(&1));
+        assert_eq!(map.node_count(), 3);
+
+        let back: Graph<String, u32> = Graph::from(&map);
+        assert_eq!(back.get_vertex_value(20).unwrap(), "");
+        assert_eq!(back.get_adjacents(30).unwrap(), vec![&30]);
+        assert_eq!(back.edge_count(), 3);
+    }
+}
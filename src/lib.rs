@@ -1,7 +1,12 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::fmt::{Display, Write};
-use std::fs;
-use std::str::FromStr;
+use crate::adjacency::Adjacency;
+use crate::attributes::Attributes;
+use crate::changes::ChangeSet;
+use crate::connectivity::Connectivity;
+use crate::hash::{HashMap, HashSet};
+use crate::observer::Observers;
+use crate::traits::NodeIndex;
+use crate::visited::VisitedSet;
+use std::collections::VecDeque;
 
 #[cfg(test)]
 mod tests {
@@ -30,6 +35,7 @@ mod tests {
         assert_eq!(g.insert_edge(OrientedEdge(1, 3), 5), None);
         assert_eq!(g.insert_edge(OrientedEdge(1, 2), 3), None);
         assert_eq!(g.insert_edge(OrientedEdge(5, 3), 5), None);
+        assert_eq!(g.insert_edge(OrientedEdge(1, 5), 5), None);
         assert_eq!(g.insert_edge(OrientedEdge(1, 3), 7), Some(5));
         assert_eq!(g.insert_edge(OrientedEdge(3, 1), 3), None);
         let mut adjacents = g.get_adjacents(1).unwrap();
@@ -67,116 +73,347 @@ mod tests {
         adjacents.sort();
         assert_eq!(adjacents, vec![&2]);
     }
+
+    #[test]
+    fn traverse_dense_and_sparse_ids() {
+        for scale in [1, 1000] {
+            let mut g = Graph::<u32, u32>::new();
+            for v in 0..200 {
+                g.insert_node(v * scale, v);
+            }
+            for v in 0..199 {
+                g.insert_edge(OrientedEdge(v * scale, (v + 1) * scale), 0);
+            }
+            let mut traverse = g.traverse_bfs();
+            traverse.sort();
+            assert_eq!(traverse, (0..200).map(|v| v * scale).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn degrees() {
+        let mut g = Graph::<u32, u32>::new();
+        g.insert_node(1, 1);
+        g.insert_node(2, 2);
+        g.insert_node(3, 3);
+        g.insert_edge(OrientedEdge(1, 3), 5);
+        g.insert_edge(OrientedEdge(1, 2), 3);
+        g.insert_edge(OrientedEdge(1, 2), 4);
+        g.insert_edge(OrientedEdge(3, 1), 3);
+        g.insert_edge(OrientedEdge(2, 2), 3);
+        assert_eq!(g.edge_count(), 4);
+        assert_eq!((g.in_degree(2), g.out_degree(2)), (Some(2), Some(1)));
+        assert_eq!((g.in_degree(1), g.out_degree(1)), (Some(1), Some(2)));
+        assert_eq!(g.in_degree(4), None);
+        let mut predecessors = g.get_predecessors(2).unwrap();
+        predecessors.sort();
+        assert_eq!(predecessors, vec![&1, &2]);
+
+        g.remove_edge(OrientedEdge(1, 2));
+        g.remove_edge(OrientedEdge(1, 2));
+        assert_eq!(g.in_degree(2), Some(1));
+        g.remove_node(2);
+        g.remove_node(2);
+        assert_eq!(g.edge_count(), 2);
+        g.remove_node(1);
+        assert_eq!(g.edge_count(), 0);
+        assert_eq!((g.in_degree(3), g.out_degree(3)), (Some(0), Some(0)));
+        assert_eq!(g.vertex_count(), 1);
+    }
 }
 
+mod adjacency;
+pub mod algo;
+pub mod arena;
+mod attributes;
+mod batch;
+mod changes;
+pub mod concurrent;
+mod connectivity;
+pub mod csr;
+pub mod dag;
+#[cfg(feature = "disk")]
+pub mod disk;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod generators;
+pub mod hash;
+#[cfg(feature = "petgraph")]
+pub mod interop;
+#[cfg(feature = "ndarray")]
+mod matrix;
+pub mod measure;
+pub mod memory;
+mod metrics;
+mod observer;
+mod operators;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod path;
+#[cfg(feature = "persistent")]
+pub mod persistent;
+mod relabel;
+#[cfg(feature = "rand")]
+mod sampling;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod subgraph;
+pub mod temporal;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod traits;
+pub mod transaction;
+pub mod tree;
+pub mod visit;
+mod visited;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use arena::ArenaGraph;
+pub use attributes::{AttrMap, AttrValue};
+pub use batch::GraphOp;
+pub use concurrent::ConcurrentGraph;
+pub use csr::{CsrGraph, FrozenGraph};
+pub use dag::DagGraph;
+#[cfg(feature = "disk")]
+pub use disk::DiskGraph;
+pub use measure::Measure;
+pub use memory::MemoryUsage;
+pub use metrics::{Degree, Metrics};
+pub use observer::{GraphEvent, ObserverId};
+pub use operators::ProductGraph;
+pub use path::Path;
+#[cfg(feature = "persistent")]
+pub use persistent::PersistentGraph;
+pub use subgraph::Subgraph;
+pub use temporal::{Interval, TemporalGraph};
+pub use traits::{EdgeWeights, GraphBase, Neighbors, NodeIndexable};
+pub use transaction::{Journal, Transaction};
+pub use tree::Tree;
+pub use visit::{Control, Visitor};
+
 pub type VertexId = u32;
 pub type GenericError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 pub struct OrientedEdge(pub VertexId, pub VertexId);
 
+#[derive(Debug, Clone)]
 pub struct Graph<V, E> {
-    adj_list: HashMap<VertexId, HashMap<VertexId, E>>,
+    adj_list: HashMap<VertexId, Adjacency<E>>,
     vertices: HashMap<VertexId, V>,
+    // reverse index: the vertices with an edge to the key, so that removals and in-degree
+    // queries don't scan the adjacency of every vertex
+    incoming: HashMap<VertexId, HashSet<VertexId>>,
+    edge_count: usize,
+    // built on the first connectivity query, None until then
+    connectivity: Option<Connectivity>,
+    // what changed since the last write to a store, None while nothing is tracked
+    changes: Option<ChangeSet>,
+    observers: Observers<V, E>,
+    attributes: Attributes,
+    // sorted ids for NodeIndexable, built on demand and dropped when the vertex set changes
+    node_index: NodeIndex,
+}
+
+// only the two maps are compared, everything else is derived from them or bookkeeping
+impl<V: PartialEq, E: PartialEq> PartialEq for Graph<V, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices == other.vertices && self.adj_list == other.adj_list
+    }
+}
+
+impl<V: Eq, E: Eq> Eq for Graph<V, E> {}
+
+impl<V, E> Default for Graph<V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<V: Display + FromStr, E: Display + FromStr> Graph<V, E> {
+impl<V, E> Graph<V, E> {
     pub fn new() -> Graph<V, E> {
         Graph {
-            adj_list: HashMap::new(),
-            vertices: HashMap::new(),
-        }
-    }
-
-    pub fn serialize_to(&self, filename: &str) -> Result<(), GenericError> {
-        let mut tgf = String::new();
-        for (v_id, v_value) in self.vertices.iter() {
-            write!(tgf, "{} {}\n", v_id.to_string(), v_value.to_string())?;
-        }
-        tgf += "#\n";
-        for (v_from, v_map) in self.adj_list.iter() {
-            for (v_to, e_value) in v_map.iter() {
-                write!(
-                    tgf,
-                    "{} {} {}\n",
-                    v_from.to_string(),
-                    v_to.to_string(),
-                    e_value.to_string()
-                )?;
-            }
+            adj_list: HashMap::default(),
+            vertices: HashMap::default(),
+            incoming: HashMap::default(),
+            edge_count: 0,
+            connectivity: None,
+            changes: None,
+            observers: Observers::default(),
+            attributes: Attributes::default(),
+            node_index: NodeIndex::default(),
         }
-        fs::write(filename, &tgf)?;
-        Ok(())
-    }
-
-    pub fn deserialize_from(filename: &str) -> Result<Graph<V, E>, GenericError>
-    where
-        <V as FromStr>::Err: std::error::Error + Send + Sync + 'static,
-        <E as FromStr>::Err: std::error::Error + Send + Sync + 'static,
-    {
-        let input = fs::read_to_string(filename)?;
-        let (mut vertices, mut edges) = input.split_once('#').ok_or("# is missing")?;
-        vertices = vertices.trim();
-        edges = edges.trim();
-
-        let mut g = Graph::new();
-        for line in vertices.lines() {
-            let (id, val) = line
-                .split_once(' ')
-                .ok_or("value for each vertex is required")?;
-            g.insert_node(id.trim().parse::<VertexId>()?, val.trim().parse::<V>()?);
-        }
-        for line in edges.lines() {
-            let (from, suffix) = line.split_once(' ').ok_or("vertex_to is missing")?;
-            let (to, value) = suffix.split_once(' ').ok_or("edge value is missing")?;
-            g.insert_edge(
-                OrientedEdge(
-                    from.trim().parse::<VertexId>()?,
-                    to.trim().parse::<VertexId>()?,
-                ),
-                value.trim().parse::<E>()?,
-            );
-        }
-        Ok(g)
     }
 
     pub fn insert_node(&mut self, vertex_id: VertexId, value: V) -> Option<V> {
-        self.adj_list.entry(vertex_id).or_insert(HashMap::new());
-        self.vertices.insert(vertex_id, value)
+        self.adj_list.entry(vertex_id).or_default();
+        self.incoming.entry(vertex_id).or_default();
+        if let Some(connectivity) = &mut self.connectivity {
+            connectivity.insert_vertex(vertex_id);
+        }
+        if let Some(changes) = &mut self.changes {
+            changes.vertices.insert(vertex_id);
+        }
+        let previous = self.vertices.insert(vertex_id, value);
+        if previous.is_none() {
+            self.node_index.invalidate();
+        }
+        if !self.observers.is_empty() {
+            let value = &self.vertices[&vertex_id];
+            self.observers.notify(GraphEvent::NodeInserted {
+                id: vertex_id,
+                value,
+            });
+        }
+        previous
     }
 
     pub fn remove_node(&mut self, vertex_id: VertexId) -> Option<V> {
-        // remove edges that point to the removing vertex
-        for map in self.adj_list.values_mut() {
-            map.remove(&vertex_id);
+        let adjacency = self.adj_list.remove(&vertex_id)?;
+        let predecessors = self.incoming.remove(&vertex_id).unwrap();
+        if let Some(changes) = &mut self.changes {
+            changes.vertices.insert(vertex_id);
+            changes
+                .edges
+                .extend(predecessors.iter().map(|p| (*p, vertex_id)));
+            changes
+                .edges
+                .extend(adjacency.keys().map(|a| (vertex_id, *a)));
         }
-        self.adj_list.remove(&vertex_id);
-        self.vertices.remove(&vertex_id)
+        self.attributes.nodes.remove(&vertex_id);
+        if !self.attributes.edges.is_empty() {
+            for predecessor in &predecessors {
+                self.attributes.edges.remove(&(*predecessor, vertex_id));
+            }
+            for adjacent in adjacency.keys() {
+                self.attributes.edges.remove(&(vertex_id, *adjacent));
+            }
+        }
+        // a self-loop is in both sets, it is counted with the incoming edges only
+        for predecessor in predecessors {
+            if let Some(map) = self.adj_list.get_mut(&predecessor) {
+                let removed = map.remove(&vertex_id);
+                if let Some(value) = &removed {
+                    if !self.observers.is_empty() {
+                        self.observers.notify(GraphEvent::EdgeRemoved {
+                            from: predecessor,
+                            to: vertex_id,
+                            value,
+                        });
+                    }
+                }
+            }
+            self.edge_count -= 1;
+        }
+        for (adjacent, value) in &adjacency {
+            if let Some(predecessors) = self.incoming.get_mut(adjacent) {
+                predecessors.remove(&vertex_id);
+                self.edge_count -= 1;
+            }
+            if !self.observers.is_empty() {
+                self.observers.notify(GraphEvent::EdgeRemoved {
+                    from: vertex_id,
+                    to: *adjacent,
+                    value,
+                });
+            }
+        }
+        if let Some(connectivity) = &mut self.connectivity {
+            connectivity.remove_vertex(vertex_id);
+        }
+        self.node_index.invalidate();
+        let removed = self.vertices.remove(&vertex_id);
+        if let Some(value) = &removed {
+            if !self.observers.is_empty() {
+                self.observers.notify(GraphEvent::NodeRemoved {
+                    id: vertex_id,
+                    value,
+                });
+            }
+        }
+        removed
     }
 
+    // the edge is ignored unless both of its vertices exist
     pub fn insert_edge(&mut self, edge: OrientedEdge, value: E) -> Option<E> {
-        self.adj_list.get_mut(&edge.0)?.insert(edge.1, value)
+        if !self.vertices.contains_key(&edge.1) {
+            return None;
+        }
+        let previous = self.adj_list.get_mut(&edge.0)?.insert(edge.1, value);
+        if let Some(changes) = &mut self.changes {
+            changes.edges.insert((edge.0, edge.1));
+        }
+        if previous.is_none() {
+            self.incoming.get_mut(&edge.1).unwrap().insert(edge.0);
+            self.edge_count += 1;
+            if let Some(connectivity) = &mut self.connectivity {
+                connectivity.insert_edge(edge.0, edge.1);
+            }
+        }
+        if !self.observers.is_empty() {
+            let value = &self.adj_list[&edge.0][&edge.1];
+            self.observers.notify(GraphEvent::EdgeInserted {
+                from: edge.0,
+                to: edge.1,
+                value,
+            });
+        }
+        previous
     }
 
     pub fn remove_edge(&mut self, edge: OrientedEdge) -> Option<E> {
-        self.adj_list.get_mut(&edge.0)?.remove(&edge.1)
+        let removed = self.adj_list.get_mut(&edge.0)?.remove(&edge.1)?;
+        self.incoming.get_mut(&edge.1).unwrap().remove(&edge.0);
+        self.attributes.edges.remove(&(edge.0, edge.1));
+        if let Some(changes) = &mut self.changes {
+            changes.edges.insert((edge.0, edge.1));
+        }
+        self.edge_count -= 1;
+        if let Some(connectivity) = &mut self.connectivity {
+            connectivity.invalidate();
+        }
+        if !self.observers.is_empty() {
+            self.observers.notify(GraphEvent::EdgeRemoved {
+                from: edge.0,
+                to: edge.1,
+                value: &removed,
+            });
+        }
+        Some(removed)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn in_degree(&self, vertex: VertexId) -> Option<usize> {
+        Some(self.incoming.get(&vertex)?.len())
+    }
+
+    pub fn out_degree(&self, vertex: VertexId) -> Option<usize> {
+        Some(self.adj_list.get(&vertex)?.len())
     }
 
     pub fn traverse_bfs(&self) -> Vec<VertexId> {
         let mut traverse = Vec::new();
         let mut queue = VecDeque::new();
-        let mut used = HashSet::new();
+        let mut used = VisitedSet::new(self.vertices.keys().copied());
 
         for start_vertex in self.vertices.keys() {
-            if !used.contains(start_vertex) {
+            if !used.contains(*start_vertex) {
                 queue.push_back(*start_vertex);
                 used.insert(*start_vertex);
                 while !queue.is_empty() {
                     let current_vertex = queue.pop_front().unwrap();
                     traverse.push(current_vertex);
                     for adjacent in self.adj_list.get(&current_vertex).unwrap().keys() {
-                        if !used.contains(adjacent) {
+                        if used.insert(*adjacent) {
                             queue.push_back(*adjacent);
-                            used.insert(*adjacent);
                         }
                     }
                 }
@@ -189,7 +426,11 @@ impl<V: Display + FromStr, E: Display + FromStr> Graph<V, E> {
         Some(self.adj_list.get(&vertex)?.keys().collect())
     }
 
+    pub fn get_predecessors(&self, vertex: VertexId) -> Option<Vec<&VertexId>> {
+        Some(self.incoming.get(&vertex)?.iter().collect())
+    }
+
     pub fn get_vertex_value(&self, vertex: VertexId) -> Option<&V> {
-        Some(self.vertices.get(&vertex)?)
+        self.vertices.get(&vertex)
     }
 }
@@ -0,0 +1,96 @@
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+use ndarray::{Array2, ArrayBase, Data, Ix2};
+
+// dense matrices for numeric code; rows and columns follow the returned ids, which are in
+// ascending order, and entry (i, j) is the weight of the edge from ids[i] to ids[j]
+impl<V, E: Copy + Into<f64>> Graph<V, E> {
+    pub fn adjacency_matrix(&self) -> (Array2<f64>, Vec<VertexId>) {
+        let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let mut matrix = Array2::zeros((ids.len(), ids.len()));
+        for (i, from) in ids.iter().enumerate() {
+            for (to, value) in &self.adj_list[from] {
+                let j = ids.binary_search(to).unwrap();
+                matrix[[i, j]] = (*value).into();
+            }
+        }
+        (matrix, ids)
+    }
+
+    // L = D - A with D the weighted out-degrees; for an undirected graph stored with both
+    // directions of every edge this is the usual symmetric Laplacian
+    pub fn laplacian_matrix(&self) -> (Array2<f64>, Vec<VertexId>) {
+        let (adjacency, ids) = self.adjacency_matrix();
+        let mut laplacian = -&adjacency;
+        for (i, row) in adjacency.rows().into_iter().enumerate() {
+            laplacian[[i, i]] += row.sum();
+        }
+        (laplacian, ids)
+    }
+}
+
+impl<V: Default> Graph<V, f64> {
+    // row i becomes vertex i, every non-zero entry an edge weighted with it
+    pub fn from_adjacency_matrix<S: Data<Elem = f64>>(
+        matrix: &ArrayBase<S, Ix2>,
+    ) -> Result<Graph<V, f64>, GenericError> {
+        let (rows, cols) = matrix.dim();
+        if rows != cols {
+            return Err(format!("adjacency matrix must be square, got {}x{}", rows, cols).into());
+        }
+        let mut graph = Graph::new();
+        for id in 0..rows {
+            graph.insert_node(id as VertexId, V::default());
+        }
+        for ((from, to), value) in matrix.indexed_iter() {
+            if *value != 0.0 {
+                graph.insert_edge(OrientedEdge(from as VertexId, to as VertexId), *value);
+            }
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn matrices() {
+        let mut g = Graph::<(), u32>::new();
+        for v in [30, 10, 20] {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(10, 20), 2);
+        g.insert_edge(OrientedEdge(20, 10), 2);
+        g.insert_edge(OrientedEdge(20, 30), 5);
+
+        let (adjacency, ids) = g.adjacency_matrix();
+        assert_eq!(ids, vec![10, 20, 30]);
+        assert_eq!(
+            adjacency,
+            array![[0.0, 2.0, 0.0], [2.0, 0.0, 5.0], [0.0, 0.0, 0.0]]
+        );
+        let (laplacian, _) = g.laplacian_matrix();
+        assert_eq!(
+            laplacian,
+            array![[2.0, -2.0, 0.0], [-2.0, 7.0, -5.0], [0.0, 0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn from_matrix() {
+        let g = Graph::<(), f64>::from_adjacency_matrix(&array![[0.0, 1.5], [0.0, 0.0]]).unwrap();
+        assert_eq!(g.vertex_count(), 2);
+        assert_eq!(g.edge_count(), 1);
+        assert_eq!(g.get_adjacents(0).unwrap(), vec![&1]);
+        assert_eq!(g.adjacency_matrix().0, array![[0.0, 1.5], [0.0, 0.0]]);
+
+        let error = Graph::<(), f64>::from_adjacency_matrix(&Array2::zeros((2, 3))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "adjacency matrix must be square, got 2x3"
+        );
+    }
+}
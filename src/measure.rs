@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+
+// what the weighted algorithms need from an edge weight; it is implemented for the integer
+// and floating point types and can be implemented for custom cost structs. Weights are
+// expected to be non-negative.
+pub trait Measure: Clone {
+    fn zero() -> Self;
+
+    // None when the sum can't be represented; the algorithms then treat the edge as
+    // impassable, which is also how NaN float weights are handled
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    fn saturating_add(&self, other: &Self) -> Self;
+
+    // must be a total order over every value checked_add can produce
+    fn compare(&self, other: &Self) -> Ordering;
+
+    // a value larger than any path, for types that have one
+    fn infinity() -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! integer_measure {
+    ($($t:ty),*) => {
+        $(
+            impl Measure for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *other)
+                }
+
+                fn saturating_add(&self, other: &Self) -> Self {
+                    <$t>::saturating_add(*self, *other)
+                }
+
+                fn compare(&self, other: &Self) -> Ordering {
+                    self.cmp(other)
+                }
+            }
+        )*
+    };
+}
+
+integer_measure!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! float_measure {
+    ($($t:ty),*) => {
+        $(
+            impl Measure for $t {
+                fn zero() -> Self {
+                    0.0
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    let sum = self + other;
+                    (!sum.is_nan()).then_some(sum)
+                }
+
+                // overflow already ends at infinity
+                fn saturating_add(&self, other: &Self) -> Self {
+                    self + other
+                }
+
+                fn compare(&self, other: &Self) -> Ordering {
+                    self.total_cmp(other)
+                }
+
+                fn infinity() -> Option<Self> {
+                    Some(<$t>::INFINITY)
+                }
+            }
+        )*
+    };
+}
+
+float_measure!(f32, f64);
+
+// heap entry ordered by its measure alone, reversed so that BinaryHeap pops the smallest
+pub(crate) struct MinScored<W, T>(pub(crate) W, pub(crate) T);
+
+impl<W: Measure, T> PartialEq for MinScored<W, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Measure, T> Eq for MinScored<W, T> {}
+
+impl<W: Measure, T> PartialOrd for MinScored<W, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Measure, T> Ord for MinScored<W, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.compare(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn measures() {
+        assert_eq!(Measure::checked_add(&250u8, &5), Some(255));
+        assert_eq!(Measure::checked_add(&250u8, &6), None);
+        assert_eq!(Measure::saturating_add(&250u8, &6), 255);
+        assert_eq!(<u8 as Measure>::infinity(), None);
+        assert_eq!(Measure::checked_add(&1.5, &f64::NAN), None);
+        assert_eq!(
+            Measure::checked_add(&f64::INFINITY, &f64::NEG_INFINITY),
+            None
+        );
+        assert_eq!(Measure::saturating_add(&f64::MAX, &f64::MAX), f64::INFINITY);
+        assert_eq!(Measure::compare(&0.5, &f64::INFINITY), Ordering::Less);
+        assert_eq!(<f32 as Measure>::infinity(), Some(f32::INFINITY));
+    }
+
+    // a cost that isn't a number: fewest hops first, then the shortest distance
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Cost {
+        hops: u32,
+        distance: f64,
+    }
+
+    impl Measure for Cost {
+        fn zero() -> Self {
+            Cost {
+                hops: 0,
+                distance: 0.0,
+            }
+        }
+
+        fn checked_add(&self, other: &Self) -> Option<Self> {
+            Some(Cost {
+                hops: self.hops.checked_add(other.hops)?,
+                distance: Measure::checked_add(&self.distance, &other.distance)?,
+            })
+        }
+
+        fn saturating_add(&self, other: &Self) -> Self {
+            Cost {
+                hops: self.hops.saturating_add(other.hops),
+                distance: self.distance + other.distance,
+            }
+        }
+
+        fn compare(&self, other: &Self) -> Ordering {
+            self.hops
+                .cmp(&other.hops)
+                .then(self.distance.total_cmp(&other.distance))
+        }
+    }
+
+    #[test]
+    fn weighted_algorithms() {
+        let mut g = Graph::<(), f64>::new();
+        for v in 1..=4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), 0.5);
+        g.insert_edge(OrientedEdge(2, 4), 0.25);
+        g.insert_edge(OrientedEdge(1, 3), 0.1);
+        g.insert_edge(OrientedEdge(3, 4), f64::NAN);
+        let path = g.freeze().shortest_path(1, 4).unwrap();
+        assert_eq!(path.vertices(), [1, 2, 4]);
+        assert_eq!(path.cost(), 0.75);
+
+        let mut costs = Graph::<(), Cost>::new();
+        for v in 1..=4 {
+            costs.insert_node(v, ());
+        }
+        let edge = |distance| Cost { hops: 1, distance };
+        costs.insert_edge(OrientedEdge(1, 2), edge(1.0));
+        costs.insert_edge(OrientedEdge(2, 3), edge(1.0));
+        costs.insert_edge(OrientedEdge(1, 3), edge(5.0));
+        let path = costs.freeze().shortest_path(1, 3).unwrap();
+        assert_eq!(path.vertices(), [1, 3]);
+        assert_eq!(path.cost(), edge(5.0));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::adjacency::Adjacency;
+use crate::hash::HashSet;
+use crate::{Graph, VertexId};
+use std::mem::size_of;
+
+// estimated heap bytes held by the graph itself, heap memory owned by the payloads
+// (e.g. the buffer of a String) isn't included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    // vertex table without the vertex payloads
+    pub vertices: usize,
+    // adjacency tables without the edge payloads
+    pub adjacency: usize,
+    // inline storage of vertex and edge payloads
+    pub payloads: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize {
+        self.vertices + self.adjacency + self.payloads
+    }
+}
+
+// hashbrown keeps a power of two buckets with a 7/8 load factor plus a control byte per
+// bucket and a trailing group
+pub(crate) fn buckets(capacity: usize) -> usize {
+    match capacity {
+        0 => 0,
+        1..=7 => capacity + 1,
+        _ => (capacity / 7 * 8).next_power_of_two(),
+    }
+}
+
+pub(crate) fn table_bytes(capacity: usize, entry: usize) -> usize {
+    match buckets(capacity) {
+        0 => 0,
+        buckets => buckets * (entry + 1) + 16,
+    }
+}
+
+impl<V, E> Graph<V, E> {
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let vertex_payloads = buckets(self.vertices.capacity()) * size_of::<V>();
+        let mut usage = MemoryUsage {
+            vertices: table_bytes(self.vertices.capacity(), size_of::<(VertexId, V)>())
+                - vertex_payloads,
+            adjacency: table_bytes(
+                self.adj_list.capacity(),
+                size_of::<(VertexId, Adjacency<E>)>(),
+            ),
+            payloads: vertex_payloads,
+        };
+        usage.adjacency += table_bytes(
+            self.incoming.capacity(),
+            size_of::<(VertexId, HashSet<VertexId>)>(),
+        );
+        for predecessors in self.incoming.values() {
+            usage.adjacency += table_bytes(predecessors.capacity(), size_of::<VertexId>());
+        }
+        for adjacency in self.adj_list.values() {
+            let (bytes, edge_payloads) = adjacency.heap_bytes();
+            usage.adjacency += bytes - edge_payloads;
+            usage.payloads += edge_payloads;
+        }
+        usage
+    }
+
+    // releases the spare capacity left behind by bulk removals
+    pub fn shrink_to_fit(&mut self) {
+        self.vertices.shrink_to_fit();
+        self.adj_list.shrink_to_fit();
+        self.incoming.shrink_to_fit();
+        for predecessors in self.incoming.values_mut() {
+            predecessors.shrink_to_fit();
+        }
+        for adjacency in self.adj_list.values_mut() {
+            adjacency.shrink_to_fit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn empty_graph() {
+        assert_eq!(Graph::<u64, u64>::new().memory_usage().total(), 0);
+    }
+
+    #[test]
+    fn shrink_after_removal() {
+        let mut g = Graph::<u64, u64>::new();
+        for v in 0..1000 {
+            g.insert_node(v, v as u64);
+        }
+        for v in 0..999 {
+            g.insert_edge(OrientedEdge(v, v + 1), 1);
+        }
+        let full = g.memory_usage();
+        assert!(full.payloads >= 1000 * 8 + 999 * 8);
+        assert!(full.vertices >= 1000 * 4);
+
+        for v in 10..1000 {
+            g.remove_node(v);
+        }
+        assert_eq!(g.memory_usage().vertices, full.vertices);
+        g.shrink_to_fit();
+        let shrunk = g.memory_usage();
+        assert!(shrunk.total() * 10 < full.total());
+        assert_eq!(g.get_adjacents(3).unwrap(), vec![&4]);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::connectivity::Connectivity;
+use crate::{Graph, VertexId};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Degree {
+    In,
+    Out,
+    // in plus out, a self-loop counts twice
+    Total,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    pub vertices: usize,
+    pub edges: usize,
+    // edges over the n * (n - 1) possible ones between distinct vertices
+    pub density: f64,
+    // the share of edges between distinct vertices whose reverse edge exists too
+    pub reciprocity: f64,
+    // in-degree plus out-degree, so 2 * edges / vertices
+    pub average_degree: f64,
+    // weakly connected
+    pub components: usize,
+    pub self_loops: usize,
+    // without any edge, a vertex with only a self-loop isn't isolated
+    pub isolated: usize,
+}
+
+impl<V, E> Graph<V, E> {
+    // everything is gathered in one pass over the adjacency lists; ratios are 0 when they
+    // would divide by zero
+    pub fn metrics(&self) -> Metrics {
+        let mut connectivity = Connectivity::default();
+        let (mut self_loops, mut reciprocal, mut isolated) = (0, 0, 0);
+        for (from, adjacency) in &self.adj_list {
+            connectivity.insert_vertex(*from);
+            if adjacency.len() == 0 && self.incoming[from].is_empty() {
+                isolated += 1;
+            }
+            for to in adjacency.keys() {
+                connectivity.insert_vertex(*to);
+                connectivity.insert_edge(*from, *to);
+                if to == from {
+                    self_loops += 1;
+                } else if self.adj_list[to].contains_key(from) {
+                    reciprocal += 1;
+                }
+            }
+        }
+        let (n, m) = (self.vertices.len(), self.edge_count);
+        let ratio = |a: usize, b: usize| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+        Metrics {
+            vertices: n,
+            edges: m,
+            density: ratio(m - self_loops, n * n.saturating_sub(1)),
+            reciprocity: ratio(reciprocal, m - self_loops),
+            average_degree: ratio(2 * m, n),
+            components: connectivity.components(),
+            self_loops,
+            isolated,
+        }
+    }
+}
+
+impl<V, E> Graph<V, E> {
+    fn degree_of(&self, vertex: &VertexId, kind: Degree) -> usize {
+        let (incoming, outgoing) = (self.incoming[vertex].len(), self.adj_list[vertex].len());
+        match kind {
+            Degree::In => incoming,
+            Degree::Out => outgoing,
+            Degree::Total => incoming + outgoing,
+        }
+    }
+
+    // how many vertices have each degree, degrees nobody has are left out
+    pub fn degree_distribution(&self, kind: Degree) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+        for vertex in self.vertices.keys() {
+            *distribution
+                .entry(self.degree_of(vertex, kind))
+                .or_default() += 1;
+        }
+        distribution
+    }
+
+    // the k vertices with the highest degree, highest first and ties by id
+    pub fn highest_degree(&self, k: usize, kind: Degree) -> Vec<(VertexId, usize)> {
+        let mut degrees: Vec<(VertexId, usize)> = self
+            .vertices
+            .keys()
+            .map(|v| (*v, self.degree_of(v, kind)))
+            .collect();
+        let key = |(v, degree): &(VertexId, usize)| (Reverse(*degree), *v);
+        if k < degrees.len() {
+            degrees.select_nth_unstable_by_key(k, key);
+            degrees.truncate(k);
+        }
+        degrees.sort_unstable_by_key(key);
+        degrees
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn metrics() {
+        let mut g = Graph::<(), ()>::new();
+        assert_eq!(g.metrics().density, 0.0);
+        for v in 1..=5 {
+            g.insert_node(v, ());
+        }
+        for (from, to) in [(1, 2), (2, 1), (2, 3), (3, 3), (4, 4)] {
+            g.insert_edge(OrientedEdge(from, to), ());
+        }
+        assert_eq!(
+            g.metrics(),
+            Metrics {
+                vertices: 5,
+                edges: 5,
+                density: 0.15,
+                reciprocity: 2.0 / 3.0,
+                average_degree: 2.0,
+                components: 3,
+                self_loops: 2,
+                isolated: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn degrees() {
+        let mut g = Graph::<(), ()>::new();
+        for v in 1..=5 {
+            g.insert_node(v, ());
+        }
+        for (from, to) in [(1, 2), (1, 3), (1, 4), (2, 3), (4, 4)] {
+            g.insert_edge(OrientedEdge(from, to), ());
+        }
+        let pairs = |d: std::collections::BTreeMap<usize, usize>| d.into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            pairs(g.degree_distribution(Degree::Out)),
+            [(0, 2), (1, 2), (3, 1)]
+        );
+        assert_eq!(
+            pairs(g.degree_distribution(Degree::In)),
+            [(0, 2), (1, 1), (2, 2)]
+        );
+        assert_eq!(
+            pairs(g.degree_distribution(Degree::Total)),
+            [(0, 1), (2, 2), (3, 2)]
+        );
+        assert_eq!(g.highest_degree(2, Degree::Total), [(1, 3), (4, 3)]);
+        assert_eq!(g.highest_degree(1, Degree::In), [(3, 2)]);
+        assert_eq!(g.highest_degree(9, Degree::Out).len(), 5);
+        assert_eq!(g.highest_degree(0, Degree::Out), []);
+    }
+}
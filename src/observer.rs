@@ -0,0 +1,174 @@
+use crate::{AttrValue, Graph, VertexId};
+
+// what a mutation did, with the value that was inserted or removed; a removed vertex
+// first reports each of its edges as removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphEvent<'a, V, E> {
+    // also sent when an existing vertex gets a new value
+    NodeInserted {
+        id: VertexId,
+        value: &'a V,
+    },
+    NodeRemoved {
+        id: VertexId,
+        value: &'a V,
+    },
+    // also sent when an existing edge gets a new value
+    EdgeInserted {
+        from: VertexId,
+        to: VertexId,
+        value: &'a E,
+    },
+    EdgeRemoved {
+        from: VertexId,
+        to: VertexId,
+        value: &'a E,
+    },
+    // set or, with None, removed; attributes going away with their vertex or edge aren't
+    // reported on their own
+    NodeAttrChanged {
+        id: VertexId,
+        key: &'a str,
+        value: Option<&'a AttrValue>,
+    },
+    EdgeAttrChanged {
+        from: VertexId,
+        to: VertexId,
+        key: &'a str,
+        value: Option<&'a AttrValue>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(usize);
+
+type Callback<V, E> = Box<dyn FnMut(&GraphEvent<'_, V, E>) + Send + Sync>;
+
+// callbacks aren't carried over when the graph is cloned
+pub(crate) struct Observers<V, E> {
+    next_id: usize,
+    callbacks: Vec<(ObserverId, Callback<V, E>)>,
+}
+
+impl<V, E> Observers<V, E> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    pub(crate) fn notify(&mut self, event: GraphEvent<'_, V, E>) {
+        for (_, callback) in &mut self.callbacks {
+            callback(&event);
+        }
+    }
+}
+
+impl<V, E> Default for Observers<V, E> {
+    fn default() -> Self {
+        Observers {
+            next_id: 0,
+            callbacks: Vec::new(),
+        }
+    }
+}
+
+impl<V, E> Clone for Observers<V, E> {
+    fn clone(&self) -> Self {
+        Observers::default()
+    }
+}
+
+impl<V, E> std::fmt::Debug for Observers<V, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} observers", self.callbacks.len())
+    }
+}
+
+impl<V, E> Graph<V, E> {
+    // the callback runs synchronously inside every mutating call, in subscription order
+    pub fn on_change(
+        &mut self,
+        callback: impl FnMut(&GraphEvent<'_, V, E>) + Send + Sync + 'static,
+    ) -> ObserverId {
+        let id = ObserverId(self.observers.next_id);
+        self.observers.next_id += 1;
+        self.observers.callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        let before = self.observers.callbacks.len();
+        self.observers.callbacks.retain(|(other, _)| *other != id);
+        self.observers.callbacks.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn events() {
+        let mut g = Graph::<&str, u32>::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        let id = g.on_change(move |event| {
+            sink.lock().unwrap().push(match event {
+                GraphEvent::NodeInserted { id, value } => format!("+{}={}", id, value),
+                GraphEvent::NodeRemoved { id, value } => format!("-{}={}", id, value),
+                GraphEvent::EdgeInserted { from, to, value } => {
+                    format!("+{}>{}={}", from, to, value)
+                }
+                GraphEvent::EdgeRemoved { from, to, value } => {
+                    format!("-{}>{}={}", from, to, value)
+                }
+                GraphEvent::NodeAttrChanged { id, key, value } => {
+                    format!("{}.{}={:?}", id, key, value.map(|v| v.to_string()))
+                }
+                GraphEvent::EdgeAttrChanged {
+                    from,
+                    to,
+                    key,
+                    value,
+                } => format!("{}>{}.{}={:?}", from, to, key, value.map(|v| v.to_string())),
+            })
+        });
+        g.insert_node(1, "a");
+        g.insert_node(2, "b");
+        g.insert_edge(OrientedEdge(1, 2), 12);
+        g.insert_edge(OrientedEdge(1, 3), 13);
+        g.insert_edge(OrientedEdge(2, 2), 22);
+        g.insert_edge(OrientedEdge(2, 1), 21);
+        g.remove_edge(OrientedEdge(1, 2));
+        g.remove_edge(OrientedEdge(1, 2));
+        g.set_node_attr(2, "color", "red");
+        g.set_edge_attr(OrientedEdge(2, 1), "w", 2);
+        g.remove_edge_attr(OrientedEdge(2, 1), "w");
+        g.remove_edge_attr(OrientedEdge(2, 1), "w");
+        g.remove_node(2);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "+1=a",
+                "+2=b",
+                "+1>2=12",
+                "+2>2=22",
+                "+2>1=21",
+                "-1>2=12",
+                "2.color=Some(\"red\")",
+                "2>1.w=Some(\"2\")",
+                "2>1.w=None",
+                "-2>2=22",
+                "-2>1=21",
+                "-2=b"
+            ]
+        );
+
+        let copy = g.clone();
+        assert!(g.remove_observer(id));
+        assert!(!g.remove_observer(id));
+        g.insert_node(5, "e");
+        drop(copy);
+        assert_eq!(log.lock().unwrap().len(), 12);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::generators::Direction;
+use crate::hash::HashMap;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+
+fn sorted_ids<V, E>(graph: &Graph<V, E>) -> Vec<VertexId> {
+    let mut ids: Vec<VertexId> = graph.vertices.keys().copied().collect();
+    ids.sort_unstable();
+    ids
+}
+
+// vertex (a, b) of a product is numbered row-major by the position of each factor's id in
+// sorted order; product_vertices makes sure every number fits into a VertexId
+struct ProductIds {
+    left: HashMap<VertexId, usize>,
+    right: HashMap<VertexId, usize>,
+}
+
+impl ProductIds {
+    fn id(&self, a: VertexId, b: VertexId) -> VertexId {
+        (self.left[&a] * self.right.len() + self.right[&b]) as VertexId
+    }
+}
+
+// the vertices of a product are pairs of the factors' vertices, each holding both payloads
+pub type ProductGraph<V, W, E> = Graph<(V, W), E>;
+
+fn product_vertices<V: Clone, E, W: Clone, F, G>(
+    left: &Graph<V, E>,
+    right: &Graph<W, F>,
+) -> Result<(ProductGraph<V, W, G>, ProductIds), GenericError> {
+    let count = (left.vertices.len() as u64).checked_mul(right.vertices.len() as u64);
+    if count.is_none_or(|count| count > VertexId::MAX as u64 + 1) {
+        return Err(format!(
+            "a product of {} and {} vertices doesn't fit into vertex ids",
+            left.vertices.len(),
+            right.vertices.len()
+        )
+        .into());
+    }
+    let index = |graph_ids: Vec<VertexId>| -> HashMap<VertexId, usize> {
+        graph_ids
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| (id, i))
+            .collect()
+    };
+    let ids = ProductIds {
+        left: index(sorted_ids(left)),
+        right: index(sorted_ids(right)),
+    };
+    let mut product = Graph::new();
+    for (a, value) in &left.vertices {
+        for (b, other) in &right.vertices {
+            product.insert_node(ids.id(*a, *b), (value.clone(), other.clone()));
+        }
+    }
+    Ok((product, ids))
+}
+
+impl<V: Clone, E> Graph<V, E> {
+    // the same vertices joined by exactly the edges that are missing, self-loops never;
+    // undirected, a pair is connected when neither direction exists in the original
+    pub fn complement(&self, direction: Direction) -> Graph<V, ()> {
+        let ids = sorted_ids(self);
+        let mut complement = Graph::new();
+        for id in &ids {
+            complement.insert_node(*id, self.vertices[id].clone());
+        }
+        for (i, from) in ids.iter().enumerate() {
+            for to in &ids[i + 1..] {
+                let forward = self.adj_list[from].contains_key(to);
+                let backward = self.adj_list[to].contains_key(from);
+                match direction {
+                    Direction::Directed => {
+                        if !forward {
+                            complement.insert_edge(OrientedEdge(*from, *to), ());
+                        }
+                        if !backward {
+                            complement.insert_edge(OrientedEdge(*to, *from), ());
+                        }
+                    }
+                    Direction::Undirected if !forward && !backward => {
+                        complement.insert_edge(OrientedEdge(*from, *to), ());
+                        complement.insert_edge(OrientedEdge(*to, *from), ());
+                    }
+                    Direction::Undirected => {}
+                }
+            }
+        }
+        complement
+    }
+
+    // (a, b) -> (a', b) for every edge a -> a' of self and (a, b) -> (a, b') for every edge
+    // b -> b' of other, each keeping its factor's weight; path x path is a grid, cycle x
+    // cycle a torus. Vertex (a, b) gets the id index(a) * other.vertex_count() + index(b),
+    // with the indices taken over each factor's sorted ids; fails when the product has more
+    // vertices than there are ids
+    pub fn cartesian_product<W: Clone>(
+        &self,
+        other: &Graph<W, E>,
+    ) -> Result<ProductGraph<V, W, E>, GenericError>
+    where
+        E: Clone,
+    {
+        let (mut product, ids) = product_vertices(self, other)?;
+        for (a, adjacency) in &self.adj_list {
+            for (next, value) in adjacency.iter() {
+                for b in other.vertices.keys() {
+                    product.insert_edge(
+                        OrientedEdge(ids.id(*a, *b), ids.id(*next, *b)),
+                        value.clone(),
+                    );
+                }
+            }
+        }
+        for (b, adjacency) in &other.adj_list {
+            for (next, value) in adjacency.iter() {
+                for a in self.vertices.keys() {
+                    product.insert_edge(
+                        OrientedEdge(ids.id(*a, *b), ids.id(*a, *next)),
+                        value.clone(),
+                    );
+                }
+            }
+        }
+        Ok(product)
+    }
+
+    // (a, b) -> (a', b') whenever both a -> a' and b -> b' exist, weighted with the pair of
+    // weights; ids are numbered as in cartesian_product
+    pub fn tensor_product<W: Clone, F: Clone>(
+        &self,
+        other: &Graph<W, F>,
+    ) -> Result<ProductGraph<V, W, (E, F)>, GenericError>
+    where
+        E: Clone,
+    {
+        let (mut product, ids) = product_vertices(self, other)?;
+        for (a, left) in &self.adj_list {
+            for (b, right) in &other.adj_list {
+                for (next_a, e) in left.iter() {
+                    for (next_b, f) in right.iter() {
+                        let edge = OrientedEdge(ids.id(*a, *b), ids.id(*next_a, *next_b));
+                        product.insert_edge(edge, (e.clone(), f.clone()));
+                    }
+                }
+            }
+        }
+        Ok(product)
+    }
+
+    // a vertex per edge holding its weight, numbered by the edge's position in (from, to)
+    // order. Directed, e -> f whenever e's head is f's tail; undirected, the two directions
+    // of a pair merge into one vertex (weighted like the lower -> higher edge when both
+    // exist) and vertices sharing an endpoint are joined both ways
+    pub fn line_graph(&self, direction: Direction) -> Graph<E, ()>
+    where
+        E: Clone,
+    {
+        let mut edges: Vec<(VertexId, VertexId, &E)> = Vec::new();
+        for (from, adjacency) in &self.adj_list {
+            for (to, value) in adjacency.iter() {
+                match direction {
+                    Direction::Directed => edges.push((*from, *to, value)),
+                    Direction::Undirected if from <= to => edges.push((*from, *to, value)),
+                    Direction::Undirected if !self.adj_list[to].contains_key(from) => {
+                        edges.push((*to, *from, value))
+                    }
+                    Direction::Undirected => {}
+                }
+            }
+        }
+        edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+
+        let mut line = Graph::new();
+        let mut by_endpoint: HashMap<VertexId, Vec<VertexId>> = HashMap::default();
+        for (id, (from, to, value)) in edges.iter().enumerate() {
+            let id = id as VertexId;
+            line.insert_node(id, (*value).clone());
+            by_endpoint.entry(*from).or_default().push(id);
+            if direction == Direction::Undirected && from != to {
+                by_endpoint.entry(*to).or_default().push(id);
+            }
+        }
+        match direction {
+            Direction::Directed => {
+                for (id, (_, to, _)) in edges.iter().enumerate() {
+                    for next in by_endpoint.get(to).into_iter().flatten() {
+                        line.insert_edge(OrientedEdge(id as VertexId, *next), ());
+                    }
+                }
+            }
+            Direction::Undirected => {
+                for ids in by_endpoint.values() {
+                    for a in ids {
+                        for b in ids.iter().filter(|b| *b != a) {
+                            line.insert_edge(OrientedEdge(*a, *b), ());
+                        }
+                    }
+                }
+            }
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::Direction;
+    use crate::*;
+
+    #[test]
+    fn complement() {
+        let mut g = Graph::<u32, u32>::new();
+        for v in 1..=4 {
+            g.insert_node(v, v * 10);
+        }
+        g.insert_edge(OrientedEdge(1, 2), 0);
+        g.insert_edge(OrientedEdge(2, 1), 0);
+        g.insert_edge(OrientedEdge(2, 3), 0);
+        g.insert_edge(OrientedEdge(4, 4), 0);
+
+        let directed = g.complement(Direction::Directed);
+        assert_eq!(directed.get_vertex_value(3), Some(&30));
+        assert_eq!(directed.edge_count(), 12 - 3);
+        assert!(directed.edge_weight(3, 2).is_some());
+        assert!(directed.edge_weight(2, 3).is_none());
+        assert!(directed.edge_weight(4, 4).is_none());
+
+        let undirected = g.complement(Direction::Undirected);
+        assert_eq!(undirected.edge_count(), 2 * (6 - 2));
+        assert!(undirected.edge_weight(3, 2).is_none());
+        assert!(undirected.edge_weight(1, 4).is_some());
+        assert!(undirected.edge_weight(4, 1).is_some());
+
+        // complementing twice gives back the graph without its self-loops
+        let mut twice = directed.complement(Direction::Directed);
+        assert_eq!(twice.edge_count(), 3);
+        twice.insert_edge(OrientedEdge(4, 4), ());
+        assert_eq!(twice.edge_count(), g.edge_count());
+    }
+
+    #[test]
+    fn products() {
+        let path = generators::path::<(), u32>(3);
+        let mut edge = Graph::<char, u32>::new();
+        edge.insert_node(7, 'a');
+        edge.insert_node(9, 'b');
+        edge.insert_edge(OrientedEdge(7, 9), 5);
+
+        let grid = path.cartesian_product(&edge).unwrap();
+        let expected = generators::grid::<(), ()>(3, 2);
+        assert_eq!(grid.edge_count(), expected.edge_count());
+        for from in expected.vertex_ids() {
+            for (to, _) in expected.edges(from) {
+                let weight = if to == from + 1 { 5 } else { 0 };
+                assert_eq!(grid.edge_weight(from, to), Some(&weight));
+            }
+        }
+        assert_eq!(grid.get_vertex_value(4), Some(&((), 'a')));
+
+        let torus = generators::cycle::<(), ()>(3)
+            .cartesian_product(&generators::cycle::<(), ()>(4))
+            .unwrap();
+        assert_eq!(torus.vertex_count(), 12);
+        assert_eq!(torus.edge_count(), 24);
+        assert!(torus.vertex_ids().all(|v| torus.out_degree(v) == Some(2)));
+
+        let tensor = path.tensor_product(&edge).unwrap();
+        assert_eq!(tensor.vertex_count(), 6);
+        assert_eq!(tensor.edge_count(), 2);
+        assert_eq!(tensor.edge_weight(0, 3), Some(&(0, 5)));
+        assert_eq!(tensor.edge_weight(2, 5), Some(&(0, 5)));
+        assert_eq!(tensor.get_vertex_value(5), Some(&((), 'b')));
+
+        let empty = Graph::<(), u32>::new();
+        assert_eq!(path.cartesian_product(&empty).unwrap().vertex_count(), 0);
+
+        // 2^16 * (2^16 + 1) ids would wrap around
+        let wide = generators::path::<(), u32>(1 << 16);
+        let wider = generators::path::<(), u32>((1 << 16) + 1);
+        let error = wide.tensor_product(&wider).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "a product of 65536 and 65537 vertices doesn't fit into vertex ids"
+        );
+    }
+
+    #[test]
+    fn line_graph() {
+        let mut g = Graph::<(), char>::new();
+        for v in 1..=4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), 'a');
+        g.insert_edge(OrientedEdge(2, 3), 'b');
+        g.insert_edge(OrientedEdge(3, 2), 'c');
+        g.insert_edge(OrientedEdge(2, 4), 'd');
+
+        // vertices 0..4 stand for 1 -> 2, 2 -> 3, 2 -> 4 and 3 -> 2
+        let directed = g.line_graph(Direction::Directed);
+        assert_eq!(directed.vertex_count(), 4);
+        assert_eq!(directed.get_vertex_value(3), Some(&'c'));
+        assert_eq!(directed.edge_count(), 5);
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (3, 1), (3, 2)] {
+            assert!(directed.edge_weight(from, to).is_some());
+        }
+
+        // 2 - 3 is a single vertex now: 0 is 1 - 2, 1 is 2 - 3 and 2 is 2 - 4, all meeting at 2
+        let undirected = g.line_graph(Direction::Undirected);
+        assert_eq!(undirected.vertex_count(), 3);
+        assert_eq!(undirected.get_vertex_value(1), Some(&'b'));
+        assert_eq!(undirected.edge_count(), 6);
+        assert!(undirected.edge_weight(2, 0).is_some());
+    }
+}
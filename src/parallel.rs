@@ -0,0 +1,274 @@
+use crate::{CsrGraph, VertexId};
+use rayon::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// incoming edges in the same layout as the outgoing ones
+fn transpose(offsets: &[usize], targets: &[u32]) -> (Vec<usize>, Vec<u32>) {
+    let n = offsets.len() - 1;
+    let mut in_offsets = vec![0usize; n + 1];
+    for t in targets {
+        in_offsets[*t as usize + 1] += 1;
+    }
+    for i in 0..n {
+        in_offsets[i + 1] += in_offsets[i];
+    }
+    let mut fill = in_offsets.clone();
+    let mut sources = vec![0u32; targets.len()];
+    for v in 0..n {
+        for t in &targets[offsets[v]..offsets[v + 1]] {
+            sources[fill[*t as usize]] = v as u32;
+            fill[*t as usize] += 1;
+        }
+    }
+    (in_offsets, sources)
+}
+
+// BFS from a single source over the index space, unreached vertices stay u32::MAX
+fn bfs_distances(offsets: &[usize], targets: &[u32], source: usize) -> Vec<u32> {
+    let mut distance = vec![u32::MAX; offsets.len() - 1];
+    let mut queue = VecDeque::new();
+    distance[source] = 0;
+    queue.push_back(source);
+    while let Some(current) = queue.pop_front() {
+        for adjacent in &targets[offsets[current]..offsets[current + 1]] {
+            let adjacent = *adjacent as usize;
+            if distance[adjacent] == u32::MAX {
+                distance[adjacent] = distance[current] + 1;
+                queue.push_back(adjacent);
+            }
+        }
+    }
+    distance
+}
+
+// Brandes' dependency accumulation for a single source
+fn betweenness_from(offsets: &[usize], targets: &[u32], source: usize) -> Vec<f64> {
+    let n = offsets.len() - 1;
+    let mut stack = Vec::with_capacity(n);
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut paths = vec![0f64; n];
+    let mut distance = vec![u32::MAX; n];
+    let mut queue = VecDeque::new();
+    paths[source] = 1.0;
+    distance[source] = 0;
+    queue.push_back(source);
+    while let Some(current) = queue.pop_front() {
+        stack.push(current);
+        for adjacent in &targets[offsets[current]..offsets[current + 1]] {
+            let adjacent = *adjacent as usize;
+            if distance[adjacent] == u32::MAX {
+                distance[adjacent] = distance[current] + 1;
+                queue.push_back(adjacent);
+            }
+            if distance[adjacent] == distance[current] + 1 {
+                paths[adjacent] += paths[current];
+                predecessors[adjacent].push(current);
+            }
+        }
+    }
+    let mut dependency = vec![0f64; n];
+    while let Some(w) = stack.pop() {
+        for v in &predecessors[w] {
+            dependency[*v] += paths[*v] / paths[w] * (1.0 + dependency[w]);
+        }
+    }
+    dependency[source] = 0.0;
+    dependency
+}
+
+// data-parallel analytics over the read-only snapshot, results are keyed by vertex id
+impl<V, E> CsrGraph<V, E> {
+    // iterates until the L1 change of the ranks drops below tolerance, ranks of dangling
+    // vertices are spread evenly over the graph
+    pub fn par_pagerank(
+        &self,
+        damping: f64,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> HashMap<VertexId, f64> {
+        let n = self.ids.len();
+        if n == 0 {
+            return HashMap::new();
+        }
+        let offsets = &self.offsets;
+        let (in_offsets, sources) = transpose(offsets, &self.targets);
+        let out_degree = |v: usize| offsets[v + 1] - offsets[v];
+
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..max_iterations {
+            let dangling: f64 = (0..n)
+                .into_par_iter()
+                .filter(|v| out_degree(*v) == 0)
+                .map(|v| rank[v])
+                .sum();
+            let base = (1.0 - damping + damping * dangling) / n as f64;
+            let next: Vec<f64> = (0..n)
+                .into_par_iter()
+                .map(|v| {
+                    let incoming: f64 = sources[in_offsets[v]..in_offsets[v + 1]]
+                        .iter()
+                        .map(|u| rank[*u as usize] / out_degree(*u as usize) as f64)
+                        .sum();
+                    base + damping * incoming
+                })
+                .collect();
+            let change: f64 = rank
+                .par_iter()
+                .zip(next.par_iter())
+                .map(|(a, b)| (a - b).abs())
+                .sum();
+            rank = next;
+            if change < tolerance {
+                break;
+            }
+        }
+        self.ids.iter().copied().zip(rank).collect()
+    }
+
+    // hop distances from every vertex to every vertex reachable from it
+    pub fn par_all_pairs_distances(&self) -> HashMap<VertexId, HashMap<VertexId, usize>> {
+        let (ids, offsets, targets) = (&self.ids, &self.offsets, &self.targets);
+        (0..ids.len())
+            .into_par_iter()
+            .map(|source| {
+                let reachable = bfs_distances(offsets, targets, source)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, d)| *d != u32::MAX)
+                    .map(|(v, d)| (ids[v], d as usize))
+                    .collect();
+                (ids[source], reachable)
+            })
+            .collect()
+    }
+
+    // unnormalized betweenness centrality of the directed, unweighted graph
+    pub fn par_betweenness(&self) -> HashMap<VertexId, f64> {
+        let (n, offsets, targets) = (self.ids.len(), &self.offsets, &self.targets);
+        let centrality = (0..n)
+            .into_par_iter()
+            .fold(
+                || vec![0f64; n],
+                |mut acc, source| {
+                    for (a, d) in acc
+                        .iter_mut()
+                        .zip(betweenness_from(offsets, targets, source))
+                    {
+                        *a += d;
+                    }
+                    acc
+                },
+            )
+            .reduce(
+                || vec![0f64; n],
+                |mut a, b| {
+                    for (x, y) in a.iter_mut().zip(b) {
+                        *x += y;
+                    }
+                    a
+                },
+            );
+        self.ids.iter().copied().zip(centrality).collect()
+    }
+
+    // weakly connected components by label propagation, every vertex is mapped to the
+    // smallest id of its component
+    pub fn par_connected_components(&self) -> HashMap<VertexId, VertexId> {
+        let n = self.ids.len();
+        let (offsets, targets) = (&self.offsets, &self.targets);
+        let (in_offsets, sources) = transpose(offsets, targets);
+        let label: Vec<AtomicU32> = (0..n as u32).map(AtomicU32::new).collect();
+
+        loop {
+            let changed = (0..n)
+                .into_par_iter()
+                .map(|v| {
+                    let neighbors = targets[offsets[v]..offsets[v + 1]]
+                        .iter()
+                        .chain(&sources[in_offsets[v]..in_offsets[v + 1]]);
+                    let smallest = neighbors
+                        .map(|u| label[*u as usize].load(Ordering::Relaxed))
+                        .min()
+                        .unwrap_or(u32::MAX);
+                    label[v].fetch_min(smallest, Ordering::Relaxed) > smallest
+                })
+                .reduce(|| false, |a, b| a || b);
+            if !changed {
+                break;
+            }
+        }
+        // vertices are sorted by id, so the smallest index is also the smallest id
+        self.ids
+            .iter()
+            .zip(label)
+            .map(|(id, l)| (*id, self.ids[l.into_inner() as usize]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn sample() -> CsrGraph<(), ()> {
+        // 1 -> 2 -> 3 -> 1 cycle with a tail 3 -> 4, and a separate 10 -> 11 pair
+        let mut g = Graph::new();
+        for v in [1, 2, 3, 4, 10, 11] {
+            g.insert_node(v, ());
+        }
+        for (from, to) in [(1, 2), (2, 3), (3, 1), (3, 4), (10, 11)] {
+            g.insert_edge(OrientedEdge(from, to), ());
+        }
+        g.freeze()
+    }
+
+    #[test]
+    fn pagerank() {
+        let ranks = sample().par_pagerank(0.85, 100, 1e-12);
+        let total: f64 = ranks.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(ranks[&11] > ranks[&10]);
+        assert!(ranks[&3] > ranks[&4]);
+
+        let mut cycle = Graph::new();
+        for v in 0..4 {
+            cycle.insert_node(v, ());
+        }
+        for v in 0..4 {
+            cycle.insert_edge(OrientedEdge(v, (v + 1) % 4), ());
+        }
+        for rank in cycle.freeze().par_pagerank(0.85, 100, 1e-12).values() {
+            assert!((rank - 0.25).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn all_pairs_distances() {
+        let distances = sample().par_all_pairs_distances();
+        assert_eq!(distances[&1][&4], 3);
+        assert_eq!(distances[&3][&2], 2);
+        assert_eq!(distances[&4].len(), 1);
+        assert!(!distances[&10].contains_key(&1));
+    }
+
+    #[test]
+    fn betweenness() {
+        let centrality = sample().par_betweenness();
+        // 3 is on 2->1, 2->4 and 1->4, 2 is on 1->3 and 1->4, 1 is on 3->2
+        assert_eq!(centrality[&3], 3.0);
+        assert_eq!(centrality[&2], 2.0);
+        assert_eq!(centrality[&1], 1.0);
+        assert_eq!(centrality[&4], 0.0);
+        assert_eq!(centrality[&10], 0.0);
+    }
+
+    #[test]
+    fn connected_components() {
+        let components = sample().par_connected_components();
+        for v in [1, 2, 3, 4] {
+            assert_eq!(components[&v], 1);
+        }
+        assert_eq!(components[&11], 10);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::traits::EdgeWeights;
+use crate::{Measure, VertexId};
+
+// a walk through a graph: at least one vertex, and for every step the weight of the edge
+// taken; a path of a single vertex has no edges and costs nothing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<E> {
+    vertices: Vec<VertexId>,
+    // weights[i] belongs to the edge vertices[i] -> vertices[i + 1]
+    weights: Vec<E>,
+}
+
+impl<E> Path<E> {
+    pub fn new(start: VertexId) -> Path<E> {
+        Path {
+            vertices: vec![start],
+            weights: Vec::new(),
+        }
+    }
+
+    // None unless there is exactly one weight less than there are vertices
+    pub fn from_parts(vertices: Vec<VertexId>, weights: Vec<E>) -> Option<Path<E>> {
+        (!vertices.is_empty() && weights.len() + 1 == vertices.len())
+            .then_some(Path { vertices, weights })
+    }
+
+    pub fn push(&mut self, to: VertexId, weight: E) {
+        self.vertices.push(to);
+        self.weights.push(weight);
+    }
+
+    pub fn start(&self) -> VertexId {
+        self.vertices[0]
+    }
+
+    pub fn end(&self) -> VertexId {
+        *self.vertices.last().unwrap()
+    }
+
+    pub fn vertices(&self) -> &[VertexId] {
+        &self.vertices
+    }
+
+    pub fn weights(&self) -> &[E] {
+        &self.weights
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn iter_edges(&self) -> impl Iterator<Item = (VertexId, VertexId, &E)> + '_ {
+        self.vertices
+            .windows(2)
+            .zip(&self.weights)
+            .map(|(pair, weight)| (pair[0], pair[1], weight))
+    }
+
+    // the sum of the weights, saturating where the type can overflow
+    pub fn cost(&self) -> E
+    where
+        E: Measure,
+    {
+        self.weights
+            .iter()
+            .fold(E::zero(), |total, weight| total.saturating_add(weight))
+    }
+
+    // appends a path that starts where this one ends, None if it doesn't
+    pub fn concat(mut self, other: Path<E>) -> Option<Path<E>> {
+        if self.end() != other.start() {
+            return None;
+        }
+        self.vertices.extend(other.vertices.into_iter().skip(1));
+        self.weights.extend(other.weights);
+        Some(self)
+    }
+
+    // every vertex exists and every step is an edge of the graph with the recorded weight
+    pub fn is_valid_in<G: EdgeWeights<Weight = E>>(&self, graph: &G) -> bool
+    where
+        E: PartialEq,
+    {
+        graph.contains_vertex(self.start())
+            && self
+                .iter_edges()
+                .all(|(from, to, weight)| graph.edge_weight(from, to) == Some(weight))
+    }
+
+    pub fn into_vertices(self) -> Vec<VertexId> {
+        self.vertices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn paths() {
+        let mut path = Path::new(1);
+        assert_eq!(path.cost(), 0);
+        assert_eq!((path.start(), path.end(), path.edge_count()), (1, 1, 0));
+        path.push(2, 5);
+        path.push(3, 2);
+        assert_eq!(path.cost(), 7);
+        assert_eq!(
+            path.iter_edges().collect::<Vec<_>>(),
+            [(1, 2, &5), (2, 3, &2)]
+        );
+
+        let tail = Path::from_parts(vec![3, 1], vec![4]).unwrap();
+        assert_eq!(Path::from_parts(vec![3, 1], vec![4, 4]), None);
+        assert_eq!(Path::<u32>::from_parts(vec![], vec![]), None);
+        assert_eq!(tail.clone().concat(tail.clone()), None);
+        let cycle = path.clone().concat(tail).unwrap();
+        assert_eq!(cycle.vertices(), [1, 2, 3, 1]);
+        assert_eq!(cycle.weights(), [5, 2, 4]);
+
+        let mut g = Graph::<(), u32>::new();
+        for v in 1..=3 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), 5);
+        g.insert_edge(OrientedEdge(2, 3), 2);
+        assert!(path.is_valid_in(&g));
+        assert!(!cycle.is_valid_in(&g));
+        g.insert_edge(OrientedEdge(3, 1), 3);
+        assert!(!cycle.is_valid_in(&g));
+        assert!(!Path::<u32>::new(7).is_valid_in(&g));
+        assert!(path.is_valid_in(&g.freeze()));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::traits::{EdgeWeights, GraphBase, Neighbors};
+use crate::{Graph, OrientedEdge, VertexId};
+use imbl::{HashMap, HashSet};
+use std::collections::VecDeque;
+
+// immutable graph over hash array mapped tries: every mutation returns a new version that
+// shares all untouched nodes with the old one, so keeping old versions around costs
+// roughly the size of the changes and cloning is O(1)
+#[derive(Debug, Clone)]
+pub struct PersistentGraph<V, E> {
+    adj_list: HashMap<VertexId, HashMap<VertexId, E>>,
+    vertices: HashMap<VertexId, V>,
+    incoming: HashMap<VertexId, HashSet<VertexId>>,
+    edge_count: usize,
+}
+
+impl<V: Clone + PartialEq, E: Clone + PartialEq> PartialEq for PersistentGraph<V, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices == other.vertices && self.adj_list == other.adj_list
+    }
+}
+
+impl<V: Clone, E: Clone> Default for PersistentGraph<V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone, E: Clone> PersistentGraph<V, E> {
+    pub fn new() -> PersistentGraph<V, E> {
+        PersistentGraph {
+            adj_list: HashMap::new(),
+            vertices: HashMap::new(),
+            incoming: HashMap::new(),
+            edge_count: 0,
+        }
+    }
+
+    pub fn insert_node(&self, vertex_id: VertexId, value: V) -> PersistentGraph<V, E> {
+        let mut next = self.clone();
+        next.adj_list.entry(vertex_id).or_default();
+        next.incoming.entry(vertex_id).or_default();
+        next.vertices.insert(vertex_id, value);
+        next
+    }
+
+    pub fn remove_node(&self, vertex_id: VertexId) -> PersistentGraph<V, E> {
+        let mut next = self.clone();
+        let Some(adjacency) = next.adj_list.remove(&vertex_id) else {
+            return next;
+        };
+        next.vertices.remove(&vertex_id);
+        let predecessors = next.incoming.remove(&vertex_id).unwrap();
+        // only `next` loses the vertex, `self` keeps every map; with the vertex's own entries
+        // gone a self-loop is found through the predecessors only
+        for predecessor in &predecessors {
+            if let Some(map) = next.adj_list.get_mut(predecessor) {
+                map.remove(&vertex_id);
+            }
+            next.edge_count -= 1;
+        }
+        for adjacent in adjacency.keys() {
+            if let Some(predecessors) = next.incoming.get_mut(adjacent) {
+                predecessors.remove(&vertex_id);
+                next.edge_count -= 1;
+            }
+        }
+        next
+    }
+
+    // an edge to or from a missing vertex gives back a version equal to this one
+    pub fn insert_edge(&self, edge: OrientedEdge, value: E) -> PersistentGraph<V, E> {
+        let mut next = self.clone();
+        if !next.vertices.contains_key(&edge.1) {
+            return next;
+        }
+        let Some(adjacency) = next.adj_list.get_mut(&edge.0) else {
+            return next;
+        };
+        if adjacency.insert(edge.1, value).is_none() {
+            next.incoming.get_mut(&edge.1).unwrap().insert(edge.0);
+            next.edge_count += 1;
+        }
+        next
+    }
+
+    pub fn remove_edge(&self, edge: OrientedEdge) -> PersistentGraph<V, E> {
+        let mut next = self.clone();
+        let removed = next
+            .adj_list
+            .get_mut(&edge.0)
+            .and_then(|adjacency| adjacency.remove(&edge.1));
+        if removed.is_some() {
+            next.incoming.get_mut(&edge.1).unwrap().remove(&edge.0);
+            next.edge_count -= 1;
+        }
+        next
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn get_vertex_value(&self, vertex: VertexId) -> Option<&V> {
+        self.vertices.get(&vertex)
+    }
+
+    pub fn get_edge_value(&self, from: VertexId, to: VertexId) -> Option<&E> {
+        self.adj_list.get(&from)?.get(&to)
+    }
+
+    pub fn get_adjacents(&self, vertex: VertexId) -> Option<Vec<&VertexId>> {
+        Some(self.adj_list.get(&vertex)?.keys().collect())
+    }
+
+    pub fn get_predecessors(&self, vertex: VertexId) -> Option<Vec<&VertexId>> {
+        Some(self.incoming.get(&vertex)?.iter().collect())
+    }
+
+    pub fn traverse_bfs(&self) -> Vec<VertexId> {
+        let mut traverse = Vec::with_capacity(self.vertices.len());
+        let mut queue = VecDeque::new();
+        let mut used = crate::hash::HashSet::default();
+        for start in self.vertices.keys() {
+            if !used.insert(*start) {
+                continue;
+            }
+            queue.push_back(*start);
+            while let Some(current) = queue.pop_front() {
+                traverse.push(current);
+                for adjacent in self.adj_list[&current].keys() {
+                    if used.insert(*adjacent) {
+                        queue.push_back(*adjacent);
+                    }
+                }
+            }
+        }
+        traverse
+    }
+}
+
+impl<V: Clone, E: Clone> From<&Graph<V, E>> for PersistentGraph<V, E> {
+    fn from(graph: &Graph<V, E>) -> Self {
+        let adj_list = graph
+            .adj_list
+            .iter()
+            .map(|(from, adjacency)| {
+                let map = adjacency.iter().map(|(to, e)| (*to, e.clone())).collect();
+                (*from, map)
+            })
+            .collect();
+        let incoming = graph
+            .incoming
+            .iter()
+            .map(|(to, predecessors)| (*to, predecessors.iter().copied().collect()))
+            .collect();
+        PersistentGraph {
+            adj_list,
+            vertices: graph
+                .vertices
+                .iter()
+                .map(|(id, v)| (*id, v.clone()))
+                .collect(),
+            incoming,
+            edge_count: graph.edge_count,
+        }
+    }
+}
+
+impl<V: Clone, E: Clone> From<&PersistentGraph<V, E>> for Graph<V, E> {
+    fn from(persistent: &PersistentGraph<V, E>) -> Self {
+        let mut graph = Graph::new();
+        for (id, value) in &persistent.vertices {
+            graph.insert_node(*id, value.clone());
+        }
+        for (from, adjacency) in &persistent.adj_list {
+            for (to, value) in adjacency {
+                graph.insert_edge(OrientedEdge(*from, *to), value.clone());
+            }
+        }
+        graph
+    }
+}
+
+impl<V: Clone, E: Clone> GraphBase for PersistentGraph<V, E> {
+    fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn contains_vertex(&self, vertex: VertexId) -> bool {
+        self.vertices.contains_key(&vertex)
+    }
+
+    fn vertex_ids(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.vertices.keys().copied()
+    }
+}
+
+impl<V: Clone, E: Clone> Neighbors for PersistentGraph<V, E> {
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        self.adj_list
+            .get(&vertex)
+            .into_iter()
+            .flat_map(|a| a.keys().copied())
+    }
+}
+
+impl<V: Clone, E: Clone> EdgeWeights for PersistentGraph<V, E> {
+    type Weight = E;
+
+    fn edges(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &E)> + '_ {
+        self.adj_list
+            .get(&vertex)
+            .into_iter()
+            .flat_map(|a| a.iter().map(|(to, value)| (*to, value)))
+    }
+
+    fn edge_weight(&self, from: VertexId, to: VertexId) -> Option<&E> {
+        self.adj_list.get(&from)?.get(&to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visit::dijkstra_visit;
+    use crate::*;
+
+    struct Ignore;
+    impl Visitor<u32> for Ignore {}
+
+    #[test]
+    fn versions() {
+        let empty = PersistentGraph::<&str, u32>::new();
+        let v1 = empty
+            .insert_node(1, "a")
+            .insert_node(2, "b")
+            .insert_node(3, "c");
+        let v2 = v1
+            .insert_edge(OrientedEdge(1, 2), 4)
+            .insert_edge(OrientedEdge(2, 3), 5)
+            .insert_edge(OrientedEdge(3, 3), 6)
+            .insert_edge(OrientedEdge(1, 9), 0);
+        let v3 = v2.remove_node(3);
+        let v4 = v3.remove_edge(OrientedEdge(1, 2)).insert_node(1, "first");
+
+        assert_eq!((empty.vertex_count(), empty.edge_count()), (0, 0));
+        assert_eq!((v1.vertex_count(), v1.edge_count()), (3, 0));
+        assert_eq!((v2.vertex_count(), v2.edge_count()), (3, 3));
+        assert_eq!((v3.vertex_count(), v3.edge_count()), (2, 1));
+        assert_eq!((v4.vertex_count(), v4.edge_count()), (2, 0));
+        assert_eq!(v2.get_edge_value(3, 3), Some(&6));
+        assert_eq!(v3.get_adjacents(2).unwrap(), Vec::<&VertexId>::new());
+        assert_eq!(v3.get_vertex_value(1), Some(&"a"));
+        assert_eq!(v4.get_vertex_value(1), Some(&"first"));
+        assert_eq!(v2.remove_edge(OrientedEdge(2, 1)), v2);
+        assert_ne!(v2, v3);
+
+        let distances = dijkstra_visit(&v2, 1, &mut Ignore);
+        assert_eq!(distances[&3], 9);
+        assert!(!dijkstra_visit(&v3, 1, &mut Ignore).contains_key(&3));
+    }
+
+    #[test]
+    fn conversions() {
+        let mut g = Graph::<u32, u32>::new();
+        for v in 0..10 {
+            g.insert_node(v, v);
+        }
+        for v in 0..9 {
+            g.insert_edge(OrientedEdge(v, v + 1), v);
+        }
+        let persistent = PersistentGraph::from(&g);
+        assert_eq!(persistent.get_predecessors(5).unwrap(), vec![&4]);
+        let changed = persistent.remove_node(5);
+        assert_eq!(Graph::from(&persistent), g);
+        g.remove_node(5);
+        assert_eq!(Graph::from(&changed), g);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::adjacency::Adjacency;
+use crate::hash::{HashMap, HashSet};
+use crate::{GenericError, Graph, GraphEvent, VertexId};
+
+impl<V, E> Graph<V, E> {
+    // renames vertices through the mapping, vertices it doesn't mention keep their id; fails
+    // without touching the graph when two vertices would end up with the same id. Edges,
+    // attributes and tracked changes follow the vertices; observers see every moved vertex
+    // and the edges touching it removed under the old ids and inserted under the new ones
+    pub fn relabel(&mut self, mapping: &HashMap<VertexId, VertexId>) -> Result<(), GenericError> {
+        let new_id = |v: VertexId| mapping.get(&v).copied().unwrap_or(v);
+        let mut taken = HashSet::default();
+        for v in self.vertices.keys() {
+            if !taken.insert(new_id(*v)) {
+                return Err(format!("more than one vertex relabeled to {}", new_id(*v)).into());
+            }
+        }
+        let moved: HashSet<VertexId> = self
+            .vertices
+            .keys()
+            .copied()
+            .filter(|v| new_id(*v) != *v)
+            .collect();
+        if moved.is_empty() {
+            return Ok(());
+        }
+        // the edges touching a moved vertex, under their old ids
+        let mut moved_edges: Vec<(VertexId, VertexId)> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, adjacency)| adjacency.keys().map(move |to| (*from, *to)))
+            .filter(|(from, to)| moved.contains(from) || moved.contains(to))
+            .collect();
+        moved_edges.sort_unstable();
+        let mut moved_vertices: Vec<VertexId> = moved.iter().copied().collect();
+        moved_vertices.sort_unstable();
+        self.notify_removed(&moved_vertices, &moved_edges);
+
+        if let Some(changes) = &mut self.changes {
+            for v in &moved {
+                changes.vertices.insert(*v);
+                changes.vertices.insert(new_id(*v));
+            }
+            for (from, adjacency) in &self.adj_list {
+                for to in adjacency.keys() {
+                    if moved.contains(from) || moved.contains(to) {
+                        changes.edges.insert((*from, *to));
+                        changes.edges.insert((new_id(*from), new_id(*to)));
+                    }
+                }
+            }
+        }
+
+        self.node_index.invalidate();
+        self.vertices = std::mem::take(&mut self.vertices)
+            .into_iter()
+            .map(|(v, value)| (new_id(v), value))
+            .collect();
+        self.adj_list = std::mem::take(&mut self.adj_list)
+            .into_iter()
+            .map(|(v, adjacency)| {
+                let mut renamed = adjacency;
+                if renamed.keys().any(|to| moved.contains(to)) {
+                    let mut rebuilt = Adjacency::default();
+                    for (to, value) in renamed {
+                        rebuilt.insert(new_id(to), value);
+                    }
+                    renamed = rebuilt;
+                }
+                (new_id(v), renamed)
+            })
+            .collect();
+        self.incoming = std::mem::take(&mut self.incoming)
+            .into_iter()
+            .map(|(v, predecessors)| (new_id(v), predecessors.into_iter().map(new_id).collect()))
+            .collect();
+        let attributes = &mut self.attributes;
+        attributes.nodes = std::mem::take(&mut attributes.nodes)
+            .into_iter()
+            .map(|(v, attrs)| (new_id(v), attrs))
+            .collect();
+        attributes.edges = std::mem::take(&mut attributes.edges)
+            .into_iter()
+            .map(|((from, to), attrs)| ((new_id(from), new_id(to)), attrs))
+            .collect();
+        if let Some(connectivity) = &mut self.connectivity {
+            connectivity.invalidate();
+        }
+        let moved_vertices: Vec<VertexId> = moved_vertices.into_iter().map(new_id).collect();
+        let moved_edges: Vec<(VertexId, VertexId)> = moved_edges
+            .into_iter()
+            .map(|(from, to)| (new_id(from), new_id(to)))
+            .collect();
+        self.notify_inserted(&moved_vertices, &moved_edges);
+        Ok(())
+    }
+
+    // like remove_node, the edges go before their vertices
+    fn notify_removed(&mut self, vertices: &[VertexId], edges: &[(VertexId, VertexId)]) {
+        if self.observers.is_empty() {
+            return;
+        }
+        for (from, to) in edges {
+            let value = &self.adj_list[from][to];
+            self.observers.notify(GraphEvent::EdgeRemoved {
+                from: *from,
+                to: *to,
+                value,
+            });
+        }
+        for id in vertices {
+            let value = &self.vertices[id];
+            self.observers
+                .notify(GraphEvent::NodeRemoved { id: *id, value });
+        }
+    }
+
+    // each vertex and edge is followed by its attributes
+    fn notify_inserted(&mut self, vertices: &[VertexId], edges: &[(VertexId, VertexId)]) {
+        if self.observers.is_empty() {
+            return;
+        }
+        for id in vertices {
+            let value = &self.vertices[id];
+            self.observers
+                .notify(GraphEvent::NodeInserted { id: *id, value });
+            for (key, value) in self.attributes.nodes.get(id).into_iter().flatten() {
+                self.observers.notify(GraphEvent::NodeAttrChanged {
+                    id: *id,
+                    key,
+                    value: Some(value),
+                });
+            }
+        }
+        for (from, to) in edges {
+            let value = &self.adj_list[from][to];
+            self.observers.notify(GraphEvent::EdgeInserted {
+                from: *from,
+                to: *to,
+                value,
+            });
+            for (key, value) in self
+                .attributes
+                .edges
+                .get(&(*from, *to))
+                .into_iter()
+                .flatten()
+            {
+                self.observers.notify(GraphEvent::EdgeAttrChanged {
+                    from: *from,
+                    to: *to,
+                    key,
+                    value: Some(value),
+                });
+            }
+        }
+    }
+
+    // renumbers the vertices to 0..n in the order of their current ids and returns the old
+    // to new mapping, for CSR and matrix code that wants dense ids
+    pub fn compact(&mut self) -> HashMap<VertexId, VertexId> {
+        let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let mapping: HashMap<VertexId, VertexId> = ids
+            .into_iter()
+            .enumerate()
+            .map(|(new, old)| (old, new as VertexId))
+            .collect();
+        // one new id per vertex, so there is nothing to collide
+        self.relabel(&mapping).unwrap();
+        mapping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::HashMap;
+    use crate::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn relabel() {
+        let mut g = Graph::<char, u32>::new();
+        g.insert_node(1, 'a');
+        g.insert_node(2, 'b');
+        g.insert_node(3, 'c');
+        g.insert_edge(OrientedEdge(1, 2), 12);
+        g.insert_edge(OrientedEdge(2, 3), 23);
+        g.insert_edge(OrientedEdge(3, 3), 33);
+        g.set_edge_attr(OrientedEdge(1, 2), "kind", "road");
+        assert_eq!(g.component_count(), 1);
+
+        let clash: HashMap<VertexId, VertexId> = [(1, 2)].into_iter().collect();
+        assert!(g.relabel(&clash).is_err());
+        assert_eq!(g.get_vertex_value(1), Some(&'a'));
+
+        // a swap
+        let swap: HashMap<VertexId, VertexId> = [(1, 3), (3, 1)].into_iter().collect();
+        g.relabel(&swap).unwrap();
+        assert_eq!(g.get_vertex_value(3), Some(&'a'));
+        assert_eq!(g.edge_weight(3, 2), Some(&12));
+        assert_eq!(g.edge_weight(2, 1), Some(&23));
+        assert_eq!(g.edge_weight(1, 1), Some(&33));
+        assert_eq!(g.in_degree(1), Some(2));
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(
+            g.get_edge_attr(OrientedEdge(3, 2), "kind"),
+            Some(&AttrValue::from("road"))
+        );
+        g.remove_node(2);
+        assert_eq!(g.component_count(), 2);
+        assert_eq!(g.edge_count(), 1);
+    }
+
+    #[test]
+    fn relabel_notifies_observers() {
+        let mut g = Graph::<char, u32>::new();
+        g.insert_node(1, 'a');
+        g.insert_node(2, 'b');
+        g.insert_node(5, 'c');
+        g.insert_edge(OrientedEdge(1, 2), 12);
+        g.insert_edge(OrientedEdge(2, 5), 25);
+        g.set_node_attr(5, "color", "red");
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        g.on_change(move |event| {
+            sink.lock().unwrap().push(match event {
+                GraphEvent::NodeInserted { id, value } => format!("+{}={}", id, value),
+                GraphEvent::NodeRemoved { id, value } => format!("-{}={}", id, value),
+                GraphEvent::EdgeInserted { from, to, value } => {
+                    format!("+{}>{}={}", from, to, value)
+                }
+                GraphEvent::EdgeRemoved { from, to, value } => {
+                    format!("-{}>{}={}", from, to, value)
+                }
+                GraphEvent::NodeAttrChanged { id, key, .. } => format!("{}.{}", id, key),
+                GraphEvent::EdgeAttrChanged { from, to, key, .. } => {
+                    format!("{}>{}.{}", from, to, key)
+                }
+            })
+        });
+        let mapping: HashMap<VertexId, VertexId> = [(5, 7)].into_iter().collect();
+        g.relabel(&mapping).unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["-2>5=25", "-5=c", "+7=c", "7.color", "+2>7=25"]
+        );
+    }
+
+    #[test]
+    fn compact() {
+        let mut g = Graph::<(), ()>::new();
+        for v in [900, 7, 40_000] {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(40_000, 7), ());
+        g.insert_edge(OrientedEdge(7, 900), ());
+
+        let mapping = g.compact();
+        assert_eq!(mapping[&7], 0);
+        assert_eq!(mapping[&900], 1);
+        assert_eq!(mapping[&40_000], 2);
+        let mut expected = Graph::new();
+        for v in 0..3 {
+            expected.insert_node(v, ());
+        }
+        expected.insert_edge(OrientedEdge(2, 0), ());
+        expected.insert_edge(OrientedEdge(0, 1), ());
+        assert_eq!(g, expected);
+        assert_eq!(g.in_degree(0), Some(1));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::hash::HashSet;
+use crate::{Graph, VertexId};
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::Rng;
+
+// the probability that a random walk jumps back to where it started
+const RESTART: f64 = 0.15;
+
+// draws uniformly among the ids that aren't taken yet, by rejection while at least half of
+// the candidates are free; once fewer are, the taken ones are filtered out, which at least
+// halves the candidates, so a draw stays O(1) amortized however many ids get taken
+struct Seeds {
+    candidates: Vec<VertexId>,
+    total: usize,
+}
+
+impl Seeds {
+    fn new(ids: Vec<VertexId>) -> Seeds {
+        let total = ids.len();
+        Seeds {
+            candidates: ids,
+            total,
+        }
+    }
+
+    // `taken` must only hold ids the seeds were built from
+    fn draw<R: Rng + ?Sized>(&mut self, taken: &HashSet<VertexId>, rng: &mut R) -> VertexId {
+        let free = self.total - taken.len();
+        if free * 2 < self.candidates.len() {
+            self.candidates.retain(|v| !taken.contains(v));
+        }
+        loop {
+            let v = *self.candidates.choose(rng).unwrap();
+            if !taken.contains(&v) {
+                return v;
+            }
+        }
+    }
+}
+
+// ids are sorted before anything is drawn, so that a seeded rng gives the same sample
+// whatever order the hash maps iterate in
+impl<V: Clone, E: Clone> Graph<V, E> {
+    fn sorted_ids(&self) -> Vec<VertexId> {
+        let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    // k distinct vertices drawn uniformly, all of them when there aren't more than k
+    pub fn sample_nodes<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Vec<VertexId> {
+        self.sorted_ids().into_iter().choose_multiple(rng, k)
+    }
+
+    // k distinct edges drawn uniformly
+    pub fn sample_edges<R: Rng + ?Sized>(
+        &self,
+        k: usize,
+        rng: &mut R,
+    ) -> Vec<(VertexId, VertexId)> {
+        let mut edges: Vec<(VertexId, VertexId)> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, adjacency)| adjacency.keys().map(move |to| (*from, *to)))
+            .collect();
+        edges.sort_unstable();
+        edges.into_iter().choose_multiple(rng, k)
+    }
+
+    // the subgraph induced by k uniformly drawn vertices
+    pub fn sample_induced<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Graph<V, E> {
+        self.induced_subgraph(self.sample_nodes(k, rng))
+    }
+
+    // random walk with restarts along the edges in either direction until k vertices are
+    // visited; a walk that stops finding new vertices, e.g. in a component smaller than the
+    // sample, continues from a fresh random start
+    pub fn sample_random_walk<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Graph<V, E> {
+        let k = k.min(self.vertices.len());
+        // k is capped by the vertex count, so this covers the empty graph as well
+        if k == 0 {
+            return Graph::new();
+        }
+        let mut seeds = Seeds::new(self.sorted_ids());
+        let mut visited: HashSet<VertexId> = HashSet::default();
+        let mut start = seeds.draw(&visited, rng);
+        let mut current = start;
+        visited.insert(start);
+        let mut stale = 0;
+        while visited.len() < k {
+            let neighbors = self.undirected_neighbors(current);
+            current = match neighbors.choose(rng) {
+                Some(next) if !rng.gen_bool(RESTART) => *next,
+                _ => start,
+            };
+            if visited.insert(current) {
+                stale = 0;
+            } else {
+                stale += 1;
+            }
+            // a hundred steps per vertex still missing is plenty for a walk that can grow
+            if stale > 100 * (k - visited.len()) {
+                start = seeds.draw(&visited, rng);
+                current = start;
+                visited.insert(start);
+                stale = 0;
+            }
+        }
+        self.induced_subgraph(visited)
+    }
+
+    // forest fire sampling (Leskovec and Faloutsos): from a random unburnt seed each burning
+    // vertex spreads to a geometrically distributed number of its unburnt neighbors, in
+    // either direction, with mean p / (1 - p); new seeds are drawn until k vertices burnt
+    pub fn sample_forest_fire<R: Rng + ?Sized>(
+        &self,
+        k: usize,
+        p: f64,
+        rng: &mut R,
+    ) -> Graph<V, E> {
+        let k = k.min(self.vertices.len());
+        let p = p.clamp(0.0, 0.99);
+        let mut seeds = Seeds::new(self.sorted_ids());
+        let mut burnt: HashSet<VertexId> = HashSet::default();
+        while burnt.len() < k {
+            let seed = seeds.draw(&burnt, rng);
+            burnt.insert(seed);
+            let mut front = vec![seed];
+            while let Some(current) = front.pop() {
+                if burnt.len() >= k {
+                    break;
+                }
+                let mut unburnt: Vec<VertexId> = self
+                    .undirected_neighbors(current)
+                    .into_iter()
+                    .filter(|v| !burnt.contains(v))
+                    .collect();
+                let mut spread = 0;
+                while rng.gen_bool(p) {
+                    spread += 1;
+                }
+                unburnt.shuffle(rng);
+                for next in unburnt.into_iter().take(spread.min(k - burnt.len())) {
+                    burnt.insert(next);
+                    front.push(next);
+                }
+            }
+        }
+        self.induced_subgraph(burnt)
+    }
+
+    // successors and predecessors, sorted and without duplicates
+    fn undirected_neighbors(&self, vertex: VertexId) -> Vec<VertexId> {
+        let mut neighbors: Vec<VertexId> = self.adj_list[&vertex]
+            .keys()
+            .chain(&self.incoming[&vertex])
+            .copied()
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::HashSet;
+    use crate::sampling::Seeds;
+    use crate::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn seeds() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut seeds = Seeds::new((0..100).collect());
+        let mut taken: HashSet<VertexId> = HashSet::default();
+        for _ in 0..100 {
+            assert!(taken.insert(seeds.draw(&taken, &mut rng)));
+        }
+        assert!(seeds.candidates.len() < 4);
+
+        // every vertex is a seed of its own, which used to scan all ids per seed
+        let mut isolated = Graph::<(), ()>::new();
+        for v in 0..50_000 {
+            isolated.insert_node(v, ());
+        }
+        let fire = isolated.sample_forest_fire(50_000, 0.0, &mut rng);
+        assert_eq!(fire.vertex_count(), 50_000);
+        let walk = isolated.sample_random_walk(100, &mut rng);
+        assert_eq!(walk.vertex_count(), 100);
+    }
+
+    fn sample() -> Graph<u32, u32> {
+        let mut g = generators::grid::<u32, u32>(10, 10);
+        // a detached pair that walks can't reach from the grid
+        g.insert_node(100, 100);
+        g.insert_node(101, 101);
+        g.insert_edge(OrientedEdge(100, 101), 1);
+        g
+    }
+
+    #[test]
+    fn uniform_samples() {
+        let g = sample();
+        let mut rng = StdRng::seed_from_u64(7);
+        let nodes = g.sample_nodes(10, &mut rng);
+        assert_eq!(nodes.len(), 10);
+        assert!(nodes.iter().all(|v| g.contains_vertex(*v)));
+        assert_eq!(g.sample_nodes(500, &mut rng).len(), 102);
+        let edges = g.sample_edges(5, &mut rng);
+        assert_eq!(edges.len(), 5);
+        assert!(edges
+            .iter()
+            .all(|(from, to)| g.edge_weight(*from, *to).is_some()));
+        let induced = g.sample_induced(30, &mut rng);
+        assert_eq!(induced.vertex_count(), 30);
+
+        let again = g.sample_nodes(10, &mut StdRng::seed_from_u64(7));
+        assert_eq!(again, nodes);
+    }
+
+    #[test]
+    fn structural_samples() {
+        let g = sample();
+        let mut rng = StdRng::seed_from_u64(3);
+        let walk = g.sample_random_walk(20, &mut rng);
+        assert_eq!(walk.vertex_count(), 20);
+        // a walk stays connected unless it had to restart somewhere else
+        assert!(walk.edge_count() >= 18);
+
+        let fire = g.sample_forest_fire(25, 0.7, &mut rng);
+        assert_eq!(fire.vertex_count(), 25);
+        assert!(fire.edge_count() > 0);
+
+        // more than the grid holds, so the detached pair has to be picked up as well
+        assert_eq!(g.sample_random_walk(102, &mut rng).vertex_count(), 102);
+        assert_eq!(g.sample_forest_fire(102, 0.2, &mut rng), g);
+        assert_eq!(g.sample_random_walk(0, &mut rng).vertex_count(), 0);
+        assert_eq!(g.sample_forest_fire(0, 0.7, &mut rng).vertex_count(), 0);
+        assert_eq!(
+            Graph::<(), ()>::new()
+                .sample_random_walk(3, &mut rng)
+                .vertex_count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::changes::ChangeSet;
+use crate::{GenericError, Graph, OrientedEdge, VertexId};
+use rusqlite::{params, Connection};
+use std::fmt::Display;
+use std::str::FromStr;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS vertices (id INTEGER PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS edges (
+        source INTEGER NOT NULL,
+        target INTEGER NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (source, target)
+    );";
+
+fn parse_value<T: FromStr>(text: &str, what: impl Display) -> Result<T, GenericError>
+where
+    <T as FromStr>::Err: Display,
+{
+    Ok(text
+        .parse::<T>()
+        .map_err(|e| format!("{}: invalid value `{}`: {}", what, text, e))?)
+}
+
+// the graph lives in a `vertices` and an `edges` table with the payloads stored as text;
+// after a save or a load the graph records what it changes so that sync_sqlite only
+// rewrites those rows
+impl<V: Display + FromStr, E: Display + FromStr> Graph<V, E>
+where
+    <V as FromStr>::Err: Display,
+    <E as FromStr>::Err: Display,
+{
+    pub fn save_to_sqlite(&mut self, conn: &mut Connection) -> Result<(), GenericError> {
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        tx.execute("DELETE FROM edges", [])?;
+        tx.execute("DELETE FROM vertices", [])?;
+        {
+            let mut insert_vertex = tx.prepare("INSERT INTO vertices VALUES (?1, ?2)")?;
+            for (id, value) in &self.vertices {
+                insert_vertex.execute(params![id, value.to_string()])?;
+            }
+            let mut insert_edge = tx.prepare("INSERT INTO edges VALUES (?1, ?2, ?3)")?;
+            for (from, adjacency) in &self.adj_list {
+                for (to, value) in adjacency {
+                    insert_edge.execute(params![from, to, value.to_string()])?;
+                }
+            }
+        }
+        tx.commit()?;
+        self.changes = Some(ChangeSet::default());
+        Ok(())
+    }
+
+    pub fn load_from_sqlite(conn: &Connection) -> Result<Graph<V, E>, GenericError> {
+        let mut g = Graph::new();
+        let mut vertices = conn.prepare("SELECT id, value FROM vertices")?;
+        let mut rows = vertices.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: VertexId = row.get(0)?;
+            let value: String = row.get(1)?;
+            g.insert_node(id, parse_value(&value, format_args!("vertex {}", id))?);
+        }
+        let mut edges = conn.prepare("SELECT source, target, value FROM edges")?;
+        let mut rows = edges.query([])?;
+        while let Some(row) = rows.next()? {
+            let (from, to): (VertexId, VertexId) = (row.get(0)?, row.get(1)?);
+            let value: String = row.get(2)?;
+            let what = format!("edge {} -> {}", from, to);
+            if g.get_vertex_value(from).is_none() || g.get_vertex_value(to).is_none() {
+                return Err(format!("{} uses an unknown vertex", what).into());
+            }
+            g.insert_edge(OrientedEdge(from, to), parse_value(&value, what)?);
+        }
+        g.changes = Some(ChangeSet::default());
+        Ok(g)
+    }
+
+    // writes the rows changed since the last save, load or sync; a graph that was never
+    // saved is written in full
+    pub fn sync_sqlite(&mut self, conn: &mut Connection) -> Result<(), GenericError> {
+        let Some(changes) = self.changes.take() else {
+            return self.save_to_sqlite(conn);
+        };
+        let result = self.write_changes(conn, &changes);
+        // on failure the changes are kept for the next attempt
+        self.changes = Some(if result.is_ok() {
+            ChangeSet::default()
+        } else {
+            changes
+        });
+        result
+    }
+
+    fn write_changes(
+        &self,
+        conn: &mut Connection,
+        changes: &ChangeSet,
+    ) -> Result<(), GenericError> {
+        let tx = conn.transaction()?;
+        {
+            let mut delete_vertex = tx.prepare("DELETE FROM vertices WHERE id = ?1")?;
+            let mut upsert_vertex =
+                tx.prepare("INSERT OR REPLACE INTO vertices VALUES (?1, ?2)")?;
+            for id in &changes.vertices {
+                match self.vertices.get(id) {
+                    Some(value) => {
+                        upsert_vertex.execute(params![id, value.to_string()])?;
+                    }
+                    None => {
+                        delete_vertex.execute(params![id])?;
+                    }
+                }
+            }
+            let mut delete_edge =
+                tx.prepare("DELETE FROM edges WHERE source = ?1 AND target = ?2")?;
+            let mut upsert_edge = tx.prepare("INSERT OR REPLACE INTO edges VALUES (?1, ?2, ?3)")?;
+            for (from, to) in &changes.edges {
+                match self.adj_list.get(from).and_then(|a| a.get(to)) {
+                    Some(value) => {
+                        upsert_edge.execute(params![from, to, value.to_string()])?;
+                    }
+                    None => {
+                        delete_edge.execute(params![from, to])?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rusqlite::Connection;
+
+    fn row_count(conn: &Connection, table: &str) -> usize {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn save_load_and_sync() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let mut g = Graph::<String, u32>::new();
+        for v in 1..=3 {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(OrientedEdge(1, 2), 12);
+        g.insert_edge(OrientedEdge(2, 3), 23);
+        g.save_to_sqlite(&mut conn).unwrap();
+        assert_eq!(Graph::load_from_sqlite(&conn).unwrap(), g);
+
+        // rows that aren't touched by the sync keep whatever the database has
+        conn.execute("UPDATE vertices SET value = 'external' WHERE id = 1", [])
+            .unwrap();
+        g.insert_node(4, "v4".to_string());
+        g.insert_edge(OrientedEdge(3, 4), 34);
+        g.insert_edge(OrientedEdge(1, 2), 21);
+        g.remove_node(2);
+        g.insert_node(2, "back".to_string());
+        g.sync_sqlite(&mut conn).unwrap();
+        assert_eq!(row_count(&conn, "vertices"), 4);
+        assert_eq!(row_count(&conn, "edges"), 1);
+
+        let loaded = Graph::<String, u32>::load_from_sqlite(&conn).unwrap();
+        assert_eq!(loaded.get_vertex_value(1).unwrap(), "external");
+        assert_eq!(loaded.get_adjacents(3).unwrap(), vec![&4]);
+        assert_eq!(loaded.get_vertex_value(2).unwrap(), "back");
+        assert_eq!(loaded.in_degree(2), Some(0));
+
+        conn.execute("INSERT INTO edges VALUES (9, 1, '0')", [])
+            .unwrap();
+        let error = Graph::<String, u32>::load_from_sqlite(&conn).err().unwrap();
+        assert_eq!(error.to_string(), "edge 9 -> 1 uses an unknown vertex");
+    }
+}
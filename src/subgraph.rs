@@ -0,0 +1,160 @@
+use crate::hash::HashSet;
+use crate::traits::{EdgeWeights, GraphBase, Neighbors, NodeIndex, NodeIndexable};
+use crate::{Graph, OrientedEdge, VertexId};
+
+// borrowed view of the subgraph induced by a set of vertices: only edges with both ends in
+// the set are visible; ids that aren't in the underlying graph are left out
+pub struct Subgraph<'a, G> {
+    graph: &'a G,
+    vertices: HashSet<VertexId>,
+    node_index: NodeIndex,
+}
+
+impl<'a, G: GraphBase> Subgraph<'a, G> {
+    pub fn induced(graph: &'a G, vertices: impl IntoIterator<Item = VertexId>) -> Self {
+        let vertices = vertices
+            .into_iter()
+            .filter(|v| graph.contains_vertex(*v))
+            .collect();
+        Subgraph {
+            graph,
+            vertices,
+            node_index: NodeIndex::default(),
+        }
+    }
+}
+
+impl<G: Neighbors> GraphBase for Subgraph<'_, G> {
+    fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    // counted on every call
+    fn edge_count(&self) -> usize {
+        self.vertices
+            .iter()
+            .map(|v| self.neighbors(*v).count())
+            .sum()
+    }
+
+    fn contains_vertex(&self, vertex: VertexId) -> bool {
+        self.vertices.contains(&vertex)
+    }
+
+    fn vertex_ids(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.vertices.iter().copied()
+    }
+}
+
+impl<G: Neighbors> Neighbors for Subgraph<'_, G> {
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        let visible = self.vertices.contains(&vertex);
+        self.graph
+            .neighbors(vertex)
+            .filter(move |to| visible && self.vertices.contains(to))
+    }
+}
+
+impl<G: EdgeWeights> EdgeWeights for Subgraph<'_, G> {
+    type Weight = G::Weight;
+
+    fn edges(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &G::Weight)> + '_ {
+        let visible = self.vertices.contains(&vertex);
+        self.graph
+            .edges(vertex)
+            .filter(move |(to, _)| visible && self.vertices.contains(to))
+    }
+
+    fn edge_weight(&self, from: VertexId, to: VertexId) -> Option<&G::Weight> {
+        if self.vertices.contains(&from) && self.vertices.contains(&to) {
+            self.graph.edge_weight(from, to)
+        } else {
+            None
+        }
+    }
+}
+
+// the vertices keep the order they have in the underlying graph
+impl<G: Neighbors + NodeIndexable> Subgraph<'_, G> {
+    fn node_index(&self) -> &[VertexId] {
+        self.node_index.get(|| {
+            let mut ids: Vec<VertexId> = self.vertices.iter().copied().collect();
+            ids.sort_unstable_by_key(|v| self.graph.index_of(*v));
+            ids
+        })
+    }
+}
+
+impl<G: Neighbors + NodeIndexable> NodeIndexable for Subgraph<'_, G> {
+    fn node_bound(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn index_of(&self, vertex: VertexId) -> Option<usize> {
+        if !self.vertices.contains(&vertex) {
+            return None;
+        }
+        let position = self.graph.index_of(vertex);
+        self.node_index()
+            .binary_search_by_key(&position, |v| self.graph.index_of(*v))
+            .ok()
+    }
+
+    fn vertex_at(&self, index: usize) -> VertexId {
+        self.node_index()[index]
+    }
+}
+
+impl<V: Clone, E: Clone> Graph<V, E> {
+    // owned copy of what Subgraph::induced shows
+    pub fn induced_subgraph(&self, vertices: impl IntoIterator<Item = VertexId>) -> Graph<V, E> {
+        let mut subgraph = Graph::new();
+        for v in vertices {
+            if let Some(value) = self.vertices.get(&v) {
+                subgraph.insert_node(v, value.clone());
+            }
+        }
+        let kept: Vec<VertexId> = subgraph.vertices.keys().copied().collect();
+        for from in kept {
+            for (to, value) in self.adj_list[&from].iter() {
+                subgraph.insert_edge(OrientedEdge(from, *to), value.clone());
+            }
+        }
+        subgraph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::traits::{EdgeWeights, GraphBase, Neighbors};
+    use crate::*;
+
+    #[test]
+    fn induced() {
+        let mut g = Graph::<(), u32>::new();
+        for v in 1..=4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), 12);
+        g.insert_edge(OrientedEdge(2, 3), 23);
+        g.insert_edge(OrientedEdge(3, 1), 31);
+        g.insert_edge(OrientedEdge(3, 4), 34);
+
+        let sub = Subgraph::induced(&g, [1, 3, 4, 7]);
+        assert_eq!(sub.vertex_count(), 3);
+        assert_eq!(sub.edge_count(), 2);
+        assert!(!sub.contains_vertex(2));
+        assert_eq!(sub.neighbors(2).count(), 0);
+        assert_eq!(sub.neighbors(1).count(), 0);
+        assert_eq!(sub.edge_weight(3, 1), Some(&31));
+        assert_eq!(sub.edge_weight(1, 2), None);
+
+        let owned = g.induced_subgraph([1, 3, 4, 7]);
+        assert_eq!((owned.vertex_count(), owned.edge_count()), (3, 2));
+        assert_eq!(owned.edge_weight(3, 4), Some(&34));
+
+        let csr = g.freeze();
+        let frozen = Subgraph::induced(&csr, [2, 3]);
+        assert_eq!(frozen.edges(2).collect::<Vec<_>>(), vec![(3, &23)]);
+    }
+}
@@ -0,0 +1,540 @@
+use crate::format::compression::Compression;
+use crate::format::tgf::{write_label, TgfReader, TgfRecord};
+use crate::hash::HashMap;
+use crate::{GenericError, Graph, GraphEvent, OrientedEdge, Path, VertexId};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+pub type Timestamp = i64;
+
+// half-open, [start, end); an interval with end <= start is never active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl Interval {
+    pub fn new(start: Timestamp, end: Timestamp) -> Interval {
+        Interval { start, end }
+    }
+
+    // from `start` on, with no end
+    pub fn since(start: Timestamp) -> Interval {
+        Interval::new(start, Timestamp::MAX)
+    }
+
+    pub fn contains(&self, time: Timestamp) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+// one period during which an edge can be traversed, an edge may have any number of them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact<E> {
+    pub interval: Interval,
+    pub value: E,
+}
+
+// the earliest moment at or after `time` that one of the contacts can be used
+fn departure<E>(contacts: &[Contact<E>], time: Timestamp) -> Option<Timestamp> {
+    contacts
+        .iter()
+        .filter_map(|c| {
+            let departure = c.interval.start.max(time);
+            (departure < c.interval.end).then_some(departure)
+        })
+        .min()
+}
+
+// edges carry validity intervals; paths are time-respecting: an edge is taken at some moment
+// inside one of its intervals no earlier than the arrival at its tail, traversal takes no time
+#[derive(Debug, Clone)]
+pub struct TemporalGraph<V, E> {
+    // the contacts of each edge are sorted by start
+    graph: Graph<V, Vec<Contact<E>>>,
+}
+
+impl<V, E> Default for TemporalGraph<V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, E> TemporalGraph<V, E> {
+    pub fn new() -> TemporalGraph<V, E> {
+        TemporalGraph {
+            graph: Graph::new(),
+        }
+    }
+
+    // every edge with all of its contacts
+    pub fn graph(&self) -> &Graph<V, Vec<Contact<E>>> {
+        &self.graph
+    }
+
+    pub fn insert_node(&mut self, vertex_id: VertexId, value: V) -> Option<V> {
+        self.graph.insert_node(vertex_id, value)
+    }
+
+    pub fn remove_node(&mut self, vertex_id: VertexId) -> Option<V> {
+        self.graph.remove_node(vertex_id)
+    }
+
+    // adds a contact to the edge, false when one of its vertices doesn't exist; the contacts
+    // of an existing edge are extended in place, which keeps its attributes and the
+    // connectivity index and is reported like an updated edge
+    pub fn insert_edge(&mut self, edge: OrientedEdge, interval: Interval, value: E) -> bool {
+        let OrientedEdge(from, to) = edge;
+        if !self.graph.vertices.contains_key(&from) || !self.graph.vertices.contains_key(&to) {
+            return false;
+        }
+        let contact = Contact { interval, value };
+        let Some(contacts) = self.graph.adj_list.get_mut(&from).unwrap().get_mut(&to) else {
+            self.graph.insert_edge(edge, vec![contact]);
+            return true;
+        };
+        let pos = contacts.partition_point(|c| c.interval.start <= interval.start);
+        contacts.insert(pos, contact);
+        if let Some(changes) = &mut self.graph.changes {
+            changes.edges.insert((from, to));
+        }
+        if !self.graph.observers.is_empty() {
+            let value = &self.graph.adj_list[&from][&to];
+            self.graph
+                .observers
+                .notify(GraphEvent::EdgeInserted { from, to, value });
+        }
+        true
+    }
+
+    // removes the edge with all of its contacts
+    pub fn remove_edge(&mut self, edge: OrientedEdge) -> Option<Vec<Contact<E>>> {
+        self.graph.remove_edge(edge)
+    }
+
+    pub fn contacts(&self, from: VertexId, to: VertexId) -> &[Contact<E>] {
+        self.graph
+            .adj_list
+            .get(&from)
+            .and_then(|a| a.get(&to))
+            .map_or(&[], |c| c)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.graph.vertex_count()
+    }
+
+    // edges with at least one contact, not contacts
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn contact_count(&self) -> usize {
+        self.graph
+            .adj_list
+            .values()
+            .flat_map(|a| a.iter().map(|(_, contacts)| contacts.len()))
+            .sum()
+    }
+
+    // the graph as it is at `time`: every vertex and the edges active then, an edge with
+    // overlapping active contacts gets the value of the one that started last
+    pub fn snapshot_at(&self, time: Timestamp) -> Graph<V, E>
+    where
+        V: Clone,
+        E: Clone,
+    {
+        let mut snapshot = Graph::new();
+        for (id, value) in &self.graph.vertices {
+            snapshot.insert_node(*id, value.clone());
+        }
+        for (from, adjacency) in &self.graph.adj_list {
+            for (to, contacts) in adjacency.iter() {
+                if let Some(active) = contacts.iter().rev().find(|c| c.interval.contains(time)) {
+                    snapshot.insert_edge(OrientedEdge(*from, *to), active.value.clone());
+                }
+            }
+        }
+        snapshot
+    }
+
+    // Dijkstra over arrival times, each reached vertex maps to its earliest arrival and the
+    // vertex it was reached from
+    fn foremost(
+        &self,
+        start: VertexId,
+        time: Timestamp,
+        target: Option<VertexId>,
+    ) -> HashMap<VertexId, (Timestamp, VertexId)> {
+        let mut arrivals = HashMap::default();
+        if !self.graph.vertices.contains_key(&start) {
+            return arrivals;
+        }
+        let mut settled = crate::hash::HashSet::default();
+        let mut queue = BinaryHeap::new();
+        arrivals.insert(start, (time, start));
+        queue.push(Reverse((time, start)));
+        while let Some(Reverse((arrival, vertex))) = queue.pop() {
+            if !settled.insert(vertex) {
+                continue;
+            }
+            if Some(vertex) == target {
+                break;
+            }
+            for (next, contacts) in self.graph.adj_list[&vertex].iter() {
+                let Some(departure) = departure(contacts, arrival) else {
+                    continue;
+                };
+                if arrivals.get(next).is_none_or(|(best, _)| departure < *best) {
+                    arrivals.insert(*next, (departure, vertex));
+                    queue.push(Reverse((departure, *next)));
+                }
+            }
+        }
+        arrivals
+    }
+
+    // the earliest time each vertex can be reached leaving `start` at `time` or later
+    pub fn earliest_arrivals(
+        &self,
+        start: VertexId,
+        time: Timestamp,
+    ) -> HashMap<VertexId, Timestamp> {
+        self.foremost(start, time, None)
+            .into_iter()
+            .map(|(v, (arrival, _))| (v, arrival))
+            .collect()
+    }
+
+    // the path that arrives first; its weights are the times each edge is taken, so the
+    // last one is the arrival
+    pub fn foremost_path(
+        &self,
+        from: VertexId,
+        to: VertexId,
+        time: Timestamp,
+    ) -> Option<Path<Timestamp>> {
+        let arrivals = self.foremost(from, time, Some(to));
+        arrivals.get(&to)?;
+        let mut steps = Vec::new();
+        let mut current = to;
+        while current != from {
+            let (arrival, previous) = arrivals[&current];
+            steps.push((current, arrival));
+            current = previous;
+        }
+        Some(timed_path(from, steps))
+    }
+
+    // hop-bounded relaxation: levels[k] holds the vertices whose earliest arrival improves
+    // once paths of k edges are allowed, with that arrival and the previous vertex; a later
+    // arrival over fewer hops can't simply shadow an earlier one over more, so it doesn't
+    // stop at the first visit like a plain BFS
+    fn levels(
+        &self,
+        start: VertexId,
+        time: Timestamp,
+    ) -> Vec<HashMap<VertexId, (Timestamp, VertexId)>> {
+        let mut levels = Vec::new();
+        if !self.graph.vertices.contains_key(&start) {
+            return levels;
+        }
+        let mut best = HashMap::default();
+        best.insert(start, time);
+        let mut level = HashMap::default();
+        level.insert(start, (time, start));
+        while !level.is_empty() {
+            let mut next: HashMap<VertexId, (Timestamp, VertexId)> = HashMap::default();
+            for (vertex, (arrival, _)) in &level {
+                for (adjacent, contacts) in self.graph.adj_list[vertex].iter() {
+                    let Some(departure) = departure(contacts, *arrival) else {
+                        continue;
+                    };
+                    let improves = best.get(adjacent).is_none_or(|b| departure < *b)
+                        && next.get(adjacent).is_none_or(|(n, _)| departure < *n);
+                    if improves {
+                        next.insert(*adjacent, (departure, *vertex));
+                    }
+                }
+            }
+            for (vertex, (arrival, _)) in &next {
+                best.insert(*vertex, *arrival);
+            }
+            levels.push(level);
+            level = next;
+        }
+        levels
+    }
+
+    // time-respecting BFS: the vertices reachable from `start` leaving at `time` or later,
+    // ordered by the fewest edges any such path needs, ties by id
+    pub fn traverse_bfs_at(&self, start: VertexId, time: Timestamp) -> Vec<VertexId> {
+        let mut hops = HashMap::default();
+        for (k, level) in self.levels(start, time).iter().enumerate() {
+            for vertex in level.keys() {
+                hops.entry(*vertex).or_insert(k);
+            }
+        }
+        let mut order: Vec<VertexId> = hops.keys().copied().collect();
+        order.sort_unstable_by_key(|v| (hops[v], *v));
+        order
+    }
+
+    // a time-respecting path with the fewest edges, arriving as early as that allows; the
+    // weights are the times each edge is taken
+    pub fn shortest_path_at(
+        &self,
+        from: VertexId,
+        to: VertexId,
+        time: Timestamp,
+    ) -> Option<Path<Timestamp>> {
+        let levels = self.levels(from, time);
+        let mut k = levels.iter().position(|level| level.contains_key(&to))?;
+        let mut steps = Vec::new();
+        let mut current = to;
+        while k > 0 {
+            let (arrival, previous) = levels[k][&current];
+            steps.push((current, arrival));
+            current = previous;
+            // the arrival that was relaxed from is the last improvement before this level
+            k = (0..k)
+                .rev()
+                .find(|j| levels[*j].contains_key(&current))
+                .unwrap();
+        }
+        Some(timed_path(from, steps))
+    }
+}
+
+// steps are (vertex, time it was reached) walking back from the end
+fn timed_path(start: VertexId, steps: Vec<(VertexId, Timestamp)>) -> Path<Timestamp> {
+    let mut path = Path::new(start);
+    for (vertex, time) in steps.into_iter().rev() {
+        path.push(vertex, time);
+    }
+    path
+}
+
+fn parse_label<T: FromStr + Default>(label: &str) -> Result<T, String>
+where
+    <T as FromStr>::Err: Display,
+{
+    if label.is_empty() {
+        Ok(T::default())
+    } else {
+        label
+            .parse::<T>()
+            .map_err(|e| format!("invalid label `{}`: {}", label, e))
+    }
+}
+
+// TGF with one line per contact, the interval goes in front of the label:
+// `<from> <to> <start> <end> [label]`
+impl<V: Display + FromStr, E: Display + FromStr> TemporalGraph<V, E> {
+    pub fn serialize_to(&self, filename: &str) -> Result<(), GenericError> {
+        Compression::from_extension(filename)
+            .write_file(filename, |encoder| self.serialize_into(encoder))
+    }
+
+    pub fn serialize_into<W: Write>(&self, mut writer: W) -> Result<(), GenericError> {
+        let mut vertices: Vec<_> = self.graph.vertices.iter().collect();
+        vertices.sort_unstable_by_key(|(id, _)| **id);
+        for (id, value) in vertices {
+            write!(writer, "{}", id)?;
+            write_label(&mut writer, value)?;
+            writeln!(writer)?;
+        }
+        writeln!(writer, "#")?;
+        let mut sources: Vec<_> = self.graph.adj_list.iter().collect();
+        sources.sort_unstable_by_key(|(from, _)| **from);
+        for (from, adjacency) in sources {
+            let mut edges: Vec<_> = adjacency.iter().collect();
+            edges.sort_unstable_by_key(|(to, _)| **to);
+            for (to, contacts) in edges {
+                for contact in contacts {
+                    let Interval { start, end } = contact.interval;
+                    write!(writer, "{} {} {} {}", from, to, start, end)?;
+                    write_label(&mut writer, &contact.value)?;
+                    writeln!(writer)?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn deserialize_from(filename: &str) -> Result<TemporalGraph<V, E>, GenericError>
+    where
+        V: Default,
+        E: Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        Self::deserialize_from_reader(Compression::from_extension(filename).open(filename)?)
+    }
+
+    pub fn deserialize_from_reader<R: BufRead>(
+        reader: R,
+    ) -> Result<TemporalGraph<V, E>, GenericError>
+    where
+        V: Default,
+        E: Default,
+        <V as FromStr>::Err: Display,
+        <E as FromStr>::Err: Display,
+    {
+        let mut temporal = TemporalGraph::new();
+        for record in TgfReader::new(reader) {
+            match record? {
+                TgfRecord::Node { id, label } => {
+                    let value = parse_label(&label).map_err(|e| format!("vertex {}: {}", id, e))?;
+                    temporal.insert_node(id, value);
+                }
+                TgfRecord::Edge { from, to, label } => {
+                    let context = |e: String| format!("edge {} -> {}: {}", from, to, e);
+                    let mut parts = label.splitn(3, char::is_whitespace);
+                    let mut bound = || -> Result<Timestamp, String> {
+                        let token = parts.next().unwrap_or_default();
+                        token
+                            .parse()
+                            .map_err(|e| format!("invalid timestamp `{}`: {}", token, e))
+                    };
+                    let interval =
+                        Interval::new(bound().map_err(context)?, bound().map_err(context)?);
+                    let value =
+                        parse_label(parts.next().unwrap_or_default().trim()).map_err(context)?;
+                    temporal.insert_edge(OrientedEdge(from, to), interval, value);
+                }
+            }
+        }
+        Ok(temporal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::temporal::{Interval, TemporalGraph};
+    use crate::*;
+
+    // 1 -> 2 only early, 2 -> 3 only late, 1 -> 3 directly in the middle, 3 -> 4 always
+    fn sample() -> TemporalGraph<String, String> {
+        let mut g = TemporalGraph::new();
+        for v in 1..=4 {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(OrientedEdge(1, 2), Interval::new(0, 5), "a".to_string());
+        g.insert_edge(OrientedEdge(2, 3), Interval::new(8, 10), "b".to_string());
+        g.insert_edge(OrientedEdge(1, 3), Interval::new(20, 30), "c".to_string());
+        g.insert_edge(OrientedEdge(3, 4), Interval::since(0), "d".to_string());
+        g.insert_edge(OrientedEdge(1, 2), Interval::new(40, 50), "e".to_string());
+        g
+    }
+
+    #[test]
+    fn snapshots() {
+        let mut g = sample();
+        assert_eq!((g.edge_count(), g.contact_count()), (4, 5));
+        assert!(!g.insert_edge(OrientedEdge(1, 9), Interval::since(0), String::new()));
+        let early = g.snapshot_at(3);
+        assert_eq!(early.vertex_count(), 4);
+        assert_eq!(early.edge_count(), 2);
+        assert_eq!(early.edge_weight(1, 2).unwrap(), "a");
+        assert_eq!(g.snapshot_at(5).edge_weight(1, 2), None);
+        assert_eq!(g.snapshot_at(45).edge_weight(1, 2).unwrap(), "e");
+        assert_eq!(g.contacts(1, 2).len(), 2);
+        assert!(g.contacts(2, 1).is_empty());
+    }
+
+    #[test]
+    fn contacts_extend_the_edge_in_place() {
+        let mut g = sample();
+        g.graph.set_edge_attr(OrientedEdge(1, 2), "kind", "road");
+        assert_eq!(g.graph.component_count(), 1);
+        let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&log);
+        g.graph.on_change(move |event| {
+            if let GraphEvent::EdgeInserted { from, to, value } = event {
+                sink.lock().unwrap().push((*from, *to, value.len()));
+            } else {
+                sink.lock().unwrap().push((0, 0, 0));
+            }
+        });
+        g.insert_edge(OrientedEdge(1, 2), Interval::new(10, 12), "f".to_string());
+        let starts: Vec<_> = g.contacts(1, 2).iter().map(|c| c.interval.start).collect();
+        assert_eq!(starts, [0, 10, 40]);
+        assert!(g.graph.get_edge_attr(OrientedEdge(1, 2), "kind").is_some());
+        assert_eq!(g.graph.cached_component_count(), Some(1));
+        // a single update, no removal
+        assert_eq!(*log.lock().unwrap(), [(1, 2, 3)]);
+    }
+
+    #[test]
+    fn time_respecting_paths() {
+        let g = sample();
+        let path = g.foremost_path(1, 4, 0).unwrap();
+        assert_eq!(path.vertices(), [1, 2, 3, 4]);
+        assert_eq!(path.weights(), [0, 8, 8]);
+        // too late for 1 -> 2, the direct edge is the only way
+        let path = g.foremost_path(1, 4, 6).unwrap();
+        assert_eq!(
+            (path.vertices(), path.weights()),
+            (&[1, 3, 4][..], &[20, 20][..])
+        );
+        assert_eq!(g.foremost_path(1, 4, 31), None);
+        assert_eq!(g.foremost_path(4, 1, 0), None);
+        let arrivals = g.earliest_arrivals(1, 0);
+        assert_eq!(arrivals[&2], 0);
+        assert_eq!(arrivals[&3], 8);
+
+        let path = g.shortest_path_at(1, 4, 0).unwrap();
+        assert_eq!(
+            (path.vertices(), path.weights()),
+            (&[1, 3, 4][..], &[20, 20][..])
+        );
+        assert_eq!(g.shortest_path_at(2, 4, 9).unwrap().vertices(), [2, 3, 4]);
+        assert_eq!(g.shortest_path_at(2, 4, 10), None);
+        assert_eq!(g.traverse_bfs_at(1, 0), vec![1, 2, 3, 4]);
+        assert_eq!(g.traverse_bfs_at(1, 31), vec![1, 2]);
+        assert_eq!(g.traverse_bfs_at(9, 0), Vec::<VertexId>::new());
+    }
+
+    // arriving late over one hop must not hide an earlier arrival over two
+    #[test]
+    fn later_arrival_doesnt_shadow() {
+        let mut g = TemporalGraph::<(), ()>::new();
+        for v in 1..=4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 3), Interval::new(10, 11), ());
+        g.insert_edge(OrientedEdge(1, 2), Interval::new(0, 1), ());
+        g.insert_edge(OrientedEdge(2, 3), Interval::new(1, 2), ());
+        g.insert_edge(OrientedEdge(3, 4), Interval::new(5, 6), ());
+        let path = g.shortest_path_at(1, 4, 0).unwrap();
+        assert_eq!(path.vertices(), [1, 2, 3, 4]);
+        assert_eq!(path.weights(), [0, 1, 5]);
+        assert_eq!(g.traverse_bfs_at(1, 0), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let g = sample();
+        let mut tgf = Vec::new();
+        g.serialize_into(&mut tgf).unwrap();
+        let text = String::from_utf8(tgf).unwrap();
+        assert!(text.contains("#\n1 2 0 5 a\n1 2 40 50 e\n1 3 20 30 c\n"));
+        let read =
+            TemporalGraph::<String, String>::deserialize_from_reader(text.as_bytes()).unwrap();
+        assert_eq!(read.graph(), g.graph());
+
+        let error =
+            TemporalGraph::<String, String>::deserialize_from_reader("1\n#\n1 1 5 x\n".as_bytes())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "edge 1 -> 1: invalid timestamp `x`: invalid digit found in string"
+        );
+    }
+}
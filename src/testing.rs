@@ -0,0 +1,158 @@
+use crate::{Graph, OrientedEdge, VertexId};
+use proptest::prelude::{any, BoxedStrategy, Strategy};
+
+// random graphs for property tests: proptest strategies and quickcheck Arbitrary; both
+// shrink by removing vertices (with their edges) and edges
+
+// vertices get the ids 0..n, every edge is given by the positions of its endpoints, and edges
+// that refer to a vertex lost while shrinking are dropped
+pub fn graph_strategy<VS, ES>(
+    vertex: VS,
+    edge: ES,
+    max_vertices: usize,
+    max_edges: usize,
+) -> impl Strategy<Value = Graph<VS::Value, ES::Value>>
+where
+    VS: Strategy,
+    ES: Strategy,
+{
+    let bound = max_vertices.max(1);
+    let vertices = proptest::collection::vec(vertex, 0..=max_vertices);
+    let edges = proptest::collection::vec((0..bound, 0..bound, edge), 0..=max_edges);
+    (vertices, edges).prop_map(|(vertices, edges)| {
+        let mut g = Graph::new();
+        for (id, value) in vertices.into_iter().enumerate() {
+            g.insert_node(id as VertexId, value);
+        }
+        for (from, to, value) in edges {
+            g.insert_edge(OrientedEdge(from as VertexId, to as VertexId), value);
+        }
+        g
+    })
+}
+
+impl<V, E> proptest::arbitrary::Arbitrary for Graph<V, E>
+where
+    V: proptest::arbitrary::Arbitrary + 'static,
+    E: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        graph_strategy(any::<V>(), any::<E>(), 16, 48).boxed()
+    }
+}
+
+// up to `size` vertices with sparse ids and about twice as many edges
+impl<V, E> quickcheck::Arbitrary for Graph<V, E>
+where
+    V: quickcheck::Arbitrary,
+    E: quickcheck::Arbitrary,
+{
+    fn arbitrary(gen: &mut quickcheck::Gen) -> Self {
+        let size = gen.size().max(1);
+        // ids spread over four times the size, saturating for sizes near u32::MAX
+        let id_range = u32::try_from(size).unwrap_or(u32::MAX).saturating_mul(4);
+        let vertex_count = (usize::arbitrary(gen) % size.saturating_add(1)).min(id_range as usize);
+        let mut g = Graph::new();
+        let mut ids = Vec::with_capacity(vertex_count);
+        while ids.len() < vertex_count {
+            let id = u32::arbitrary(gen) % id_range;
+            if g.get_vertex_value(id).is_none() {
+                g.insert_node(id, V::arbitrary(gen));
+                ids.push(id);
+            }
+        }
+        if !ids.is_empty() {
+            for _ in 0..usize::arbitrary(gen) % (2 * vertex_count + 1) {
+                let from = *gen.choose(&ids).unwrap();
+                let to = *gen.choose(&ids).unwrap();
+                g.insert_edge(OrientedEdge(from, to), E::arbitrary(gen));
+            }
+        }
+        g
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let mut edges: Vec<(VertexId, VertexId)> = self
+            .adj_list
+            .iter()
+            .flat_map(|(from, adjacency)| adjacency.keys().map(move |to| (*from, *to)))
+            .collect();
+        edges.sort_unstable();
+
+        let graph = self.clone();
+        let without_vertex = ids.into_iter().map(move |id| {
+            let mut g = graph.clone();
+            g.remove_node(id);
+            g
+        });
+        let graph = self.clone();
+        let without_edge = edges.into_iter().map(move |(from, to)| {
+            let mut g = graph.clone();
+            g.remove_edge(OrientedEdge(from, to));
+            g
+        });
+        Box::new(without_vertex.chain(without_edge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::graph_strategy;
+    use crate::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn generated_graphs_are_valid(g in graph_strategy(any::<u8>(), 0..10u32, 8, 20)) {
+            prop_assert!(g.vertex_count() <= 8);
+            prop_assert!(g.edge_count() <= 20);
+            let mut traverse = g.traverse_bfs();
+            traverse.sort();
+            prop_assert_eq!(traverse, (0..g.vertex_count() as VertexId).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn tgf_round_trip(g in any::<Graph<u16, u16>>()) {
+            let mut tgf = Vec::new();
+            g.serialize_into(&mut tgf).unwrap();
+            prop_assert_eq!(Graph::deserialize_from_reader(&tgf[..]).unwrap(), g);
+        }
+    }
+
+    #[test]
+    fn quickcheck_small_sizes() {
+        for size in [0, 1] {
+            let mut gen = quickcheck::Gen::new(size);
+            for _ in 0..20 {
+                let g = <Graph<u8, u8> as quickcheck::Arbitrary>::arbitrary(&mut gen);
+                assert!(g.vertex_count() <= 1);
+                assert!(g.traverse_bfs().iter().all(|id| *id < 4));
+            }
+        }
+    }
+
+    #[test]
+    fn quickcheck_shrinks() {
+        fn degrees_add_up(g: Graph<u8, u8>) -> bool {
+            let ids = g.traverse_bfs();
+            let out: usize = ids.iter().map(|v| g.out_degree(*v).unwrap()).sum();
+            let inc: usize = ids.iter().map(|v| g.in_degree(*v).unwrap()).sum();
+            out == g.edge_count() && inc == g.edge_count()
+        }
+        quickcheck::quickcheck(degrees_add_up as fn(Graph<u8, u8>) -> bool);
+
+        let mut g = Graph::<u8, u8>::new();
+        g.insert_node(1, 0);
+        g.insert_node(2, 0);
+        g.insert_edge(OrientedEdge(1, 2), 0);
+        let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&g).collect();
+        assert_eq!(shrunk.len(), 3);
+        assert_eq!(shrunk[2].edge_count(), 0);
+        assert_eq!(shrunk[2].vertex_count(), 2);
+    }
+}
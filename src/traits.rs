@@ -0,0 +1,173 @@
+use crate::{CsrGraph, Graph, VertexId};
+use std::sync::OnceLock;
+
+// the capabilities algorithms are written against, so they run unchanged on Graph, on a
+// frozen CsrGraph and on subgraph views
+pub trait GraphBase {
+    fn vertex_count(&self) -> usize;
+    fn edge_count(&self) -> usize;
+    fn contains_vertex(&self, vertex: VertexId) -> bool;
+    fn vertex_ids(&self) -> impl Iterator<Item = VertexId> + '_;
+}
+
+// outgoing neighbors, empty for a vertex that doesn't exist
+pub trait Neighbors: GraphBase {
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_;
+}
+
+pub trait EdgeWeights: Neighbors {
+    type Weight;
+
+    fn edges(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &Self::Weight)> + '_;
+    fn edge_weight(&self, from: VertexId, to: VertexId) -> Option<&Self::Weight>;
+}
+
+// a bijection between the vertices and 0..node_bound(), for algorithms that want to keep
+// their state in vectors; Graph ids can be arbitrarily sparse, so Graph and Subgraph sort
+// them on the first call and keep the order until their vertex set changes
+pub trait NodeIndexable: GraphBase {
+    fn node_bound(&self) -> usize;
+    fn index_of(&self, vertex: VertexId) -> Option<usize>;
+    fn vertex_at(&self, index: usize) -> VertexId;
+}
+
+// the ids in ascending order, a vertex's position is its index
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NodeIndex(OnceLock<Vec<VertexId>>);
+
+impl NodeIndex {
+    pub(crate) fn get(&self, ids: impl FnOnce() -> Vec<VertexId>) -> &[VertexId] {
+        self.0.get_or_init(ids)
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.0.take();
+    }
+}
+
+impl<V, E> Graph<V, E> {
+    fn node_index(&self) -> &[VertexId] {
+        self.node_index.get(|| {
+            let mut ids: Vec<VertexId> = self.vertices.keys().copied().collect();
+            ids.sort_unstable();
+            ids
+        })
+    }
+}
+
+impl<V, E> GraphBase for Graph<V, E> {
+    fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn contains_vertex(&self, vertex: VertexId) -> bool {
+        self.vertices.contains_key(&vertex)
+    }
+
+    fn vertex_ids(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.vertices.keys().copied()
+    }
+}
+
+impl<V, E> Neighbors for Graph<V, E> {
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        self.adj_list
+            .get(&vertex)
+            .into_iter()
+            .flat_map(|a| a.keys().copied())
+    }
+}
+
+impl<V, E> EdgeWeights for Graph<V, E> {
+    type Weight = E;
+
+    fn edges(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &E)> + '_ {
+        self.adj_list
+            .get(&vertex)
+            .into_iter()
+            .flat_map(|a| a.iter().map(|(to, value)| (*to, value)))
+    }
+
+    fn edge_weight(&self, from: VertexId, to: VertexId) -> Option<&E> {
+        self.adj_list.get(&from)?.get(&to)
+    }
+}
+
+impl<V, E> NodeIndexable for Graph<V, E> {
+    fn node_bound(&self) -> usize {
+        self.vertices.len()
+    }
+
+    // dense ids 0..n are their own index, anything else is binary searched
+    fn index_of(&self, vertex: VertexId) -> Option<usize> {
+        let ids = self.node_index();
+        match ids.get(vertex as usize) {
+            Some(id) if *id == vertex => Some(vertex as usize),
+            _ => ids.binary_search(&vertex).ok(),
+        }
+    }
+
+    fn vertex_at(&self, index: usize) -> VertexId {
+        self.node_index()[index]
+    }
+}
+
+impl<V, E> GraphBase for CsrGraph<V, E> {
+    fn vertex_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    fn contains_vertex(&self, vertex: VertexId) -> bool {
+        self.index(vertex).is_some()
+    }
+
+    fn vertex_ids(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.ids.iter().copied()
+    }
+}
+
+impl<V, E> Neighbors for CsrGraph<V, E> {
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        self.targets[self.index(vertex).map_or(0..0, |i| self.row(i))]
+            .iter()
+            .map(|t| self.ids[*t as usize])
+    }
+}
+
+impl<V, E> EdgeWeights for CsrGraph<V, E> {
+    type Weight = E;
+
+    fn edges(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &E)> + '_ {
+        let row = self.index(vertex).map_or(0..0, |i| self.row(i));
+        self.targets[row.clone()]
+            .iter()
+            .zip(&self.weights[row])
+            .map(|(t, weight)| (self.ids[*t as usize], weight))
+    }
+
+    fn edge_weight(&self, from: VertexId, to: VertexId) -> Option<&E> {
+        self.get_edge_value(from, to)
+    }
+}
+
+impl<V, E> NodeIndexable for CsrGraph<V, E> {
+    fn node_bound(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn index_of(&self, vertex: VertexId) -> Option<usize> {
+        self.index(vertex)
+    }
+
+    fn vertex_at(&self, index: usize) -> VertexId {
+        self.ids[index]
+    }
+}
@@ -0,0 +1,381 @@
+use crate::{AttrMap, Graph, GraphOp, OrientedEdge, VertexId};
+use std::collections::VecDeque;
+
+// what reverts one mutation: a graph op, or putting back the attributes a vertex or edge
+// had before it was removed
+enum Step<V, E> {
+    Op(GraphOp<V, E>),
+    NodeAttrs(VertexId, AttrMap),
+    EdgeAttrs(VertexId, VertexId, AttrMap),
+}
+
+// mutations made through a transaction, each one records the step that reverts it; values
+// are cloned because the replaced or removed one is returned as usual
+pub struct Transaction<'a, V, E> {
+    graph: &'a mut Graph<V, E>,
+    undo: Vec<Step<V, E>>,
+}
+
+impl<V: Clone, E: Clone> Transaction<'_, V, E> {
+    pub fn graph(&self) -> &Graph<V, E> {
+        self.graph
+    }
+
+    pub fn insert_node(&mut self, vertex_id: VertexId, value: V) -> Option<V> {
+        let previous = self.graph.insert_node(vertex_id, value);
+        self.undo.push(Step::Op(match &previous {
+            Some(value) => GraphOp::InsertNode(vertex_id, value.clone()),
+            None => GraphOp::RemoveNode(vertex_id),
+        }));
+        previous
+    }
+
+    // the edges are removed one by one first, so that reverting brings them back
+    pub fn remove_node(&mut self, vertex_id: VertexId) -> Option<V> {
+        let predecessors: Vec<VertexId> = self
+            .graph
+            .get_predecessors(vertex_id)?
+            .into_iter()
+            .copied()
+            .collect();
+        let adjacents: Vec<VertexId> = self
+            .graph
+            .get_adjacents(vertex_id)?
+            .into_iter()
+            .copied()
+            .collect();
+        for from in predecessors {
+            self.remove_edge(OrientedEdge(from, vertex_id));
+        }
+        for to in adjacents {
+            self.remove_edge(OrientedEdge(vertex_id, to));
+        }
+        // steps are reverted last to first, the attributes come back after their vertex
+        if let Some(attrs) = self.graph.attributes.nodes.get(&vertex_id) {
+            self.undo.push(Step::NodeAttrs(vertex_id, attrs.clone()));
+        }
+        let value = self.graph.remove_node(vertex_id)?;
+        self.undo
+            .push(Step::Op(GraphOp::InsertNode(vertex_id, value.clone())));
+        Some(value)
+    }
+
+    pub fn insert_edge(&mut self, edge: OrientedEdge, value: E) -> Option<E> {
+        let OrientedEdge(from, to) = edge;
+        if self.graph.get_vertex_value(from).is_none() || self.graph.get_vertex_value(to).is_none()
+        {
+            return None;
+        }
+        let previous = self.graph.insert_edge(edge, value);
+        self.undo.push(Step::Op(match &previous {
+            Some(value) => GraphOp::InsertEdge(OrientedEdge(from, to), value.clone()),
+            None => GraphOp::RemoveEdge(OrientedEdge(from, to)),
+        }));
+        previous
+    }
+
+    pub fn remove_edge(&mut self, edge: OrientedEdge) -> Option<E> {
+        let OrientedEdge(from, to) = edge;
+        if let Some(attrs) = self.graph.attributes.edges.get(&(from, to)) {
+            self.undo.push(Step::EdgeAttrs(from, to, attrs.clone()));
+        }
+        let value = self.graph.remove_edge(edge)?;
+        self.undo.push(Step::Op(GraphOp::InsertEdge(
+            OrientedEdge(from, to),
+            value.clone(),
+        )));
+        Some(value)
+    }
+
+    // replaying a step through the transaction records the step that reverts it in turn
+    fn apply(&mut self, step: Step<V, E>) {
+        match step {
+            Step::Op(GraphOp::InsertNode(vertex, value)) => {
+                self.insert_node(vertex, value);
+            }
+            Step::Op(GraphOp::RemoveNode(vertex)) => {
+                self.remove_node(vertex);
+            }
+            Step::Op(GraphOp::InsertEdge(edge, value)) => {
+                self.insert_edge(edge, value);
+            }
+            Step::Op(GraphOp::RemoveEdge(edge)) => {
+                self.remove_edge(edge);
+            }
+            // only ever recorded for a removal, which the step that follows reverts and which
+            // records the attributes again when replayed
+            Step::NodeAttrs(vertex, attrs) => restore_node_attrs(self.graph, vertex, attrs),
+            Step::EdgeAttrs(from, to, attrs) => restore_edge_attrs(self.graph, from, to, attrs),
+        }
+    }
+
+    fn rollback(self) {
+        for step in self.undo.into_iter().rev() {
+            match step {
+                Step::Op(GraphOp::InsertNode(vertex, value)) => {
+                    self.graph.insert_node(vertex, value);
+                }
+                Step::Op(GraphOp::RemoveNode(vertex)) => {
+                    self.graph.remove_node(vertex);
+                }
+                Step::Op(GraphOp::InsertEdge(edge, value)) => {
+                    self.graph.insert_edge(edge, value);
+                }
+                Step::Op(GraphOp::RemoveEdge(edge)) => {
+                    self.graph.remove_edge(edge);
+                }
+                Step::NodeAttrs(vertex, attrs) => restore_node_attrs(self.graph, vertex, attrs),
+                Step::EdgeAttrs(from, to, attrs) => restore_edge_attrs(self.graph, from, to, attrs),
+            }
+        }
+    }
+}
+
+// through the setters, so that observers hear about the attributes coming back
+fn restore_node_attrs<V, E>(graph: &mut Graph<V, E>, vertex: VertexId, attrs: AttrMap) {
+    for (key, value) in attrs {
+        graph.set_node_attr(vertex, key, value);
+    }
+}
+
+fn restore_edge_attrs<V, E>(graph: &mut Graph<V, E>, from: VertexId, to: VertexId, attrs: AttrMap) {
+    for (key, value) in attrs {
+        graph.set_edge_attr(OrientedEdge(from, to), key, value);
+    }
+}
+
+// runs f in a transaction, commits what it did when it returns Ok and returns the undo
+// steps in application order, reverts everything when it returns Err
+fn run<V: Clone, E: Clone, T, Err>(
+    graph: &mut Graph<V, E>,
+    f: impl FnOnce(&mut Transaction<'_, V, E>) -> Result<T, Err>,
+) -> Result<(T, Vec<Step<V, E>>), Err> {
+    let mut tx = Transaction {
+        graph,
+        undo: Vec::new(),
+    };
+    match f(&mut tx) {
+        Ok(result) => Ok((result, tx.undo)),
+        Err(e) => {
+            tx.rollback();
+            Err(e)
+        }
+    }
+}
+
+impl<V: Clone, E: Clone> Graph<V, E> {
+    // all or nothing: the mutations made inside f stay only if it returns Ok
+    pub fn transaction<T, Err>(
+        &mut self,
+        f: impl FnOnce(&mut Transaction<'_, V, E>) -> Result<T, Err>,
+    ) -> Result<T, Err> {
+        run(self, f).map(|(result, _)| result)
+    }
+}
+
+// undo/redo history of the transactions committed through it, at most `depth` of them
+// are kept; the graph is expected not to change behind the journal's back in between
+pub struct Journal<V, E> {
+    depth: usize,
+    undo: VecDeque<Vec<Step<V, E>>>,
+    redo: Vec<Vec<Step<V, E>>>,
+}
+
+impl<V: Clone, E: Clone> Journal<V, E> {
+    pub fn new(depth: usize) -> Journal<V, E> {
+        Journal {
+            depth,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    // like Graph::transaction, a commit that changed something becomes one undo step and
+    // drops whatever could be redone
+    pub fn transaction<T, Err>(
+        &mut self,
+        graph: &mut Graph<V, E>,
+        f: impl FnOnce(&mut Transaction<'_, V, E>) -> Result<T, Err>,
+    ) -> Result<T, Err> {
+        let (result, undo) = run(graph, f)?;
+        if !undo.is_empty() {
+            self.redo.clear();
+            self.push_undo(undo);
+        }
+        Ok(result)
+    }
+
+    fn push_undo(&mut self, undo: Vec<Step<V, E>>) {
+        if self.undo.len() == self.depth {
+            self.undo.pop_front();
+        }
+        if self.depth > 0 {
+            self.undo.push_back(undo);
+        }
+    }
+
+    // reverting a step is itself recorded, which yields the steps to redo it
+    fn replay(graph: &mut Graph<V, E>, steps: Vec<Step<V, E>>) -> Vec<Step<V, E>> {
+        let result: Result<_, ()> = run(graph, |tx| {
+            for step in steps.into_iter().rev() {
+                tx.apply(step);
+            }
+            Ok(())
+        });
+        result.unwrap().1
+    }
+
+    // false when there is nothing to undo
+    pub fn undo(&mut self, graph: &mut Graph<V, E>) -> bool {
+        let Some(steps) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(Self::replay(graph, steps));
+        true
+    }
+
+    pub fn redo(&mut self, graph: &mut Graph<V, E>) -> bool {
+        let Some(steps) = self.redo.pop() else {
+            return false;
+        };
+        let undo = Self::replay(graph, steps);
+        self.push_undo(undo);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn sample() -> Graph<String, u32> {
+        let mut g = Graph::new();
+        for v in 1..=3 {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(OrientedEdge(1, 2), 12);
+        g.insert_edge(OrientedEdge(2, 3), 23);
+        g.insert_edge(OrientedEdge(3, 2), 32);
+        g
+    }
+
+    #[test]
+    fn rollback() {
+        let mut g = sample();
+        let before = g.clone();
+        let result: Result<(), String> = g.transaction(|tx| {
+            tx.insert_node(4, "v4".to_string());
+            tx.insert_node(1, "first".to_string());
+            tx.insert_edge(OrientedEdge(4, 1), 41);
+            tx.insert_edge(OrientedEdge(1, 2), 0);
+            tx.remove_node(2);
+            assert_eq!(tx.graph().edge_count(), 1);
+            Err("abort".to_string())
+        });
+        assert_eq!(result, Err("abort".to_string()));
+        assert_eq!(g, before);
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.in_degree(2), Some(2));
+
+        let removed = g.transaction(|tx| Ok::<_, ()>(tx.remove_node(2)));
+        assert_eq!(removed, Ok(Some("v2".to_string())));
+        assert_eq!(g.edge_count(), 0);
+    }
+
+    #[test]
+    fn attributes_survive_rollback() {
+        let mut g = sample();
+        g.set_node_attr(2, "color", "red");
+        g.set_edge_attr(OrientedEdge(1, 2), "weight", 1.5);
+        g.set_edge_attr(OrientedEdge(2, 3), "kind", "road");
+        let result: Result<(), ()> = g.transaction(|tx| {
+            tx.remove_node(2);
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(g.get_node_attr(2, "color"), Some(&AttrValue::from("red")));
+        assert_eq!(
+            g.get_edge_attr(OrientedEdge(1, 2), "weight"),
+            Some(&AttrValue::Float(1.5))
+        );
+        assert_eq!(
+            g.get_edge_attr(OrientedEdge(2, 3), "kind"),
+            Some(&AttrValue::from("road"))
+        );
+
+        let mut journal = Journal::new(1);
+        journal
+            .transaction(&mut g, |tx| Ok::<_, ()>(tx.remove_edge(OrientedEdge(2, 3))))
+            .unwrap();
+        assert_eq!(g.edge_attrs(OrientedEdge(2, 3)), None);
+        journal.undo(&mut g);
+        assert_eq!(
+            g.get_edge_attr(OrientedEdge(2, 3), "kind"),
+            Some(&AttrValue::from("road"))
+        );
+        journal.redo(&mut g);
+        assert_eq!(g.edge_weight(2, 3), None);
+        assert_eq!(g.edge_attrs(OrientedEdge(2, 3)), None);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut g = sample();
+        let original = g.clone();
+        let mut journal = Journal::new(2);
+        assert!(!journal.undo(&mut g));
+
+        journal
+            .transaction(&mut g, |tx| {
+                tx.remove_node(2);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        let without_2 = g.clone();
+        journal
+            .transaction(&mut g, |tx| {
+                tx.insert_edge(OrientedEdge(3, 1), 31);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        let with_edge = g.clone();
+
+        assert!(journal.undo(&mut g));
+        assert_eq!(g, without_2);
+        assert!(journal.undo(&mut g));
+        assert_eq!(g, original);
+        assert!(!journal.can_undo());
+        assert!(journal.redo(&mut g));
+        assert!(journal.redo(&mut g));
+        assert_eq!(g, with_edge);
+        assert!(!journal.can_redo());
+
+        // a new commit drops the redo steps, the depth drops the oldest undo step
+        assert!(journal.undo(&mut g));
+        journal
+            .transaction(&mut g, |tx| {
+                tx.insert_node(9, "v9".to_string());
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert!(!journal.can_redo());
+        journal
+            .transaction(&mut g, |tx| {
+                tx.remove_node(9);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert!(journal.undo(&mut g));
+        assert!(journal.undo(&mut g));
+        assert!(!journal.undo(&mut g));
+        assert_eq!(g, without_2);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::hash::HashMap;
+use crate::{Graph, VertexId};
+use std::fmt::Display;
+
+// why a graph isn't a tree hanging from the given root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeError {
+    MissingRoot(VertexId),
+    RootHasParent(VertexId),
+    MultipleParents(VertexId),
+    // not reachable from the root, also the case for every vertex on a detached cycle
+    Unreachable(VertexId),
+}
+
+impl Display for TreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeError::MissingRoot(v) => write!(f, "root {} doesn't exist", v),
+            TreeError::RootHasParent(v) => write!(f, "root {} has an incoming edge", v),
+            TreeError::MultipleParents(v) => write!(f, "vertex {} has more than one parent", v),
+            TreeError::Unreachable(v) => write!(f, "vertex {} can't be reached from the root", v),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+// borrowed view of a graph whose edges all point away from the root, every other vertex
+// having exactly one parent; parents, children and depths are derived once up front
+#[derive(Debug, Clone)]
+pub struct Tree<'a, V, E> {
+    graph: &'a Graph<V, E>,
+    root: VertexId,
+    parent: HashMap<VertexId, VertexId>,
+    // sorted by id
+    children: HashMap<VertexId, Vec<VertexId>>,
+    depth: HashMap<VertexId, usize>,
+}
+
+impl<V, E> Graph<V, E> {
+    pub fn as_tree(&self, root: VertexId) -> Result<Tree<'_, V, E>, TreeError> {
+        Tree::new(self, root)
+    }
+}
+
+impl<'a, V, E> Tree<'a, V, E> {
+    pub fn new(graph: &'a Graph<V, E>, root: VertexId) -> Result<Tree<'a, V, E>, TreeError> {
+        if !graph.vertices.contains_key(&root) {
+            return Err(TreeError::MissingRoot(root));
+        }
+        if !graph.incoming[&root].is_empty() {
+            return Err(TreeError::RootHasParent(root));
+        }
+        let mut children = HashMap::default();
+        let mut parent = HashMap::default();
+        let mut depth = HashMap::default();
+        depth.insert(root, 0);
+        let mut stack = vec![root];
+        while let Some(current) = stack.pop() {
+            let mut below: Vec<VertexId> = graph.adj_list[&current].keys().copied().collect();
+            below.sort_unstable();
+            for child in &below {
+                if graph.incoming[child].len() > 1 {
+                    return Err(TreeError::MultipleParents(*child));
+                }
+                parent.insert(*child, current);
+                depth.insert(*child, depth[&current] + 1);
+                stack.push(*child);
+            }
+            children.insert(current, below);
+        }
+        // with one parent each and the root without one, a vertex the walk missed lies on a
+        // cycle or hangs off one
+        if let Some(v) = graph.vertices.keys().find(|v| !depth.contains_key(v)) {
+            return Err(TreeError::Unreachable(*v));
+        }
+        Ok(Tree {
+            graph,
+            root,
+            parent,
+            children,
+            depth,
+        })
+    }
+
+    pub fn graph(&self) -> &'a Graph<V, E> {
+        self.graph
+    }
+
+    pub fn root(&self) -> VertexId {
+        self.root
+    }
+
+    pub fn parent(&self, vertex: VertexId) -> Option<VertexId> {
+        self.parent.get(&vertex).copied()
+    }
+
+    // empty for a leaf and for a vertex that doesn't exist
+    pub fn children(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        self.children.get(&vertex).into_iter().flatten().copied()
+    }
+
+    // from the parent up to the root
+    pub fn ancestors(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        std::iter::successors(self.parent(vertex), |v| self.parent(*v))
+    }
+
+    // the vertex and everything below it in preorder, children in id order
+    pub fn subtree(&self, vertex: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        let mut stack: Vec<VertexId> = self
+            .depth
+            .get(&vertex)
+            .map(|_| vertex)
+            .into_iter()
+            .collect();
+        std::iter::from_fn(move || {
+            let current = stack.pop()?;
+            stack.extend(self.children[&current].iter().rev());
+            Some(current)
+        })
+    }
+
+    pub fn depth(&self, vertex: VertexId) -> Option<usize> {
+        self.depth.get(&vertex).copied()
+    }
+
+    // the depth of the deepest leaf, 0 for a lone root
+    pub fn height(&self) -> usize {
+        self.depth.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn is_leaf(&self, vertex: VertexId) -> bool {
+        self.children.get(&vertex).is_some_and(|c| c.is_empty())
+    }
+
+    pub fn leaves(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.subtree(self.root).filter(|v| self.is_leaf(*v))
+    }
+
+    // a vertex counts as its own ancestor here
+    pub fn lowest_common_ancestor(&self, a: VertexId, b: VertexId) -> Option<VertexId> {
+        let (mut a, mut b) = (a, b);
+        let (mut depth_a, mut depth_b) = (self.depth(a)?, self.depth(b)?);
+        while depth_a > depth_b {
+            a = self.parent[&a];
+            depth_a -= 1;
+        }
+        while depth_b > depth_a {
+            b = self.parent[&b];
+            depth_b -= 1;
+        }
+        while a != b {
+            a = self.parent[&a];
+            b = self.parent[&b];
+        }
+        Some(a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::TreeError;
+    use crate::*;
+
+    //      1
+    //    /   \
+    //   2     3
+    //  / \     \
+    // 4   5     6
+    fn sample() -> Graph<(), ()> {
+        let mut g = Graph::new();
+        for v in 1..=6 {
+            g.insert_node(v, ());
+        }
+        for (from, to) in [(1, 3), (1, 2), (2, 5), (2, 4), (3, 6)] {
+            g.insert_edge(OrientedEdge(from, to), ());
+        }
+        g
+    }
+
+    #[test]
+    fn navigation() {
+        let g = sample();
+        let tree = g.as_tree(1).unwrap();
+        assert_eq!(tree.parent(5), Some(2));
+        assert_eq!(tree.parent(1), None);
+        assert_eq!(tree.children(2).collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(tree.children(9).count(), 0);
+        assert_eq!(tree.ancestors(4).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(tree.subtree(1).collect::<Vec<_>>(), [1, 2, 4, 5, 3, 6]);
+        assert_eq!(tree.subtree(3).collect::<Vec<_>>(), [3, 6]);
+        assert_eq!(tree.subtree(9).count(), 0);
+        assert_eq!(tree.depth(6), Some(2));
+        assert_eq!(tree.height(), 2);
+        assert_eq!(tree.leaves().collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(tree.lowest_common_ancestor(4, 5), Some(2));
+        assert_eq!(tree.lowest_common_ancestor(4, 6), Some(1));
+        assert_eq!(tree.lowest_common_ancestor(2, 5), Some(2));
+        assert_eq!(tree.lowest_common_ancestor(2, 9), None);
+    }
+
+    #[test]
+    fn validation() {
+        let mut g = sample();
+        assert_eq!(g.as_tree(7).err(), Some(TreeError::MissingRoot(7)));
+        assert_eq!(g.as_tree(2).err(), Some(TreeError::RootHasParent(2)));
+        g.insert_node(7, ());
+        assert_eq!(g.as_tree(1).err(), Some(TreeError::Unreachable(7)));
+        g.insert_edge(OrientedEdge(7, 7), ());
+        assert_eq!(g.as_tree(1).err(), Some(TreeError::Unreachable(7)));
+        g.remove_node(7);
+        g.insert_edge(OrientedEdge(3, 5), ());
+        assert_eq!(g.as_tree(1).err(), Some(TreeError::MultipleParents(5)));
+        assert_eq!(
+            TreeError::MultipleParents(5).to_string(),
+            "vertex 5 has more than one parent"
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use crate::hash::HashMap;
+use crate::measure::MinScored;
+use crate::traits::EdgeWeights;
+use crate::visited::VisitedSet;
+use crate::{Graph, Measure, Path, VertexId};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    // from discover_vertex: don't follow the vertex's edges, from examine_edge: skip the edge
+    Prune,
+    // stop the whole traversal
+    Break,
+}
+
+// hooks called by bfs_visit, dfs_visit and dijkstra_visit; every discovered vertex is
+// finished once its edges are done, tree edges are the ones that reach a vertex first (for
+// Dijkstra: that give it a shorter tentative distance)
+pub trait Visitor<E> {
+    fn discover_vertex(&mut self, _vertex: VertexId) -> Control {
+        Control::Continue
+    }
+
+    fn examine_edge(&mut self, _from: VertexId, _to: VertexId, _value: &E) -> Control {
+        Control::Continue
+    }
+
+    fn tree_edge(&mut self, _from: VertexId, _to: VertexId, _value: &E) -> Control {
+        Control::Continue
+    }
+
+    fn finish_vertex(&mut self, _vertex: VertexId) -> Control {
+        Control::Continue
+    }
+}
+
+// the traversals below work on any graph with edge weights and return Break when a hook
+// stopped them, Continue otherwise
+pub fn bfs_visit<G, T>(graph: &G, start: VertexId, visitor: &mut T) -> Control
+where
+    G: EdgeWeights,
+    T: Visitor<G::Weight>,
+{
+    if !graph.contains_vertex(start) {
+        return Control::Continue;
+    }
+    let mut used = VisitedSet::new(graph.vertex_ids());
+    let mut queue = VecDeque::new();
+    used.insert(start);
+    match visitor.discover_vertex(start) {
+        Control::Break => return Control::Break,
+        Control::Prune => return visitor.finish_vertex(start),
+        Control::Continue => queue.push_back(start),
+    }
+    while let Some(current) = queue.pop_front() {
+        for (adjacent, value) in graph.edges(current) {
+            match visitor.examine_edge(current, adjacent, value) {
+                Control::Break => return Control::Break,
+                Control::Prune => continue,
+                Control::Continue => {}
+            }
+            if !used.insert(adjacent) {
+                continue;
+            }
+            if visitor.tree_edge(current, adjacent, value) == Control::Break {
+                return Control::Break;
+            }
+            match visitor.discover_vertex(adjacent) {
+                Control::Break => return Control::Break,
+                Control::Prune => {
+                    if visitor.finish_vertex(adjacent) == Control::Break {
+                        return Control::Break;
+                    }
+                }
+                Control::Continue => queue.push_back(adjacent),
+            }
+        }
+        if visitor.finish_vertex(current) == Control::Break {
+            return Control::Break;
+        }
+    }
+    Control::Continue
+}
+
+pub fn dfs_visit<G, T>(graph: &G, start: VertexId, visitor: &mut T) -> Control
+where
+    G: EdgeWeights,
+    T: Visitor<G::Weight>,
+{
+    if !graph.contains_vertex(start) {
+        return Control::Continue;
+    }
+    dfs_from(
+        graph,
+        start,
+        &mut VisitedSet::new(graph.vertex_ids()),
+        visitor,
+    )
+}
+
+fn dfs_from<G, T>(graph: &G, start: VertexId, used: &mut VisitedSet, visitor: &mut T) -> Control
+where
+    G: EdgeWeights,
+    T: Visitor<G::Weight>,
+{
+    used.insert(start);
+    let mut stack = Vec::new();
+    match visitor.discover_vertex(start) {
+        Control::Break => return Control::Break,
+        Control::Prune => return visitor.finish_vertex(start),
+        Control::Continue => stack.push((start, graph.edges(start))),
+    }
+    while let Some((current, edges)) = stack.last_mut() {
+        let current = *current;
+        let Some((adjacent, value)) = edges.next() else {
+            stack.pop();
+            if visitor.finish_vertex(current) == Control::Break {
+                return Control::Break;
+            }
+            continue;
+        };
+        match visitor.examine_edge(current, adjacent, value) {
+            Control::Break => return Control::Break,
+            Control::Prune => continue,
+            Control::Continue => {}
+        }
+        if !used.insert(adjacent) {
+            continue;
+        }
+        if visitor.tree_edge(current, adjacent, value) == Control::Break {
+            return Control::Break;
+        }
+        match visitor.discover_vertex(adjacent) {
+            Control::Break => return Control::Break,
+            Control::Prune => {
+                if visitor.finish_vertex(adjacent) == Control::Break {
+                    return Control::Break;
+                }
+            }
+            Control::Continue => stack.push((adjacent, graph.edges(adjacent))),
+        }
+    }
+    Control::Continue
+}
+
+// vertices are discovered when their distance is final, the result has the distances of
+// everything settled before the traversal ended; weights are expected to be non-negative,
+// an edge whose sum doesn't fit (or is NaN) is skipped after being examined
+pub fn dijkstra_visit<G, T>(
+    graph: &G,
+    start: VertexId,
+    visitor: &mut T,
+) -> HashMap<VertexId, G::Weight>
+where
+    G: EdgeWeights,
+    G::Weight: Measure,
+    T: Visitor<G::Weight>,
+{
+    let mut settled = HashMap::default();
+    if !graph.contains_vertex(start) {
+        return settled;
+    }
+    let mut tentative: HashMap<VertexId, G::Weight> = HashMap::default();
+    let mut heap = BinaryHeap::new();
+    tentative.insert(start, G::Weight::zero());
+    heap.push(MinScored(G::Weight::zero(), start));
+
+    while let Some(MinScored(dist, current)) = heap.pop() {
+        if settled.contains_key(&current) {
+            continue;
+        }
+        settled.insert(current, dist.clone());
+        match visitor.discover_vertex(current) {
+            Control::Break => return settled,
+            Control::Prune => {
+                if visitor.finish_vertex(current) == Control::Break {
+                    return settled;
+                }
+                continue;
+            }
+            Control::Continue => {}
+        }
+        for (adjacent, value) in graph.edges(current) {
+            match visitor.examine_edge(current, adjacent, value) {
+                Control::Break => return settled,
+                Control::Prune => continue,
+                Control::Continue => {}
+            }
+            let Some(candidate) = dist.checked_add(value) else {
+                continue;
+            };
+            if settled.contains_key(&adjacent)
+                || tentative
+                    .get(&adjacent)
+                    .is_some_and(|d| d.compare(&candidate) != Ordering::Greater)
+            {
+                continue;
+            }
+            tentative.insert(adjacent, candidate.clone());
+            heap.push(MinScored(candidate, adjacent));
+            if visitor.tree_edge(current, adjacent, value) == Control::Break {
+                return settled;
+            }
+        }
+        if visitor.finish_vertex(current) == Control::Break {
+            return settled;
+        }
+    }
+    settled
+}
+
+// the lightest path, None when `to` can't be reached; Dijkstra straight on the graph through
+// the traits, stopping as soon as `to` is settled
+pub fn shortest_path<G>(graph: &G, from: VertexId, to: VertexId) -> Option<Path<G::Weight>>
+where
+    G: EdgeWeights,
+    G::Weight: Measure,
+{
+    // the last tree edge into a vertex is the one its final distance came from
+    struct Parents {
+        target: VertexId,
+        parent: HashMap<VertexId, VertexId>,
+    }
+    impl<E> Visitor<E> for Parents {
+        fn discover_vertex(&mut self, vertex: VertexId) -> Control {
+            if vertex == self.target {
+                Control::Break
+            } else {
+                Control::Continue
+            }
+        }
+
+        fn tree_edge(&mut self, from: VertexId, to: VertexId, _value: &E) -> Control {
+            self.parent.insert(to, from);
+            Control::Continue
+        }
+    }
+
+    let mut parents = Parents {
+        target: to,
+        parent: HashMap::default(),
+    };
+    dijkstra_visit(graph, from, &mut parents).get(&to)?;
+    let mut vertices = vec![to];
+    while *vertices.last().unwrap() != from {
+        vertices.push(parents.parent[vertices.last().unwrap()]);
+    }
+    vertices.reverse();
+    let mut path = Path::new(from);
+    for pair in vertices.windows(2) {
+        path.push(pair[1], graph.edge_weight(pair[0], pair[1])?.clone());
+    }
+    Some(path)
+}
+
+// depth-first counterpart of traverse_bfs, every vertex not reached yet starts a new tree
+pub fn traverse_dfs<G: EdgeWeights>(graph: &G) -> Vec<VertexId> {
+    struct Preorder(Vec<VertexId>);
+    impl<E> Visitor<E> for Preorder {
+        fn discover_vertex(&mut self, vertex: VertexId) -> Control {
+            self.0.push(vertex);
+            Control::Continue
+        }
+    }
+
+    let mut preorder = Preorder(Vec::with_capacity(graph.vertex_count()));
+    let mut used = VisitedSet::new(graph.vertex_ids());
+    for start in graph.vertex_ids() {
+        if !used.contains(start) {
+            dfs_from(graph, start, &mut used, &mut preorder);
+        }
+    }
+    preorder.0
+}
+
+impl<V, E> Graph<V, E> {
+    pub fn bfs_visit<T: Visitor<E>>(&self, start: VertexId, visitor: &mut T) -> Control {
+        bfs_visit(self, start, visitor)
+    }
+
+    pub fn dfs_visit<T: Visitor<E>>(&self, start: VertexId, visitor: &mut T) -> Control {
+        dfs_visit(self, start, visitor)
+    }
+
+    pub fn dijkstra_visit<T: Visitor<E>>(
+        &self,
+        start: VertexId,
+        visitor: &mut T,
+    ) -> HashMap<VertexId, E>
+    where
+        E: Measure,
+    {
+        dijkstra_visit(self, start, visitor)
+    }
+
+    pub fn shortest_path(&self, from: VertexId, to: VertexId) -> Option<Path<E>>
+    where
+        E: Measure,
+    {
+        shortest_path(self, from, to)
+    }
+
+    pub fn traverse_dfs(&self) -> Vec<VertexId> {
+        traverse_dfs(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::HashMap;
+    use crate::visit::{Control, Visitor};
+    use crate::*;
+
+    // records every event, stops at `target` and never follows edges into `blocked`
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        parent: HashMap<VertexId, VertexId>,
+        target: Option<VertexId>,
+        blocked: Option<VertexId>,
+    }
+
+    impl Visitor<u32> for Recorder {
+        fn discover_vertex(&mut self, vertex: VertexId) -> Control {
+            self.events.push(format!("discover {}", vertex));
+            if Some(vertex) == self.target {
+                Control::Break
+            } else {
+                Control::Continue
+            }
+        }
+
+        fn examine_edge(&mut self, _from: VertexId, to: VertexId, _value: &u32) -> Control {
+            if Some(to) == self.blocked {
+                Control::Prune
+            } else {
+                Control::Continue
+            }
+        }
+
+        fn tree_edge(&mut self, from: VertexId, to: VertexId, _value: &u32) -> Control {
+            self.parent.insert(to, from);
+            Control::Continue
+        }
+
+        fn finish_vertex(&mut self, vertex: VertexId) -> Control {
+            self.events.push(format!("finish {}", vertex));
+            Control::Continue
+        }
+    }
+
+    // a chain 1 -> 2 -> 3 -> 4 with a shortcut 1 -> 4 that is heavier than the chain
+    fn sample() -> Graph<(), u32> {
+        let mut g = Graph::new();
+        for v in 1..=4 {
+            g.insert_node(v, ());
+        }
+        g.insert_edge(OrientedEdge(1, 2), 1);
+        g.insert_edge(OrientedEdge(2, 3), 1);
+        g.insert_edge(OrientedEdge(3, 4), 1);
+        g.insert_edge(OrientedEdge(1, 4), 5);
+        g
+    }
+
+    #[test]
+    fn bfs_and_dfs_events() {
+        let g = sample();
+        let mut bfs = Recorder::default();
+        assert_eq!(g.bfs_visit(1, &mut bfs), Control::Continue);
+        assert_eq!(bfs.parent[&4], 1);
+        assert_eq!(bfs.events.first().unwrap(), "discover 1");
+        assert_eq!(bfs.events.len(), 8);
+
+        let mut dfs = Recorder::default();
+        assert_eq!(g.dfs_visit(1, &mut dfs), Control::Continue);
+        assert_eq!(dfs.events.last().unwrap(), "finish 1");
+        // the chain is left before 1 finishes
+        let position = |event: &str| dfs.events.iter().position(|e| e == event).unwrap();
+        assert!(position("finish 2") < position("finish 1"));
+
+        let mut blocked = Recorder {
+            blocked: Some(3),
+            ..Recorder::default()
+        };
+        g.dfs_visit(1, &mut blocked);
+        assert!(!blocked.events.contains(&"discover 3".to_string()));
+        assert_eq!(blocked.parent[&4], 1);
+
+        let mut stopped = Recorder {
+            target: Some(2),
+            ..Recorder::default()
+        };
+        assert_eq!(g.bfs_visit(1, &mut stopped), Control::Break);
+        assert!(!stopped.events.contains(&"finish 1".to_string()));
+    }
+
+    #[test]
+    fn dijkstra() {
+        let g = sample();
+        let mut recorder = Recorder::default();
+        let distances = g.dijkstra_visit(1, &mut recorder);
+        assert_eq!(distances[&4], 3);
+        assert_eq!(recorder.parent[&4], 3);
+        assert_eq!(
+            recorder
+                .events
+                .iter()
+                .filter(|e| e.starts_with("discover"))
+                .count(),
+            4
+        );
+
+        let mut stopped = Recorder {
+            target: Some(3),
+            ..Recorder::default()
+        };
+        let distances = g.dijkstra_visit(1, &mut stopped);
+        assert_eq!(distances.len(), 3);
+        assert!(!distances.contains_key(&4));
+        assert!(g.dijkstra_visit(9, &mut Recorder::default()).is_empty());
+
+        let path = g.shortest_path(1, 4).unwrap();
+        assert_eq!(path.cost(), 3);
+        assert_eq!(Some(path), g.freeze().shortest_path(1, 4));
+        assert_eq!(g.shortest_path(1, 1).unwrap().vertices(), [1]);
+        assert_eq!(g.shortest_path(4, 1), None);
+        assert_eq!(g.shortest_path(1, 9), None);
+    }
+
+    #[test]
+    fn traverse_dfs() {
+        let mut g = sample();
+        g.insert_node(10, ());
+        g.insert_edge(OrientedEdge(10, 3), 0);
+        let mut traverse = g.traverse_dfs();
+        assert_eq!(traverse.len(), 5);
+        traverse.sort();
+        assert_eq!(traverse, vec![1, 2, 3, 4, 10]);
+    }
+
+    #[test]
+    fn generic_backends() {
+        let g = sample();
+        let csr = g.freeze();
+        let mut recorder = Recorder::default();
+        assert_eq!(visit::dijkstra_visit(&csr, 1, &mut recorder)[&4], 3);
+        let sub = Subgraph::induced(&g, [1, 4]);
+        assert_eq!(
+            visit::dijkstra_visit(&sub, 1, &mut Recorder::default())[&4],
+            5
+        );
+        let mut traverse = visit::traverse_dfs(&csr);
+        assert_eq!(traverse.len(), 4);
+        traverse.sort();
+        assert_eq!(traverse, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::hash::HashSet;
+use crate::VertexId;
+
+// a bitset costs max_id / 8 bytes against roughly 8 bytes per vertex for a hash set
+const DENSITY_FACTOR: u64 = 32;
+
+// visited-vertex tracking for traversals, a bitset indexed by id when the ids are dense
+// enough and a hash set otherwise
+pub(crate) enum VisitedSet {
+    Bits(Vec<u64>),
+    Hashed(HashSet<VertexId>),
+}
+
+impl VisitedSet {
+    pub(crate) fn new(vertices: impl Iterator<Item = VertexId>) -> VisitedSet {
+        let (count, max) = vertices.fold((0u64, None), |(count, max), v| {
+            (count + 1, max.max(Some(v)))
+        });
+        match max {
+            Some(max) if (max as u64 + 1) <= count * DENSITY_FACTOR => {
+                VisitedSet::Bits(vec![0; (max as usize) / 64 + 1])
+            }
+            _ => VisitedSet::Hashed(HashSet::default()),
+        }
+    }
+
+    pub(crate) fn contains(&self, vertex: VertexId) -> bool {
+        match self {
+            VisitedSet::Bits(bits) => bits
+                .get(vertex as usize / 64)
+                .is_some_and(|word| word & (1 << (vertex % 64)) != 0),
+            VisitedSet::Hashed(set) => set.contains(&vertex),
+        }
+    }
+
+    // returns whether the vertex wasn't visited before
+    pub(crate) fn insert(&mut self, vertex: VertexId) -> bool {
+        match self {
+            VisitedSet::Bits(bits) => {
+                let index = vertex as usize / 64;
+                if index >= bits.len() {
+                    bits.resize(index + 1, 0);
+                }
+                let mask = 1 << (vertex % 64);
+                let fresh = bits[index] & mask == 0;
+                bits[index] |= mask;
+                fresh
+            }
+            VisitedSet::Hashed(set) => set.insert(vertex),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visited::VisitedSet;
+
+    #[test]
+    fn strategy() {
+        let dense: Vec<u32> = (0..100).collect();
+        assert!(matches!(
+            VisitedSet::new(dense.iter().copied()),
+            VisitedSet::Bits(_)
+        ));
+        let sparse: Vec<u32> = (0..100).map(|v| v * 1000).collect();
+        assert!(matches!(
+            VisitedSet::new(sparse.iter().copied()),
+            VisitedSet::Hashed(_)
+        ));
+        assert!(matches!(
+            VisitedSet::new([].into_iter()),
+            VisitedSet::Hashed(_)
+        ));
+    }
+
+    #[test]
+    fn insert_and_contains() {
+        let ids: Vec<u32> = (0..100).collect();
+        for mut visited in [
+            VisitedSet::new(ids.iter().copied()),
+            VisitedSet::new([].into_iter()),
+        ] {
+            assert!(!visited.contains(63));
+            assert!(visited.insert(63));
+            assert!(!visited.insert(63));
+            assert!(visited.contains(63));
+            assert!(!visited.contains(64));
+            // ids beyond the initial range still work
+            assert!(visited.insert(5000));
+            assert!(visited.contains(5000));
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{Graph, OrientedEdge, VertexId};
+use wasm_bindgen::prelude::*;
+
+// the graph as seen from JavaScript: string vertex labels and integer edge weights, so that
+// shortest paths can be asked for; formats go through strings instead of files
+#[wasm_bindgen(js_name = Graph)]
+#[derive(Default)]
+pub struct JsGraph {
+    graph: Graph<String, u32>,
+}
+
+// a shortest path together with its total weight
+#[wasm_bindgen(js_name = Route)]
+pub struct JsRoute {
+    vertices: Vec<VertexId>,
+    cost: u32,
+}
+
+#[wasm_bindgen(js_class = Route)]
+impl JsRoute {
+    #[wasm_bindgen(getter)]
+    pub fn vertices(&self) -> Vec<VertexId> {
+        self.vertices.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+}
+
+fn js_error(error: crate::GenericError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+#[wasm_bindgen(js_class = Graph)]
+impl JsGraph {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsGraph {
+        JsGraph::default()
+    }
+
+    #[wasm_bindgen(js_name = insertNode)]
+    pub fn insert_node(&mut self, vertex: VertexId, label: String) -> Option<String> {
+        self.graph.insert_node(vertex, label)
+    }
+
+    #[wasm_bindgen(js_name = removeNode)]
+    pub fn remove_node(&mut self, vertex: VertexId) -> Option<String> {
+        self.graph.remove_node(vertex)
+    }
+
+    #[wasm_bindgen(js_name = insertEdge)]
+    pub fn insert_edge(&mut self, from: VertexId, to: VertexId, weight: u32) -> Option<u32> {
+        self.graph.insert_edge(OrientedEdge(from, to), weight)
+    }
+
+    #[wasm_bindgen(js_name = removeEdge)]
+    pub fn remove_edge(&mut self, from: VertexId, to: VertexId) -> Option<u32> {
+        self.graph.remove_edge(OrientedEdge(from, to))
+    }
+
+    #[wasm_bindgen(getter, js_name = vertexCount)]
+    pub fn vertex_count(&self) -> usize {
+        self.graph.vertex_count()
+    }
+
+    #[wasm_bindgen(getter, js_name = edgeCount)]
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn label(&self, vertex: VertexId) -> Option<String> {
+        self.graph.get_vertex_value(vertex).cloned()
+    }
+
+    // neighbors in ascending id order
+    pub fn adjacents(&self, vertex: VertexId) -> Option<Vec<VertexId>> {
+        let mut adjacents: Vec<VertexId> = self
+            .graph
+            .get_adjacents(vertex)?
+            .into_iter()
+            .copied()
+            .collect();
+        adjacents.sort_unstable();
+        Some(adjacents)
+    }
+
+    pub fn bfs(&self) -> Vec<VertexId> {
+        self.graph.traverse_bfs()
+    }
+
+    // vertices of the lightest path including both ends, undefined when there is none
+    #[wasm_bindgen(js_name = shortestPath)]
+    pub fn shortest_path(&self, from: VertexId, to: VertexId) -> Option<Vec<VertexId>> {
+        Some(self.graph.shortest_path(from, to)?.into_vertices())
+    }
+
+    #[wasm_bindgen(js_name = shortestDistance)]
+    pub fn shortest_distance(&self, from: VertexId, to: VertexId) -> Option<u32> {
+        Some(self.graph.shortest_path(from, to)?.cost())
+    }
+
+    // both of the above from a single search
+    #[wasm_bindgen(js_name = shortestRoute)]
+    pub fn shortest_route(&self, from: VertexId, to: VertexId) -> Option<JsRoute> {
+        let path = self.graph.shortest_path(from, to)?;
+        Some(JsRoute {
+            cost: path.cost(),
+            vertices: path.into_vertices(),
+        })
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        self.graph.to_cytoscape_json()
+    }
+
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<JsGraph, JsError> {
+        let graph = Graph::from_cytoscape_json(json).map_err(js_error)?;
+        Ok(JsGraph { graph })
+    }
+
+    #[wasm_bindgen(js_name = toTgf)]
+    pub fn to_tgf(&self) -> Result<String, JsError> {
+        let mut tgf = Vec::new();
+        self.graph.serialize_into(&mut tgf).map_err(js_error)?;
+        Ok(String::from_utf8(tgf)?)
+    }
+
+    #[wasm_bindgen(js_name = fromTgf)]
+    pub fn from_tgf(tgf: &[u8]) -> Result<JsGraph, JsError> {
+        let graph = Graph::deserialize_from_reader(tgf).map_err(js_error)?;
+        Ok(JsGraph { graph })
+    }
+}
+
+// JsError can only be created on a wasm target, so only the successful paths are checked here
+#[cfg(test)]
+mod tests {
+    use crate::wasm::JsGraph;
+
+    #[test]
+    fn round_trip() {
+        let mut g = JsGraph::new();
+        for v in 1..=4 {
+            g.insert_node(v, format!("v{}", v));
+        }
+        g.insert_edge(1, 2, 5);
+        g.insert_edge(1, 3, 1);
+        g.insert_edge(3, 2, 1);
+        assert_eq!(g.shortest_path(1, 2), Some(vec![1, 3, 2]));
+        assert_eq!(g.shortest_distance(1, 2), Some(2));
+        assert_eq!(g.shortest_path(1, 4), None);
+        let route = g.shortest_route(1, 2).unwrap();
+        assert_eq!((route.vertices(), route.cost()), (vec![1, 3, 2], 2));
+        assert!(g.shortest_route(1, 4).is_none());
+        assert_eq!(g.adjacents(1), Some(vec![2, 3]));
+
+        let json = JsGraph::from_json(&g.to_json()).ok().unwrap();
+        assert_eq!(json.graph, g.graph);
+        let tgf = JsGraph::from_tgf(g.to_tgf().ok().unwrap().as_bytes())
+            .ok()
+            .unwrap();
+        assert_eq!(tgf.graph, g.graph);
+        assert_eq!(tgf.label(4), Some("v4".to_string()));
+    }
+}